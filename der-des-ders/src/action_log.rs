@@ -0,0 +1,230 @@
+#[cfg(test)]
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Input;
+#[cfg(test)]
+use crate::{run_turn, GameEngine, Output, Player, Players};
+
+/// Something which happened during a game, in the order it happened
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum LoggedAction {
+    /// An input read from a player, whether the game accepted it or not
+    Input(Input),
+    /// The value of a die rolled
+    Roll(u8),
+}
+
+/// Every input and die roll of a game, enough to replay it from its seed, see
+/// `GameEngine::from_log`.
+/// Only the actual game is logged: as hooks, the log is not carried over when the
+/// engine is cloned, e.g. when the robot explores possible moves.
+#[derive(Debug, Default)]
+pub struct ActionLog(Vec<LoggedAction>);
+
+impl Clone for ActionLog {
+    fn clone(&self) -> Self {
+        ActionLog::default()
+    }
+}
+
+impl ActionLog {
+    pub fn record(&mut self, action: LoggedAction) {
+        self.0.push(action)
+    }
+
+    pub fn actions(&self) -> &[LoggedAction] {
+        &self.0
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[cfg(test)]
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len)
+    }
+}
+
+/// Dice rolled by a state since its engine last moved them to its action log, if the
+/// state's dice are logged at all.
+/// They are not part of the game: they are neither saved nor compared, and clones of
+/// the state do not log their dice.
+#[derive(Debug, Default)]
+pub struct RolledDice(Option<Vec<u8>>);
+
+impl RolledDice {
+    pub fn logged() -> Self {
+        RolledDice(Some(Vec::new()))
+    }
+
+    pub fn push(&mut self, die: u8) {
+        if let Some(dice) = &mut self.0 {
+            dice.push(die);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn dice(&self) -> &[u8] {
+        self.0.as_deref().unwrap_or(&[])
+    }
+
+    pub fn take(&mut self) -> Vec<u8> {
+        self.0.as_mut().map(std::mem::take).unwrap_or_default()
+    }
+}
+
+impl Clone for RolledDice {
+    fn clone(&self) -> Self {
+        RolledDice::default()
+    }
+}
+
+impl PartialEq for RolledDice {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for RolledDice {}
+
+/// Plays the inputs of a log, whichever side they came from: replaying from the same
+/// seed, inputs are asked for in the same order. Once the log is exhausted, the side
+/// asked for an input resigns so that the replay of a truncated log ends.
+#[cfg(test)]
+pub struct LoggedInputs(pub Rc<RefCell<VecDeque<Input>>>);
+
+#[cfg(test)]
+impl Player for LoggedInputs {
+    fn output(&mut self, _message: &Output, _engine: &GameEngine) {}
+
+    fn input(&mut self) -> Input {
+        self.0.borrow_mut().pop_front().unwrap_or(Input::Resign)
+    }
+
+    fn out(&self) -> Vec<Output> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+impl GameEngine {
+    /// Replay a game started with `GameEngine::new(seed)` from its log, turn by turn
+    /// until the log's inputs are all played
+    pub fn from_log(seed: u64, log: &[LoggedAction]) -> GameEngine {
+        let inputs: VecDeque<Input> = log
+            .iter()
+            .filter_map(|action| match action {
+                LoggedAction::Input(input) => Some(input.clone()),
+                LoggedAction::Roll(_) => None,
+            })
+            .collect();
+        let inputs = Rc::new(RefCell::new(inputs));
+        let mut players = Players {
+            allies_player: Box::new(LoggedInputs(inputs.clone())),
+            empires_player: Box::new(LoggedInputs(inputs.clone())),
+        };
+        let mut engine = GameEngine::new(seed);
+        while !engine.game_ends() && !inputs.borrow().is_empty() {
+            run_turn(&mut players, &mut engine);
+        }
+        engine
+    }
+}
+
+#[cfg(test)]
+mod action_log_tests {
+    use super::LoggedAction;
+    use crate::{
+        fixtures::{EngineBuilder, PlayerDouble},
+        minimax::Robot,
+        run_turn, GameEngine, Input, Phase, Players,
+        Side::*,
+    };
+
+    #[test]
+    fn replaying_a_log_reproduces_the_game() {
+        let mut engine = GameEngine::new(42);
+        let mut players = Players {
+            allies_player: Box::new(Robot::new(Allies, 1)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+        for _ in 0..3 {
+            run_turn(&mut players, &mut engine);
+        }
+
+        let replayed = GameEngine::from_log(42, &engine.replay_log());
+
+        assert_eq!(engine.state, replayed.state);
+    }
+
+    #[test]
+    fn replaying_a_truncated_log_ends_with_a_resignation() {
+        let mut engine = GameEngine::new(42);
+        let mut players = Players {
+            allies_player: Box::new(Robot::new(Allies, 1)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+        for _ in 0..3 {
+            run_turn(&mut players, &mut engine);
+        }
+        let mut log = engine.replay_log();
+        let last_input = log
+            .iter()
+            .rposition(|action| matches!(action, LoggedAction::Input(_)))
+            .unwrap();
+        log.truncate(last_input);
+
+        let replayed = GameEngine::from_log(42, &log);
+
+        assert!(replayed.game_ends());
+        assert!(
+            replayed.state.explain_winner().contains("resigned"),
+            "{}",
+            replayed.state.explain_winner()
+        );
+    }
+
+    #[test]
+    fn dice_rolled_by_clones_are_not_logged() {
+        let mut engine = GameEngine::new(42);
+
+        engine.clone().roll();
+        assert!(engine.replay_log().is_empty());
+
+        let die = engine.roll();
+        assert_eq!(vec![LoggedAction::Roll(die)], engine.replay_log());
+    }
+
+    #[test]
+    fn inputs_read_by_advance_to_are_logged() {
+        let mut engine = EngineBuilder::new(11).on_turn(2).build();
+        engine.set_phase(Phase::Initiative(Allies));
+        let mut allies = PlayerDouble {
+            out: vec![],
+            inp: vec![Input::Number(0)],
+        };
+        let mut empires = PlayerDouble {
+            out: vec![],
+            inp: vec![Input::Number(1)],
+        };
+
+        engine.advance_to(Phase::DrawEvents, &mut allies, &mut empires);
+
+        let inputs: Vec<LoggedAction> = engine
+            .replay_log()
+            .into_iter()
+            .filter(|action| matches!(action, LoggedAction::Input(_)))
+            .collect();
+        assert_eq!(
+            vec![
+                LoggedAction::Input(Input::Number(0)),
+                LoggedAction::Input(Input::Number(1))
+            ],
+            inputs
+        );
+    }
+}
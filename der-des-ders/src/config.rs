@@ -0,0 +1,218 @@
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{side::Nation, tech::TechnologyType};
+
+/// Rules variants which can be enabled for a game.
+///
+/// The default configuration plays the standard rules. Options missing when
+/// deserializing take their default value.
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    /// Nations whose breakdown is at or below this level are too exhausted to
+    /// launch offensives, but can still be reinforced. 0 disables the rule.
+    pub exhaustion_threshold: u8,
+    /// What happens to hits in excess of what's needed for a nation to surrender
+    pub overkill_policy: OverkillPolicy,
+    /// Record resources collected by each side every turn, see `WarState::collected`
+    pub record_resources: bool,
+    /// Number of faces of the dice rolled, 6 by default.
+    /// Success thresholds (attack factors, technology unlocks, sea tables) are expressed
+    /// for a d6 and are not scaled: bigger dice make rolls above a threshold more likely.
+    pub die_faces: u8,
+    /// Minimum number of PR a side must bid to contest initiative, a side bidding less
+    /// concedes it. When both sides concede, initiative is rolled without bids.
+    pub min_initiative_bid: u8,
+    /// Changes to the standard adjacency of nations, e.g. to model difficult terrain
+    pub adjacency_overrides: Vec<AdjacencyOverride>,
+    /// Prompt both sides for their initiative bid before reading either, and reveal bids
+    /// once both are in. Not suitable for hotseat play where both sides share a keyboard.
+    pub simultaneous_initiative: bool,
+    /// Turns on which sides collect their resources
+    pub collect_on: CollectOn,
+    /// JSON file replacing the built-in countries table, see `countries::CountryData`
+    pub countries_path: Option<PathBuf>,
+    /// Turns during which sides mobilize and can stockpile more than `max_resources`.
+    /// Excess PR is lost on the first increase after the window.
+    pub mobilization_window: Option<RangeInclusive<u8>>,
+    /// Last turn played, 14 by default. Games capped earlier are scored as if they had
+    /// reached the end of the war.
+    pub max_turns: u8,
+    /// Victory points a side needs before a surrender can win it the game: the side
+    /// wins when it has at least this many VP and the sudden death roll is lower than
+    /// its VP. 0, the default, lets any surrender decide the game.
+    pub victory_threshold: u8,
+    /// Offensives assigned more PR than their side has spend all it has instead of
+    /// being rejected
+    pub clamp_offensive_pr: bool,
+    /// Most PR a side can stockpile, 20 by default. PR in excess are lost.
+    pub max_resources: u8,
+    /// Surrenders can win the game on a sudden death roll, see `victory_threshold`.
+    /// When disabled, games always run to the turn limit.
+    pub sudden_death: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            exhaustion_threshold: 0,
+            overkill_policy: OverkillPolicy::Ignore,
+            record_resources: false,
+            die_faces: 6,
+            min_initiative_bid: 0,
+            adjacency_overrides: Vec::new(),
+            simultaneous_initiative: false,
+            collect_on: CollectOn::EveryTurn,
+            countries_path: None,
+            mobilization_window: None,
+            max_turns: 14,
+            victory_threshold: 0,
+            clamp_offensive_pr: false,
+            max_resources: 20,
+            sudden_death: true,
+        }
+    }
+}
+
+impl GameConfig {
+    /// List all rules options with their value, one per line, so that a game's rules
+    /// can be recorded in a human readable form
+    #[cfg(test)]
+    pub fn describe(&self) -> String {
+        let countries = match &self.countries_path {
+            Some(path) => path.display().to_string(),
+            None => "built-in".to_string(),
+        };
+        [
+            format!("exhaustion_threshold: {}", self.exhaustion_threshold),
+            format!("overkill_policy: {:?}", self.overkill_policy),
+            format!("record_resources: {}", self.record_resources),
+            format!("die_faces: {}", self.die_faces),
+            format!("min_initiative_bid: {}", self.min_initiative_bid),
+            format!("adjacency_overrides: {:?}", self.adjacency_overrides),
+            format!("simultaneous_initiative: {}", self.simultaneous_initiative),
+            format!("collect_on: {:?}", self.collect_on),
+            format!("countries: {}", countries),
+            format!("mobilization_window: {:?}", self.mobilization_window),
+            format!("max_turns: {}", self.max_turns),
+            format!("victory_threshold: {}", self.victory_threshold),
+            format!("clamp_offensive_pr: {}", self.clamp_offensive_pr),
+            format!("max_resources: {}", self.max_resources),
+            format!("sudden_death: {}", self.sudden_death),
+        ]
+        .join("\n")
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Default, Serialize, Deserialize)]
+pub enum CollectOn {
+    /// Resources are collected every turn
+    #[default]
+    EveryTurn,
+    /// Resources are collected on the first turn of each year
+    YearStart,
+    /// Resources are only collected on the listed turns
+    Turns(Vec<u8>),
+}
+
+impl CollectOn {
+    /// Whether resources are collected on the given turn
+    pub fn collects_on(&self, turn: u8) -> bool {
+        match self {
+            CollectOn::EveryTurn => true,
+            CollectOn::YearStart => matches!(turn, 1 | 2 | 5 | 8 | 11 | 14),
+            CollectOn::Turns(turns) => turns.contains(&turn),
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum OverkillPolicy {
+    /// Excess hits are lost
+    #[default]
+    Ignore,
+    /// Each excess hit gives 1 VP to the side inflicting them, before the surrender's
+    /// sudden death roll
+    VictoryPoints,
+}
+
+/// Overrides the adjacency of two nations, in both directions
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct AdjacencyOverride {
+    pub between: (Nation, Nation),
+    pub adjacency: Adjacency,
+}
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Adjacency {
+    /// Nations cannot attack each other
+    Never,
+    /// Nations can always attack each other
+    Always,
+    /// Nations can attack each other once the attacking side has reached the given level
+    /// of the technology
+    WithTechnology(TechnologyType, u8),
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+    use crate::Nation::*;
+
+    fn custom_config() -> GameConfig {
+        GameConfig {
+            exhaustion_threshold: 2,
+            overkill_policy: OverkillPolicy::VictoryPoints,
+            record_resources: true,
+            die_faces: 8,
+            min_initiative_bid: 1,
+            adjacency_overrides: vec![AdjacencyOverride {
+                between: (Italy, Bulgaria),
+                adjacency: Adjacency::WithTechnology(TechnologyType::Air, 2),
+            }],
+            simultaneous_initiative: true,
+            collect_on: CollectOn::Turns(vec![1, 3]),
+            countries_path: Some(PathBuf::from("countries.json")),
+            mobilization_window: Some(2..=4),
+            max_turns: 10,
+            victory_threshold: 5,
+            clamp_offensive_pr: true,
+            max_resources: 15,
+            sudden_death: false,
+        }
+    }
+
+    #[test]
+    fn config_is_identical_after_serialization_round_trip() {
+        let config = custom_config();
+
+        let json = serde_json::to_string(&config).unwrap();
+
+        assert_eq!(config, serde_json::from_str::<GameConfig>(&json).unwrap());
+    }
+
+    #[test]
+    fn missing_options_take_their_default_value() {
+        let config: GameConfig = serde_json::from_str(r#"{ "die_faces": 8 }"#).unwrap();
+
+        assert_eq!(
+            GameConfig {
+                die_faces: 8,
+                ..GameConfig::default()
+            },
+            config
+        );
+    }
+
+    #[test]
+    fn description_lists_every_option() {
+        let description = custom_config().describe();
+
+        assert_eq!(15, description.lines().count());
+        assert!(description.contains("die_faces: 8"));
+        assert!(description.contains("countries: countries.json"));
+    }
+}
@@ -0,0 +1,208 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::config::{Adjacency, AdjacencyOverride};
+use crate::side::{Country, Nation, NationState, Side};
+
+/// A nation's entry in a custom countries file
+#[derive(Deserialize, Eq, PartialEq, Clone, Debug)]
+pub struct CountryData {
+    pub nation: Nation,
+    pub side: Side,
+    pub resources: u8,
+    pub vp: u8,
+    pub attack_factor: u8,
+    pub max_breakdown: u8,
+    pub max_tech_level: u8,
+    /// Whether the nation starts the game at war, at its maximum breakdown
+    pub at_war: bool,
+    /// Nations this one can attack, which must list this one in return
+    pub adjacent: Vec<Nation>,
+}
+
+/// Countries loaded from a file, replacing the built-in `COUNTRIES` table
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct CountryTable {
+    pub countries: HashMap<Nation, Country>,
+    pub nations: HashMap<Nation, NationState>,
+    /// Adjacency of every pair of loaded nations, expressed as overrides of the
+    /// standard map
+    pub adjacency: Vec<AdjacencyOverride>,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum CountriesError {
+    /// The file could not be read
+    Io(String),
+    /// The file is not a valid list of countries
+    Parse(String),
+    /// A nation is listed more than once
+    Duplicate(Nation),
+    /// A nation is adjacent to a nation missing from the table
+    UnknownNeighbour(Nation, Nation),
+    /// A nation is adjacent to another which is not adjacent to it
+    Asymmetric(Nation, Nation),
+}
+
+impl Display for CountriesError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            CountriesError::Io(err) => write!(f, "cannot read countries: {}", err),
+            CountriesError::Parse(err) => write!(f, "cannot parse countries: {}", err),
+            CountriesError::Duplicate(nation) => write!(f, "{} is listed twice", nation),
+            CountriesError::UnknownNeighbour(nation, neighbour) => {
+                write!(f, "{} is adjacent to unlisted {}", nation, neighbour)
+            }
+            CountriesError::Asymmetric(nation, neighbour) => {
+                write!(
+                    f,
+                    "{} is adjacent to {} but not the other way round",
+                    nation, neighbour
+                )
+            }
+        }
+    }
+}
+
+/// Load a JSON list of `CountryData` from the given file
+pub fn load_countries(path: &Path) -> Result<CountryTable, CountriesError> {
+    let content = std::fs::read_to_string(path).map_err(|e| CountriesError::Io(e.to_string()))?;
+    parse_countries(&content)
+}
+
+/// Parse and validate a JSON list of `CountryData`
+pub fn parse_countries(content: &str) -> Result<CountryTable, CountriesError> {
+    let data: Vec<CountryData> =
+        serde_json::from_str(content).map_err(|e| CountriesError::Parse(e.to_string()))?;
+    validate_countries(&data)?;
+
+    let countries = data
+        .iter()
+        .map(|d| {
+            (
+                d.nation,
+                Country {
+                    nation: d.nation,
+                    side: d.side,
+                    max_tech_level: d.max_tech_level,
+                    resources: d.resources,
+                    attack_factor: d.attack_factor,
+                    max_breakdown: d.max_breakdown,
+                    vp: d.vp,
+                },
+            )
+        })
+        .collect();
+    let nations = data
+        .iter()
+        .map(|d| {
+            let state = if d.at_war {
+                NationState::AtWar(d.max_breakdown)
+            } else {
+                NationState::AtPeace
+            };
+            (d.nation, state)
+        })
+        .collect();
+    let mut adjacency = Vec::new();
+    for (index, from) in data.iter().enumerate() {
+        for to in &data[index + 1..] {
+            adjacency.push(AdjacencyOverride {
+                between: (from.nation, to.nation),
+                adjacency: if from.adjacent.contains(&to.nation) {
+                    Adjacency::Always
+                } else {
+                    Adjacency::Never
+                },
+            });
+        }
+    }
+    Ok(CountryTable {
+        countries,
+        nations,
+        adjacency,
+    })
+}
+
+/// Check nations are listed once and adjacency is symmetric
+fn validate_countries(data: &[CountryData]) -> Result<(), CountriesError> {
+    let by_nation: HashMap<Nation, &CountryData> = data.iter().map(|d| (d.nation, d)).collect();
+    if by_nation.len() != data.len() {
+        let duplicate = data
+            .iter()
+            .enumerate()
+            .find(|(index, d)| data[..*index].iter().any(|o| o.nation == d.nation))
+            .map(|(_, d)| d.nation)
+            .unwrap();
+        return Err(CountriesError::Duplicate(duplicate));
+    }
+    for country in data {
+        for neighbour in &country.adjacent {
+            match by_nation.get(neighbour) {
+                None => {
+                    return Err(CountriesError::UnknownNeighbour(country.nation, *neighbour));
+                }
+                Some(other) if !other.adjacent.contains(&country.nation) => {
+                    return Err(CountriesError::Asymmetric(country.nation, *neighbour));
+                }
+                Some(_) => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod countries_tests {
+    use super::*;
+    use crate::{
+        fixtures::EngineBuilder,
+        GameConfig,
+        Nation::*,
+        Side::{Allies, Empires},
+    };
+
+    const TWO_NATIONS: &str = r#"[
+        { "nation": "France", "side": "Allies", "resources": 5, "vp": 3,
+          "attack_factor": 4, "max_breakdown": 6, "max_tech_level": 7,
+          "at_war": true, "adjacent": ["Germany"] },
+        { "nation": "Germany", "side": "Empires", "resources": 2, "vp": 3,
+          "attack_factor": 3, "max_breakdown": 6, "max_tech_level": 7,
+          "at_war": true, "adjacent": ["France"] }
+    ]"#;
+
+    #[test]
+    fn game_uses_countries_loaded_from_file() {
+        let path = std::env::temp_dir().join("der-des-ders-two-nations.json");
+        std::fs::write(&path, TWO_NATIONS).unwrap();
+        let mut engine = EngineBuilder::new(11)
+            .with_config(GameConfig {
+                countries_path: Some(path),
+                ..GameConfig::default()
+            })
+            .build();
+
+        engine.state.load_countries().unwrap();
+        engine.collect_resources();
+
+        assert_eq!(5, engine.state.resources_for(&Allies));
+        assert_eq!(2, engine.state.resources_for(&Empires));
+        assert!(engine.state.adjacent(&France, &Germany));
+        assert_eq!(vec![&Germany], engine.state.neighbours(&France));
+        assert_eq!(vec![France], engine.state.all_nations_at_war(Allies));
+    }
+
+    #[test]
+    fn asymmetric_adjacency_is_rejected() {
+        let asymmetric = TWO_NATIONS.replace(r#""adjacent": ["France"]"#, r#""adjacent": []"#);
+
+        assert_eq!(
+            Err(CountriesError::Asymmetric(France, Germany)),
+            parse_countries(&asymmetric)
+        );
+    }
+}
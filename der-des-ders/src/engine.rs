@@ -1,49 +1,294 @@
+use crate::action_log::{ActionLog, LoggedAction};
+use crate::config::OverkillPolicy;
 use crate::event::Event;
 use crate::event::ARMISTICE;
 use crate::events::*;
 use crate::logic::*;
+use crate::save::{load_game, SaveError};
 use crate::side::*;
-use crate::state::StateChange::*;
 use crate::state::*;
+use crate::technologies_track;
+use crate::turn_log::TurnLog;
 use crate::TechEffects;
 use crate::TechnologyType;
-use crate::ALLIES_TECHNOLOGIES;
 use crate::DEFAULT_INITIATIVE;
-use crate::EMPIRE_TECHNOLOGIES;
+use crate::{Input, Output, Player, TRENCH_WARFARE_LEVEL};
+use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::fmt::Debug;
 use std::mem::swap;
+use std::path::Path;
+
+/// Russia leaves the war once the Russian revolution track reaches this level
+pub const RUSSIAN_REVOLUTION_LIMIT: u8 = 6;
 
 #[derive(Debug, Clone)]
 pub struct GameEngine {
     pub(crate) state: GameState,
     logic: Box<dyn GameLogic>,
-    played_events: Vec<ActiveEvent>,
+    turn_end_hooks: TurnEndHooks,
+    output_hooks: OutputHooks,
+    turn_log: TurnLog,
+    /// States at the start of each turn played, the last one being the turn being
+    /// played, only kept once enabled with `enable_history`
+    history: Option<Vec<GameState>>,
+    /// Changes applied with `apply_change` during the current turn, most recent last,
+    /// see `undo`
+    applied_changes: Vec<StateChange>,
+    action_log: ActionLog,
+}
+
+type TurnEndHook = Box<dyn FnMut(&GameState)>;
+
+/// Callbacks invoked once a new turn has started.
+/// Hooks are not carried over when the engine is cloned, e.g. when the robot
+/// explores possible moves, so that they only fire for the actual game.
+#[derive(Default)]
+struct TurnEndHooks(Vec<TurnEndHook>);
+
+impl Clone for TurnEndHooks {
+    fn clone(&self) -> Self {
+        TurnEndHooks::default()
+    }
+}
+
+impl Debug for TurnEndHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TurnEndHooks({})", self.0.len())
+    }
+}
+
+type OutputHook = Box<dyn FnMut(&Output)>;
+
+/// Callbacks invoked with every output sent to both players.
+/// Outputs are sent while the engine is only borrowed, hence the `RefCell`. As turn
+/// end hooks, they are not carried over when the engine is cloned.
+#[derive(Default)]
+struct OutputHooks(RefCell<Vec<OutputHook>>);
+
+impl Clone for OutputHooks {
+    fn clone(&self) -> Self {
+        OutputHooks::default()
+    }
+}
+
+impl Debug for OutputHooks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "OutputHooks({})", self.0.borrow().len())
+    }
 }
 
 impl GameEngine {
     pub fn new(seed: u64) -> Self {
-        GameEngine {
-            state: GameState::new(seed),
-            logic: Box::new(default_game_logic()),
-            played_events: Vec::new(),
-        }
+        GameEngine::from_state(GameState::new(seed))
     }
 
-    pub fn with_state(state: GameState) -> GameEngine {
+    /// Wraps an existing state, e.g. a loaded game.
+    /// The game logic is rebuilt from the events still active, layered in the order
+    /// they were played, and the engine starts with no turn end hooks.
+    pub fn from_state(mut state: GameState) -> GameEngine {
+        state.log_dice();
+        let mut logic: Box<dyn GameLogic> = Box::new(default_game_logic());
+        for active_event in state.activations() {
+            if let Some(layer) = active_event.layer {
+                logic = layer.wrap(logic);
+            }
+            logic.event_activated(active_event);
+        }
         GameEngine {
+            turn_log: TurnLog::new(&state),
             state,
-            logic: Box::new(default_game_logic()),
-            played_events: Vec::new(),
+            logic,
+            turn_end_hooks: TurnEndHooks::default(),
+            output_hooks: OutputHooks::default(),
+            history: None,
+            applied_changes: Vec::new(),
+            action_log: ActionLog::default(),
+        }
+    }
+
+    /// Save the game to the given file, along with its dice and configuration
+    #[cfg(test)]
+    pub fn save(&self, path: &Path) -> Result<(), SaveError> {
+        crate::save::save_game(&self.state, path)
+    }
+
+    /// Load a game saved with `save`, see `from_state` for how its logic is rebuilt
+    pub fn load(path: &Path) -> Result<GameEngine, SaveError> {
+        load_game(path).map(GameEngine::from_state)
+    }
+
+    /// Save the game under the given name in the given saves directory
+    #[cfg(test)]
+    pub fn save_slot(&self, dir: &Path, name: &str) -> Result<(), SaveError> {
+        crate::save::save_slot(&self.state, dir, name)
+    }
+
+    /// Replace this game with the one saved under the given name, as `load` does
+    #[cfg(test)]
+    pub fn load_slot(&mut self, dir: &Path, name: &str) -> Result<(), SaveError> {
+        let path = crate::save::slot_path(dir, name)?;
+        *self = GameEngine::load(&path)?;
+        Ok(())
+    }
+
+    /// Names of the games saved in the given saves directory
+    #[cfg(test)]
+    pub fn list_slots(dir: &Path) -> Vec<String> {
+        crate::save::list_slots(dir)
+    }
+
+    /// Remove the game saved under the given name
+    #[cfg(test)]
+    pub fn delete_slot(dir: &Path, name: &str) -> Result<(), SaveError> {
+        crate::save::delete_slot(dir, name)
+    }
+
+    /// Inputs read and changes applied on each turn played so far, from which the
+    /// state at the end of any of these turns can be replayed
+    #[cfg(test)]
+    pub fn turn_log(&self) -> &TurnLog {
+        &self.turn_log
+    }
+
+    /// Keep a copy of the state at the start of every turn played from now on, see
+    /// `history`. The `turn_log` replays any turn without keeping every state.
+    #[cfg(test)]
+    pub fn enable_history(&mut self) {
+        if self.history.is_none() {
+            self.history = Some(vec![self.state.clone()]);
+        }
+    }
+
+    /// States at the start of each turn played since `enable_history` was called, in
+    /// order
+    #[cfg(test)]
+    pub fn history(&self) -> &[GameState] {
+        match self.history.as_deref() {
+            Some([played @ .., _]) => played,
+            _ => &[],
+        }
+    }
+
+    /// Read the player's next input, recording it in the game's action log
+    pub fn read_input(&mut self, player: &mut dyn Player) -> Input {
+        let input = player.input();
+        self.log_rolled_dice();
+        self.action_log.record(LoggedAction::Input(input.clone()));
+        input
+    }
+
+    fn log_rolled_dice(&mut self) {
+        for die in self.state.take_rolled_dice() {
+            self.action_log.record(LoggedAction::Roll(die));
         }
     }
 
+    /// Every input read and die rolled so far in this game, see `GameEngine::from_log`
+    #[cfg(test)]
+    pub fn replay_log(&self) -> Vec<LoggedAction> {
+        let mut actions = self.action_log.actions().to_vec();
+        actions.extend(
+            self.state
+                .rolled_dice()
+                .iter()
+                .map(|die| LoggedAction::Roll(*die)),
+        );
+        actions
+    }
+
     pub fn game_ends(self: &GameEngine) -> bool {
         self.state.game_ends()
     }
 
-    pub fn collect_resources(&mut self) {
+    #[cfg(test)]
+    pub fn technologies_for(&self, side: Side) -> &crate::Technologies {
+        &self.state.state_of_war.get(&side).unwrap().technologies
+    }
+
+    pub fn vp_for(&self, side: Side) -> u8 {
+        self.state.state_of_war.get(&side).unwrap().vp
+    }
+
+    /// Nations at war, whatever their side, in a stable order
+    #[cfg(test)]
+    pub fn at_war_nations(&self) -> Vec<Nation> {
+        self.nations_where(NationState::is_at_war)
+    }
+
+    /// Nations which did not enter the war yet or left it without surrendering,
+    /// whatever their side, in a stable order
+    #[cfg(test)]
+    pub fn at_peace_nations(&self) -> Vec<Nation> {
+        self.nations_where(|state| *state == NationState::AtPeace)
+    }
+
+    #[cfg(test)]
+    fn nations_where(&self, predicate: impl Fn(&NationState) -> bool) -> Vec<Nation> {
+        let mut nations: Vec<Nation> = self
+            .state
+            .nations
+            .iter()
+            .filter(|(_, state)| predicate(state))
+            .map(|(nation, _)| *nation)
+            .collect();
+        nations.sort();
+        nations
+    }
+
+    /// State of the given nation, nations absent from the game being at peace
+    #[cfg(test)]
+    pub fn nation_state(&self, nation: Nation) -> NationState {
+        self.state
+            .nations
+            .get(&nation)
+            .cloned()
+            .unwrap_or(NationState::AtPeace)
+    }
+
+    /// Bring the US into the war, as Lusitania and the Zimmerman telegram do once both
+    /// have been played
+    #[cfg(test)]
+    pub fn us_enters_war(&mut self) {
+        self.state.us_enters_war();
+    }
+
+    /// Collections since the US entered the war, `None` while they have not
+    #[cfg(test)]
+    pub fn lafayette(&self) -> Option<u8> {
+        self.state.lafayette
+    }
+
+    /// Whether resources are collected this turn, see `GameConfig::collect_on`
+    pub fn collects_resources_this_turn(&self) -> bool {
+        self.state
+            .config
+            .collect_on
+            .collects_on(self.state.current_turn)
+    }
+
+    /// Collect resources for both sides, unless the configuration says there's no
+    /// collection this turn, returning the PR each side actually gained, Allies first
+    pub fn collect_resources(&mut self) -> [u8; 2] {
+        if !self.collects_resources_this_turn() {
+            return [0, 0];
+        }
+        let before = Side::all().map(|side| self.state.resources_for(&side));
         self.logic.collect_resources(&mut self.state);
+        let mut gained = [0, 0];
+        for (index, side) in Side::iter().enumerate() {
+            gained[index] = self
+                .state
+                .resources_for(&side)
+                .saturating_sub(before[index]);
+        }
+        if self.state.config.record_resources {
+            for (side, gain) in Side::iter().zip(gained) {
+                let war_state = self.state.state_of_war.get_mut(&side).unwrap();
+                war_state.collected.push(gain);
+            }
+        }
+        gained
     }
 
     pub fn reduce_pr(&mut self, side: Side, pr: u8) -> &mut Self {
@@ -64,89 +309,316 @@ impl GameEngine {
         self.state.current_year()
     }
 
+    #[cfg(test)]
     pub fn all_nations_at_war(&self, initiative: Side) -> Vec<Nation> {
         self.state.all_nations_at_war(initiative)
     }
 
-    pub(crate) fn reinforce(&mut self, nation: Nation, pr: u8) -> &Self {
-        let available_resources = self.state.resources_for(&nation.side());
-        let nation_state = self.state.nations.get_mut(&nation).unwrap();
-        let maximum_breakdown = nation.maximum_breakdown();
-        let current_breakdown = nation_state.breakdown();
-
-        let (spent, reinforcement) =
-            (1..=(pr + 1)).fold((0, 0), |(spent, reinforcement), resource| {
-                let new_spent = spent + resource;
-                if new_spent <= pr
-                    && reinforcement + current_breakdown < maximum_breakdown
-                    && new_spent <= available_resources
-                {
-                    (new_spent, reinforcement + 1)
-                } else {
-                    (spent, reinforcement)
-                }
-            });
+    /// PR needed to reinforce the given nation by `desired_points` breakdown levels at
+    /// once, each level costing 1 PR more than the previous one. Levels beyond the
+    /// nation's maximum breakdown cannot be bought and cost nothing.
+    pub fn reinforcement_cost(&self, nation: Nation, desired_points: u8) -> u8 {
+        let levels = desired_points.min(self.reinforcement_headroom(&nation));
+        (1..=levels).sum()
+    }
+
+    /// Breakdown levels the given nation can still gain
+    fn reinforcement_headroom(&self, nation: &Nation) -> u8 {
+        let maximum_breakdown = self.state.countries.get(nation).unwrap().max_breakdown;
+        let current_breakdown = self
+            .state
+            .nations
+            .get(nation)
+            .map_or(0, NationState::breakdown_level);
+        maximum_breakdown.saturating_sub(current_breakdown)
+    }
+
+    /// The given side spends up to `pr` to reinforce one of its nations by as many
+    /// levels as it can afford, see `reinforcement_cost`
+    pub(crate) fn reinforce(
+        &mut self,
+        side: Side,
+        nation: Nation,
+        pr: u8,
+    ) -> Result<ReinforceResult, ReinforceError> {
+        if self.state.side_of(&nation) != side {
+            return Err(ReinforceError::WrongSide(nation));
+        }
+        let available_resources = self.state.resources_for(&side);
+        if !self.state.is_at_war(&nation) {
+            return Err(ReinforceError::NationNotAtWar(nation));
+        }
+        if pr == 0 || available_resources == 0 {
+            return Err(ReinforceError::InsufficientResources(nation));
+        }
+        if self.reinforcement_headroom(&nation) == 0 {
+            return Err(ReinforceError::MaximumBreakdown(nation));
+        }
+
+        let budget = pr.min(available_resources);
+        let headroom = self.reinforcement_headroom(&nation);
+        let reinforcement = (0..=headroom)
+            .rev()
+            .find(|&levels| self.reinforcement_cost(nation, levels) <= budget)
+            .unwrap_or(0);
+        let spent = self.reinforcement_cost(nation, reinforcement);
+        // the next level would have been affordable but for the nation's maximum
+        let capped = reinforcement == headroom && spent + reinforcement < budget;
 
-        nation_state.reinforce(reinforcement);
-        self.reduce_pr(nation.side(), spent);
+        self.state
+            .nations
+            .get_mut(&nation)
+            .unwrap()
+            .reinforce(reinforcement);
+        self.reduce_pr(side, spent);
 
         if nation == Nation::Russia && reinforcement > 0 {
             self.track_russian_revolution(reinforcement);
         }
 
-        self
+        Ok(ReinforceResult {
+            nation,
+            levels: reinforcement,
+            spent,
+            capped,
+        })
     }
 
-    fn track_russian_revolution(&mut self, reinforcement: u8) {
-        let revolution_increase = (0..reinforcement)
+    /// Roll a die for each level Russia gained or lost, each 1 advancing the Russian
+    /// revolution
+    fn track_russian_revolution(&mut self, levels: u8) -> Option<HitsResult> {
+        let revolution_increase = (0..levels)
             .map(|_| self.roll())
             .filter(|&die| die == 1)
             .count() as u8;
-        self.state.russian_revolution += revolution_increase;
-        if self.state.russian_revolution >= 6 {
-            self.state.surrenders(&Nation::Russia);
+        self.advance_russian_revolution(revolution_increase)
+    }
+
+    /// Advance the Russian revolution track by the given amount. Once it reaches its end
+    /// Russia surrenders, no longer providing any PR to the Allies, and the result of
+    /// the surrender is returned.
+    pub fn advance_russian_revolution(&mut self, amount: u8) -> Option<HitsResult> {
+        if amount == 0 || !self.state.is_at_war(&Nation::Russia) {
+            return None;
+        }
+        self.state.russian_revolution += amount;
+        if self.state.russian_revolution >= RUSSIAN_REVOLUTION_LIMIT {
+            Some(self.state.surrenders(&Nation::Russia))
+        } else {
+            None
         }
     }
 
+    /// Apply hits to the given nation. Hits on Russia may advance the Russian revolution,
+    /// see `track_russian_revolution`.
     pub(crate) fn apply_hits(&mut self, to: &Nation, hits: u8) -> HitsResult {
-        self.logic.apply_hits(&mut self.state, to, hits)
+        let result = self.logic.apply_hits(&mut self.state, to, hits);
+        match result {
+            HitsResult::Hits(Nation::Russia, levels) => {
+                self.track_russian_revolution(levels).unwrap_or(result)
+            }
+            _ => result,
+        }
+    }
+
+    /// Apply hits to several nations in turn, e.g. for events hitting more than one
+    /// nation. Stops once hits decide the game: remaining hits are not applied and the
+    /// last result is the `Winner`.
+    #[cfg(test)]
+    pub fn apply_hits_distributed(&mut self, hits: &[(Nation, u8)]) -> Vec<HitsResult> {
+        let mut results = Vec::new();
+        for (nation, count) in hits {
+            let result = self.apply_hits(nation, *count);
+            let decided = matches!(result, HitsResult::Winner(_));
+            results.push(result);
+            if decided {
+                break;
+            }
+        }
+        results
     }
 
     pub(crate) fn draw_events(&mut self) -> Vec<Event> {
         self.state.draw_events()
     }
 
-    pub(crate) fn resolve_offensive(&mut self, offensive: &Offensive) -> OffensiveOutcome {
-        let operational = self
-            .state
-            .nations
-            .get(&offensive.from)
-            .unwrap()
-            .operational_level();
-        let resources = self
-            .state
-            .state_of_war
-            .get(&offensive.initiative)
-            .unwrap()
-            .resources;
+    /// Ids of the next `n` events to be drawn, in draw order, leaving the game untouched.
+    /// Only meant for debugging and teaching as it reveals hidden information.
+    pub fn peek_next_draw(&self, n: usize) -> Vec<u8> {
+        self.state.peek_next_draw(n)
+    }
 
-        if operational < offensive.pr {
-            return OffensiveOutcome::OperationalLevelTooLow(operational, offensive.pr);
+    /// Check an offensive can be launched before rolling any die
+    pub fn validate_offensive(&self, offensive: &Offensive) -> Result<(), OffensiveRejection> {
+        let offensive = &self.clamped(offensive);
+        let operational = self.state.operational_level(&offensive.from);
+        let resources = self.state.resources_for(&offensive.initiative);
+
+        if self.state.has_attacked(&offensive.from) {
+            Err(OffensiveRejection::AlreadyAttacked(offensive.from))
+        } else if !self.state.adjacent(&offensive.from, &offensive.to) {
+            Err(OffensiveRejection::NotAdjacent(
+                offensive.from,
+                offensive.to,
+            ))
+        } else if offensive.pr == 0 {
+            Err(OffensiveRejection::NoPr(offensive.from, offensive.to))
+        } else if operational < offensive.pr {
+            Err(OffensiveRejection::OperationalLevelTooLow(
+                operational,
+                offensive.pr,
+            ))
         } else if resources < offensive.pr {
-            return OffensiveOutcome::NotEnoughResources(offensive.pr, resources);
+            Err(OffensiveRejection::NotEnoughResources(
+                offensive.pr,
+                resources,
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Inputs accepted in the current phase from the side to play, given its resources
+    /// and nations. Nations which were reinforced and technologies already tried this
+    /// turn are left to the caller, and so is `Resign` which is always accepted.
+    pub fn legal_inputs(&self) -> Vec<Input> {
+        let state = &self.state;
+        let bids = |side: Side| {
+            (0..=state.resources_for(&side))
+                .map(Input::Number)
+                .collect()
+        };
+        match state.phase {
+            Phase::Initiative(side) => bids(side),
+            Phase::UBoot => bids(Side::Empires),
+            Phase::Blockade => bids(Side::Allies),
+            Phase::ImproveTechnologies(side) => {
+                let resources = state.resources_for(&side);
+                let mut inputs: Vec<Input> = state
+                    .available_technologies(&side)
+                    .iter()
+                    .flat_map(|tech| (1..=resources).map(|pr| Input::Select(tech.category, pr)))
+                    .collect();
+                inputs.push(Input::Pass);
+                inputs
+            }
+            Phase::LaunchOffensives(side) => {
+                let mut inputs = vec![];
+                for from in state.offensive_sources(side) {
+                    for to in Nation::values() {
+                        for pr in 1..=state.operational_level(&from) {
+                            let offensive = Offensive {
+                                initiative: side,
+                                from,
+                                to: *to,
+                                pr,
+                            };
+                            if self.validate_offensive(&offensive).is_ok() {
+                                inputs.push(Input::Offensive(from, *to, pr));
+                            }
+                        }
+                    }
+                }
+                inputs.push(Input::Pass);
+                inputs
+            }
+            Phase::Reinforcements(side) => {
+                let resources = state.resources_for(&side);
+                let mut inputs = vec![];
+                for nation in state.all_nations_at_war(side) {
+                    let maximum = state.countries.get(&nation).unwrap().max_breakdown;
+                    let losses = maximum.saturating_sub(state.breakdown_level(&nation));
+                    for pr in 1..=losses.min(resources) {
+                        inputs.push(Input::Reinforce(nation, pr));
+                    }
+                }
+                inputs.push(Input::Pass);
+                inputs
+            }
+            Phase::DrawEvents | Phase::CollectResources | Phase::NewTurn => vec![],
+        }
+    }
+
+    /// The outcome of resolving the given offensive right away, with the change it would
+    /// make to both sides' resources, leaving the game untouched.
+    /// Hits inflicted to the target nation are only reported by the outcome, while other
+    /// effects such as VP gained from a surrender or the offensive counting as the
+    /// nation's one for the turn are not reported at all.
+    /// The dice rolled are the ones `resolve_offensive` would roll next.
+    #[cfg(test)]
+    pub fn preview_offensive(&self, offensive: &Offensive) -> (OffensiveOutcome, StateChange) {
+        let mut preview = self.clone();
+        let outcome = preview.resolve_offensive(offensive);
+        let change = StateChange::MoreChanges(
+            Side::iter()
+                .filter_map(|side| {
+                    let pr = (preview.state.resources_for(&side) as i16
+                        - self.state.resources_for(&side) as i16)
+                        .clamp(i8::MIN as i16, i8::MAX as i16) as i8;
+                    (pr != 0).then_some(StateChange::ChangeResources { side, pr })
+                })
+                .collect(),
+        );
+        (outcome, change)
+    }
+
+    /// Let offensives assigned more PR than their side has spend what it has instead of
+    /// being rejected, see `OffensiveOutcome::PartialSpend`
+    #[cfg(test)]
+    pub fn set_clamp_offensive_pr(&mut self, clamp: bool) {
+        self.state.config.clamp_offensive_pr = clamp;
+    }
+
+    /// The offensive actually launched, spending no more PR than its side has when
+    /// `GameConfig::clamp_offensive_pr` is set
+    fn clamped(&self, offensive: &Offensive) -> Offensive {
+        let resources = self.state.resources_for(&offensive.initiative);
+        if self.state.config.clamp_offensive_pr && resources > 0 && offensive.pr > resources {
+            Offensive {
+                pr: resources,
+                ..offensive.clone()
+            }
+        } else {
+            offensive.clone()
+        }
+    }
+
+    /// Launch the given offensive. Offensives launched during an offensives phase count
+    /// as the attacking nation's offensive for the turn, unlike the ones triggered by
+    /// events.
+    pub(crate) fn resolve_offensive(&mut self, requested: &Offensive) -> OffensiveOutcome {
+        if let Err(rejection) = self.validate_offensive(requested) {
+            return OffensiveOutcome::Rejected(rejection);
+        }
+        let offensive = &self.clamped(requested);
+        if let Phase::LaunchOffensives(_) = self.state.phase {
+            self.state.record_attack(offensive.from);
         }
 
         let (artillery_bonus, attack_bonus, defense_malus, _) = self.compute_bonus(offensive);
 
+        let max_tech_level = self
+            .state
+            .countries
+            .get(&offensive.from)
+            .unwrap()
+            .max_tech_level;
+        let dice_count = effective_dice(offensive.pr, artillery_bonus, max_tech_level);
+
         let dice: Vec<u8> = self.roll_offensive_dice(offensive.pr);
-        let artillery_dice: Vec<u8> = self.roll_artillery_dice(artillery_bonus);
+        let artillery_dice: Vec<u8> = self.roll_artillery_dice(dice_count - offensive.pr);
 
         let attack_hits = self.evaluate_attack_hits(attack_bonus, defense_malus, offensive, &dice);
 
         let artillery_hits = self.evaluate_artillery_hits(offensive, &artillery_dice);
 
         self.reduce_pr(offensive.initiative, offensive.pr);
-        OffensiveOutcome::Hits(self.apply_hits(&offensive.to, attack_hits + artillery_hits))
+        let result = self.apply_hits(&offensive.to, attack_hits + artillery_hits);
+        if offensive.pr < requested.pr {
+            OffensiveOutcome::PartialSpend(requested.pr, offensive.pr, result)
+        } else {
+            OffensiveOutcome::Hits(result)
+        }
     }
 
     fn evaluate_artillery_hits(&mut self, offensive: &Offensive, artillery_dice: &Vec<u8>) -> u8 {
@@ -189,82 +661,200 @@ impl GameEngine {
 
     pub(crate) fn new_turn(&mut self) -> &mut Self {
         self.logic.new_turn(&mut self.state);
+        self.state.sweep_active_events();
+        if let Some(history) = &mut self.history {
+            history.push(self.state.clone());
+        }
+        self.log_rolled_dice();
+        let changes = std::mem::take(&mut self.applied_changes);
+        self.turn_log
+            .end_turn(&self.state, self.action_log.actions(), changes);
+        for hook in self.turn_end_hooks.0.iter_mut() {
+            hook(&self.state);
+        }
+        self.state.reset_wasted_pr();
+        self.state.reset_attacks();
+        self
+    }
+
+    /// Plays the game until the event with the given id is drawn and applied, returning
+    /// `false` if the game ended before. Phases are resolved as in `advance_to`.
+    #[cfg(test)]
+    pub fn run_until_event(
+        &mut self,
+        event_id: u8,
+        allies: &mut dyn Player,
+        empires: &mut dyn Player,
+    ) -> bool {
+        while !self.game_ends() {
+            self.advance_to(Phase::DrawEvents, allies, empires);
+            if self.game_ends() {
+                break;
+            }
+            for event in self.draw_events() {
+                self.play_events(&event);
+            }
+            let next = self.state.next_phase();
+            self.set_phase(next);
+            if self
+                .state
+                .events_drawn_this_turn
+                .iter()
+                .any(|event| event.event_id == event_id)
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Run a multi-step action, restoring the engine as it was before if the action fails
+    /// so that it does not leave a partially updated game.
+    /// Turn end and output hooks are kept as they are, and so is the action log but for
+    /// the inputs and dice of the failed action.
+    #[cfg(test)]
+    pub fn transaction<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut GameEngine) -> Result<T, E>,
+    ) -> Result<T, E> {
+        self.log_rolled_dice();
+        let logged = self.action_log.len();
+        let snapshot = self.clone();
+        let result = f(self);
+        if result.is_err() {
+            let hooks = std::mem::take(&mut self.turn_end_hooks);
+            let output_hooks = std::mem::take(&mut self.output_hooks);
+            let mut action_log = std::mem::take(&mut self.action_log);
+            *self = snapshot;
+            self.turn_end_hooks = hooks;
+            self.output_hooks = output_hooks;
+            action_log.truncate(logged);
+            self.action_log = action_log;
+            self.state.log_dice();
+        }
+        result
+    }
+
+    /// Register a callback to be invoked with the game's state after each turn ends,
+    /// e.g. to autosave the game.
+    pub fn on_turn_end(&mut self, cb: impl FnMut(&GameState) + 'static) -> &mut Self {
+        self.turn_end_hooks.0.push(Box::new(cb));
+        self
+    }
+
+    /// Register a callback to be invoked with every output sent to the players, e.g.
+    /// to log the game or feed a live display. Outputs sent to both players are seen
+    /// once.
+    #[cfg(test)]
+    pub fn on_output(&mut self, cb: impl FnMut(&Output) + 'static) -> &mut Self {
+        self.output_hooks.0.borrow_mut().push(Box::new(cb));
         self
     }
 
+    /// Invoke the callbacks registered with `on_output`
+    pub(crate) fn notify_output(&self, message: &Output) {
+        for hook in self.output_hooks.0.borrow_mut().iter_mut() {
+            hook(message);
+        }
+    }
+
+    /// Apply the event's effects, unless its precondition does not hold anymore
     pub(crate) fn play_events(&mut self, event: &Event) {
+        if !event.applies_to(&self.state) {
+            return;
+        }
         let active_event = self.play(event);
-        self.played_events.push(active_event);
+        self.state.activate(active_event);
     }
 
     fn play(&mut self, event: &Event) -> ActiveEvent {
+        // by default, events last for one turn
+        let mut deactivation = Deactivation::EndOfTurn;
+        let mut layer = None;
         match event.event_id {
-            4 => self.activate_event(RaceToTheSea::new),
-            5 => self.activate_event(ShellCrisis::new),
-            6 => self.activate_event(Gas::new),
-            7 => self.activate_event(VonLettowInAfrica::new),
-            8 => self.activate_event(Gallipoli::new),
-            9 => self.activate_event(SeparatePeace::new),
+            4 => layer = Some(Layer::RaceToTheSea),
+            5 => layer = Some(Layer::ShellCrisis),
+            6 => layer = Some(Layer::Gas),
+            7 => {
+                layer = Some(Layer::VonLettowInAfrica);
+                deactivation = Deactivation::Never;
+            }
+            8 => layer = Some(Layer::Gallipoli),
+            9 => layer = Some(Layer::SeparatePeace),
             10 => {
-                self.state
-                    .nations
-                    .insert(Nation::Italy, NationState::AtWar(5));
+                self.state.enter_war(Nation::Italy, 5);
             }
             11 => {
-                self.state
-                    .nations
-                    .insert(Nation::Bulgaria, NationState::AtWar(3));
+                self.state.enter_war(Nation::Bulgaria, 3);
+            }
+            12 => {
+                layer = Some(Layer::LusitaniaSunk);
+                deactivation = Deactivation::Never;
             }
-            12 => self.activate_event(LusitaniaSunk::new),
             14 => match self.roll() {
                 1 => {
                     self.increase_pr(Side::Empires, 3);
                 }
                 5 => {
-                    self.activate_event(GermanFleetDefeated::new);
+                    layer = Some(Layer::GermanFleetDefeated);
+                    deactivation = Deactivation::Never;
                 }
                 6 => {
-                    self.activate_event(GermanFleetDestroyed::new);
+                    layer = Some(Layer::GermanFleetDestroyed);
+                    deactivation = Deactivation::Never;
                 }
                 _ => {}
             },
-            15 => self.activate_event(AustrianOffensive::new),
-            16 => self.activate_event(WoodrowWilson::new),
-            19 => self.activate_event(BrusilovOffensive::new),
+            15 => layer = Some(Layer::AustrianOffensive),
+            16 => {
+                layer = Some(Layer::WoodrowWilson);
+                deactivation = Deactivation::Never;
+            }
+            19 => layer = Some(Layer::BrusilovOffensive),
             20 => {
-                self.state
-                    .nations
-                    .insert(Nation::Romania, NationState::AtWar(3));
+                self.state.enter_war(Nation::Romania, 3);
             }
-            22 => self.activate_event(Mutinies::new),
-            23 => self.activate_event(GazaOffensive::new),
+            22 => layer = Some(Layer::Mutinies),
+            23 => layer = Some(Layer::GazaOffensive),
             24 => {
                 self.reduce_pr(Side::Empires, 2);
             }
-            25 => self.activate_event(AustrianOffensive::new),
-            26 => self.activate_event(UBoot::new),
-            27 => self.activate_event(FlyingCircus::new),
+            25 => layer = Some(Layer::AustrianOffensive),
+            26 => {
+                layer = Some(Layer::UBoot);
+                deactivation = Deactivation::Never;
+            }
+            27 => {
+                layer = Some(Layer::FlyingCircus);
+                deactivation = Deactivation::AfterTurn(self.state.current_turn + 1);
+            }
             28 => {
-                self.state
-                    .nations
-                    .insert(Nation::Greece, NationState::AtWar(3));
+                self.state.enter_war(Nation::Greece, 3);
+            }
+            29 => {
+                layer = Some(Layer::ZimmermanTelegram);
+                deactivation = Deactivation::Never;
             }
-            29 => self.activate_event(ZimmermanTelegram::new),
             31 => {
                 self.state
                     .nations
                     .insert(Nation::Russia, NationState::AtPeace);
             }
-            32 => self.activate_event(Friedensturm::new),
-            33 => self.activate_event(UnifiedCommand::new),
-            35 => self.activate_event(BattleOfMegiddo::new),
+            32 => layer = Some(Layer::Friedensturm),
+            33 => {
+                layer = Some(Layer::UnifiedCommand);
+                deactivation = Deactivation::Never;
+            }
+            35 => layer = Some(Layer::BattleOfMegiddo),
             36 => {
                 if let Some(NationState::AtWar(_)) = self.state.nations.get(&Nation::Greece) {
-                    self.activate_event(SalonikiExpedition::new)
+                    layer = Some(Layer::SalonikiExpedition);
+                    deactivation = Deactivation::Never;
                 }
             }
             37 => {
-                let all_nations = self.state.nations.keys().cloned().collect::<Vec<Nation>>();
+                let mut all_nations = self.state.nations.keys().cloned().collect::<Vec<Nation>>();
+                all_nations.sort();
                 for nation in all_nations.iter() {
                     self.apply_hits(nation, 1);
                 }
@@ -277,21 +867,22 @@ impl GameEngine {
             }
             _ => {}
         }
+        if let Some(layer) = layer {
+            self.activate_event(layer);
+        }
         let active_event = ActiveEvent {
             event: event.clone(),
-            deactivation: |_game| true, // by default, events last for one turn
+            deactivation,
+            layer,
         };
         self.logic.event_activated(&active_event);
         active_event
     }
 
-    fn activate_event<T>(&mut self, new: fn(Box<dyn GameLogic>) -> T)
-    where
-        T: GameLogic + 'static,
-    {
+    fn activate_event(&mut self, layer: Layer) {
         let mut previous: Box<dyn GameLogic> = Box::new(DummyLogic::new());
         swap(&mut previous, &mut self.logic);
-        self.logic = Box::new((new)(previous));
+        self.logic = layer.wrap(previous);
     }
 
     pub(crate) fn blockade_effect(&mut self, bonus: u8) -> StateChange {
@@ -299,28 +890,163 @@ impl GameEngine {
     }
 
     pub(crate) fn improve_technology(&mut self, initiative: &Side, tech: &TechnologyType) {
-        self.state
-            .state_of_war
-            .get_mut(initiative)
-            .unwrap()
-            .technologies
-            .as_mut()
-            .improve(tech);
+        let year = self.current_year();
+        let war_state = self.state.state_of_war.get_mut(initiative).unwrap();
+        war_state.technologies.as_mut().improve(tech);
+        if *tech == TechnologyType::Defense
+            && war_state.technologies.defense == TRENCH_WARFARE_LEVEL
+            && war_state.trench_warfare_since.is_none()
+        {
+            war_state.trench_warfare_since = Some(year);
+        }
+    }
+
+    /// Whether the given side has enough resources, and operational capacity for offensives,
+    /// to play the given input, e.g. to enable controls in a UI
+    #[cfg(test)]
+    pub fn can_afford(&self, side: Side, input: &Input) -> bool {
+        let resources = self.state.resources_for(&side);
+        match input {
+            Input::Number(pr) | Input::Select(_, pr) | Input::Reinforce(_, pr) => *pr <= resources,
+            Input::Offensive(from, _, pr) => {
+                *pr <= resources && *pr <= self.state.operational_level(from)
+            }
+            Input::ReinforceMany(plan) => {
+                plan.iter().map(|(_, pr)| *pr as u16).sum::<u16>() <= resources as u16
+            }
+            Input::Pass | Input::ApplyHit(_) | Input::Next | Input::Resign => true,
+        }
     }
 
-    pub(crate) fn winner(&self) -> Side {
-        self.state.winner()
+    pub(crate) fn resign(&mut self, side: Side) {
+        self.state.resign(side)
+    }
+
+    pub(crate) fn result(&self) -> GameResult {
+        self.state.result()
     }
 
     pub(crate) fn apply_change(&mut self, change: &StateChange) {
         self.state.apply_change(change);
+        self.applied_changes.push(change.clone());
+    }
+
+    /// Revert the last change applied with `apply_change`, returning it, or `None` if
+    /// there is nothing left to undo.
+    /// PR spent on reinforcements, offensives or technologies are not refunded as their
+    /// effects stay. Only resources changes are reverted, see `StateChange::invert`: dice rolled to
+    /// compute a change are not rolled back either.
+    #[cfg(test)]
+    pub fn undo(&mut self) -> Option<StateChange> {
+        let change = self.applied_changes.pop()?;
+        self.state.apply_change(&change.invert());
+        Some(change)
     }
 
     pub(crate) fn set_phase(&mut self, phase: Phase) {
         self.state.set_phase(phase)
     }
 
+    /// Resolves phases in sequence until `phase` is reached or the game ends.
+    ///
+    /// This is a shortcut for tests and tools, not a full game driver: players are asked
+    /// once per phase for their initiative bid, a technology to improve, a nation to
+    /// reinforce and their sea control bonus, while offensives are skipped and hits in
+    /// excess of the Allies' resources from U-Boots are ignored.
+    #[cfg(test)]
+    pub fn advance_to(&mut self, phase: Phase, allies: &mut dyn Player, empires: &mut dyn Player) {
+        let mut allies_bid = 0;
+        while self.state.phase != phase && !self.game_ends() {
+            let player: &mut dyn Player = match self.state.side_to_play() {
+                Some(Side::Empires) => &mut *empires,
+                _ => &mut *allies,
+            };
+            match self.state.phase {
+                Phase::Initiative(side) if self.state.current_turn > 1 => {
+                    player.output(&Output::ChooseInitiative, self);
+                    let bid = match self.read_input(player) {
+                        Input::Number(pr) => pr.min(self.state.resources_for(&side)),
+                        _ => 0,
+                    };
+                    match side {
+                        Side::Allies => allies_bid = bid,
+                        Side::Empires => self.determine_initiative(allies_bid, bid),
+                    }
+                }
+                Phase::Initiative(_) => {}
+                Phase::DrawEvents => {
+                    for event in self.draw_events() {
+                        self.play_events(&event);
+                    }
+                }
+                Phase::CollectResources => {
+                    self.collect_resources();
+                }
+                Phase::ImproveTechnologies(side) => {
+                    player.output(
+                        &Output::ImproveTechnologies(crate::all_technology_types()),
+                        self,
+                    );
+                    if let Input::Select(tech, pr) = self.read_input(player) {
+                        if pr > 0 && pr <= self.state.resources_for(&side) {
+                            self.try_improve_technology(side, tech, pr);
+                        }
+                    }
+                }
+                Phase::LaunchOffensives(_) => {}
+                Phase::Reinforcements(side) => {
+                    player.output(&Output::ReinforceNations, self);
+                    if let Input::Reinforce(nation, pr) = self.read_input(player) {
+                        if let Err(err) = self.reinforce(side, nation, pr) {
+                            player.output(&Output::InvalidReinforcement(err), self);
+                        }
+                    }
+                }
+                Phase::UBoot | Phase::Blockade => {
+                    let side = self.state.side_to_play().unwrap();
+                    let prompt = match side {
+                        Side::Empires => Output::IncreaseUBoot,
+                        Side::Allies => Output::IncreaseBlockade,
+                    };
+                    player.output(&prompt, self);
+                    let bonus = match self.read_input(player) {
+                        Input::Number(n) => n.min(self.state.resources_for(&side)),
+                        _ => 0,
+                    };
+                    let change = match side {
+                        Side::Empires => self.uboot_losses(bonus),
+                        Side::Allies => self.blockade_effect(bonus),
+                    };
+                    self.apply_change(&change);
+                }
+                Phase::NewTurn => {
+                    self.new_turn();
+                    // new turn already moves to the initiative phase unless the game ends
+                    continue;
+                }
+            }
+            let next = self.state.next_phase();
+            self.set_phase(next);
+        }
+    }
+
     pub(crate) fn determine_initiative(&mut self, allies_pr: u8, empires_pr: u8) {
+        // a side bidding below the minimum concedes initiative and keeps its PR
+        let min_bid = self.state.config.min_initiative_bid;
+        let (allies_pr, empires_pr) = match (allies_pr >= min_bid, empires_pr >= min_bid) {
+            (true, true) => (allies_pr, empires_pr),
+            (true, false) => {
+                self.state.initiative = Side::Allies;
+                self.reduce_pr(Side::Allies, allies_pr);
+                return;
+            }
+            (false, true) => {
+                self.state.initiative = Side::Empires;
+                self.reduce_pr(Side::Empires, empires_pr);
+                return;
+            }
+            (false, false) => (0, 0),
+        };
         let allies_initiative = allies_pr + self.roll();
         let empires_initiative = empires_pr + self.roll();
 
@@ -332,12 +1058,71 @@ impl GameEngine {
 
         self.reduce_pr(Side::Allies, allies_pr);
         self.reduce_pr(Side::Empires, empires_pr);
+        match self.state.initiative {
+            Side::Allies => self.state.waste_pr(Side::Empires, empires_pr),
+            Side::Empires => self.state.waste_pr(Side::Allies, allies_pr),
+        }
     }
 
+    #[cfg(test)]
     pub(crate) fn valuation(&self) -> f64 {
         self.state.valuation()
     }
 
+    /// Rough estimate of the number of turns before the game is decided, e.g. to plan
+    /// how much PR to keep in reserve. This is a heuristic, not a rollout: each side is
+    /// assumed to break down one level of the enemy's weakest nations per turn, and
+    /// each surrender triggers the usual sudden death roll. The estimate never exceeds
+    /// the number of turns left before the turn limit.
+    #[cfg(test)]
+    pub fn estimate_remaining_turns(&self) -> f64 {
+        if self.game_ends() {
+            return 0.0;
+        }
+        let turns_left = (self.state.last_turn() + 1 - self.state.current_turn) as f64;
+        [Side::Allies, Side::Empires]
+            .iter()
+            .map(|side| self.turns_to_decision(*side, turns_left))
+            .fold(turns_left, f64::min)
+    }
+
+    /// Expected number of turns for the given side to win by sudden death, counting
+    /// games still undecided at the turn limit as lasting `turns_left`
+    #[cfg(test)]
+    fn turns_to_decision(&self, side: Side, turns_left: f64) -> f64 {
+        let mut enemies: Vec<(u8, u8)> = self
+            .state
+            .all_nations_at_war(side.other())
+            .iter()
+            .map(|nation| {
+                (
+                    self.state.breakdown_level(nation),
+                    self.state.countries.get(nation).unwrap().vp,
+                )
+            })
+            .collect();
+        enemies.sort();
+
+        let faces = self.state.config.die_faces as f64;
+        let mut vp = self.state.state_of_war.get(&side).unwrap().vp;
+        let mut elapsed = 0.0;
+        let mut undecided = 1.0;
+        let mut expected = 0.0;
+        for (breakdown, nation_vp) in enemies {
+            elapsed += breakdown as f64;
+            vp += nation_vp;
+            // the side wins if the sudden death roll is lower than its VP
+            let decides = if vp < self.state.config.victory_threshold {
+                0.0
+            } else {
+                ((vp as f64 - 1.0) / faces).clamp(0.0, 1.0)
+            };
+            expected += undecided * decides * f64::min(elapsed, turns_left);
+            undecided *= 1.0 - decides;
+        }
+        expected + undecided * turns_left
+    }
+
     pub(crate) fn try_improve_technology(
         &mut self,
         initiative: Side,
@@ -353,23 +1138,23 @@ impl GameEngine {
             .unwrap()
             .technologies;
         let current_tech_level = techs.value(&tech);
-        let technologies_track = match initiative {
-            Side::Allies => &ALLIES_TECHNOLOGIES,
-            Side::Empires => &EMPIRE_TECHNOLOGIES,
-        };
-
-        if current_tech_level == 4 {
+        if self.state.is_tech_maxed(&initiative, tech) {
             return TechnologyImprovement::NoMoreTechnologyImprovement(tech, current_tech_level);
         }
+        let technologies_track = technologies_track(&initiative);
 
         if let Some(technology) = &technologies_track[tech.index()][current_tech_level as usize] {
             if year >= technology.date {
+                let war_state = self.state.state_of_war.get_mut(&initiative).unwrap();
+                war_state.tech_pr_spent += pr_spent as u16;
                 if die + pr_spent > technology.min_dice_unlock {
+                    war_state.tech_levels_gained += 1;
                     self.improve_technology(&initiative, &tech);
                     self.reduce_pr(initiative, pr_spent);
                     TechnologyImprovement::ImprovedTechnology(tech, pr_spent)
                 } else {
                     self.reduce_pr(initiative, pr_spent);
+                    self.state.waste_pr(initiative, pr_spent);
                     TechnologyImprovement::FailedTechnology(tech, pr_spent)
                 }
             } else {
@@ -391,10 +1176,7 @@ struct DefaultGameLogic {}
 impl GameLogic for DefaultGameLogic {
     fn collect_resources(&mut self, state: &mut GameState) {
         state.lafayette = state.lafayette.map(|l| l + 1);
-        state.increase_pr(
-            Side::Allies,
-            state.tally_resources(&Side::Allies) + state.lafayette.unwrap_or(0),
-        );
+        state.increase_pr(Side::Allies, state.tally_resources(&Side::Allies));
         state.increase_pr(Side::Empires, state.tally_resources(&Side::Empires));
     }
 
@@ -402,15 +1184,14 @@ impl GameLogic for DefaultGameLogic {
         let max_attacker_tech_level = state.countries.get(&offensive.from).unwrap().max_tech_level;
         let max_defender_tech_level = state.countries.get(&offensive.to).unwrap().max_tech_level;
 
-        let artillery_bonus = state
-            .artillery_bonus(&offensive.initiative)
-            .min(max_attacker_tech_level);
+        // capped when rolling the dice, see `effective_dice`
+        let artillery_bonus = state.artillery_bonus(&offensive.initiative);
         let attack_bonus = state
             .attack_bonus(&offensive.initiative)
             .min(max_attacker_tech_level) as i8;
 
-        let defense_malus = state
-            .defense_bonus(&offensive.initiative.other())
+        let defender = offensive.initiative.other();
+        let defense_malus = (state.defense_bonus(&defender) + state.trench_warfare_bonus(&defender))
             .min(max_defender_tech_level) as i8;
         (artillery_bonus, attack_bonus, defense_malus, 0)
     }
@@ -473,8 +1254,14 @@ impl GameLogic for DefaultGameLogic {
     }
 
     fn apply_hits(&mut self, state: &mut GameState, nation: &Nation, hits: u8) -> HitsResult {
-        if let NationState::AtWar(breakdown) = state.nations.get_mut(nation).unwrap() {
+        if let Some(NationState::AtWar(breakdown)) = state.nations.get_mut(nation) {
             if hits >= *breakdown {
+                let overkill = hits - *breakdown;
+                if state.config.overkill_policy == OverkillPolicy::VictoryPoints {
+                    let side = state.side_of(nation).other();
+                    let war_state = state.state_of_war.get_mut(&side).unwrap();
+                    war_state.vp = war_state.vp.saturating_add(overkill);
+                }
                 state.surrenders(nation)
             } else {
                 *breakdown -= hits;
@@ -498,42 +1285,13 @@ impl GameLogic for DefaultGameLogic {
     }
 
     fn uboot_losses(&mut self, state: &mut GameState, bonus: u8) -> StateChange {
-        let die = state.roll() + bonus;
-        let loss = match die {
-            1..=4 => 0,
-            5 => 2,
-            _ => 4,
-        };
-        MoreChanges(vec![
-            ChangeResources {
-                side: Side::Allies,
-                pr: -loss,
-            },
-            ChangeResources {
-                side: Side::Empires,
-                pr: -(bonus as i8),
-            },
-        ])
+        let die = state.modified_roll(bonus as i8);
+        UBootResult::new(die, bonus).into()
     }
 
     fn blockade_effect(&mut self, state: &mut GameState, bonus: u8) -> StateChange {
-        let die = state.roll() + bonus;
-        let gain = match die {
-            1 => 3,
-            2 => 1,
-            _ => 0,
-        };
-
-        MoreChanges(vec![
-            ChangeResources {
-                side: Side::Allies,
-                pr: -(bonus as i8),
-            },
-            ChangeResources {
-                side: Side::Empires,
-                pr: gain,
-            },
-        ])
+        let die = state.modified_roll(bonus as i8);
+        BlockadeResult::new(die, bonus).into()
     }
 }
 
@@ -543,41 +1301,975 @@ fn default_game_logic() -> impl GameLogic {
 
 #[cfg(test)]
 mod engine_test {
+    use std::{cell::RefCell, rc::Rc};
+
     use crate::{
+        config::{Adjacency, AdjacencyOverride, CollectOn, OverkillPolicy},
         event::ALL_EVENTS,
-        fixtures::EngineBuilder,
-        Nation, NationState,
+        fixtures::{EngineBuilder, PlayerDouble},
+        minimax::Robot,
+        run_turn,
+        side::INITIAL_NATION_STATE,
+        state::TURN_LIMIT,
+        state::{ActiveEvent, Deactivation},
+        GameConfig, GameEngine, GameState, HitsResult,
+        Input::{self, Pass},
+        Nation, NationState, Offensive, OffensiveOutcome, OffensiveRejection, Output, Phase,
+        Players, ReinforceError, ReinforceResult,
         Side::{self, *},
+        StateChange, Technologies, TechnologyImprovement, TechnologyType, RUSSIAN_REVOLUTION_LIMIT,
+        ZERO_TECHNOLOGIES,
     };
 
     #[test]
-    fn played_events_stay_between_turns() {
-        let mut engine = EngineBuilder::new(11).build();
+    fn engine_can_be_built_from_an_existing_state() {
+        let mut state = GameState::new(17);
+        state.current_turn = 6;
+        state.increase_pr(Allies, 7);
+        state.nations.insert(Nation::Italy, NationState::AtWar(3));
 
-        // activate "Race to the sea"
-        engine.play_events(&ALL_EVENTS[3]);
-        engine.new_turn();
+        let engine = GameEngine::from_state(state.clone());
 
-        assert_eq!(1, engine.played_events.len());
+        assert_eq!(state, engine.state);
     }
 
     #[test]
-    fn lafayette_increases_by_1_every_turn() {
-        let mut engine = EngineBuilder::new(14).build();
+    fn history_keeps_the_state_at_the_start_of_each_turn_played() {
+        let mut engine = EngineBuilder::new(42).build();
+        engine.enable_history();
+        let mut players = Players {
+            allies_player: Box::new(Robot::new(Allies, 1)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
 
-        engine.state.lafayette = Some(0);
-        engine.collect_resources();
+        for _ in 0..3 {
+            run_turn(&mut players, &mut engine);
+        }
 
-        assert_eq!(15, engine.state.resources_for(&Allies));
+        let turns: Vec<u8> = engine
+            .history()
+            .iter()
+            .map(|state| state.current_turn)
+            .collect();
+        assert_eq!(vec![1, 2, 3], turns);
     }
 
     #[test]
-    fn when_turn_reaches_14_end_the_game() {
-        let mut engine = EngineBuilder::new(14).on_turn(14).build();
-
-        engine.new_turn();
-
-        assert!(engine.game_ends());
+    fn nations_at_war_and_at_peace_are_listed_whatever_their_side() {
+        let engine = GameEngine::new(14);
+        let mut at_war: Vec<Nation> = INITIAL_NATION_STATE
+            .iter()
+            .filter(|(_, state)| state.is_at_war())
+            .map(|(nation, _)| *nation)
+            .collect();
+        at_war.sort();
+
+        assert_eq!(at_war, engine.at_war_nations());
+        assert!(engine.at_peace_nations().contains(&Nation::Italy));
+        assert_eq!(
+            INITIAL_NATION_STATE.len(),
+            engine.at_war_nations().len() + engine.at_peace_nations().len()
+        );
+    }
+
+    #[test]
+    fn heavier_events_are_drawn_first_more_often() {
+        let mut engine = EngineBuilder::new(23).with_event_weight(1, 10).build();
+        let mut drawn_first = [0; 5];
+
+        for _ in 0..300 {
+            drawn_first[engine.peek_next_draw(1)[0] as usize] += 1;
+            engine.roll();
+        }
+
+        assert!(drawn_first[1] > 5 * drawn_first[2], "{:?}", drawn_first);
+    }
+
+    #[test]
+    fn peeking_at_next_draw_yields_forced_events_first() {
+        let mut engine = EngineBuilder::new(23)
+            .on_turn(2)
+            .force_next_events(vec![7, 3])
+            .build();
+
+        let upcoming = engine.peek_next_draw(3);
+
+        let drawn: Vec<u8> = engine
+            .draw_events()
+            .iter()
+            .map(|event| event.event_id)
+            .collect();
+        assert_eq!(vec![7, 3], drawn);
+        assert_eq!(drawn, upcoming[..2]);
+        assert_eq!(3, upcoming.len());
+        assert!(!drawn.contains(&upcoming[2]));
+    }
+
+    #[test]
+    fn peeking_at_next_draw_does_not_disturb_the_dice() {
+        let mut peeked = EngineBuilder::new(23).on_turn(2).build();
+        let mut untouched = EngineBuilder::new(23).on_turn(2).build();
+
+        let upcoming = peeked.peek_next_draw(3);
+
+        let drawn: Vec<u8> = peeked
+            .draw_events()
+            .iter()
+            .map(|event| event.event_id)
+            .collect();
+        assert_eq!(upcoming, drawn);
+        assert_eq!(untouched.draw_events(), peeked.state.events_drawn_this_turn);
+        assert_eq!(untouched.roll(), peeked.roll());
+    }
+
+    #[test]
+    fn lasting_events_stay_active_between_turns() {
+        let mut engine = EngineBuilder::new(11).build();
+
+        // activate "Race to the sea" and "Unified command"
+        engine.play_events(&ALL_EVENTS[3]);
+        engine.play_events(&ALL_EVENTS[32]);
+        engine.new_turn();
+
+        assert_eq!(vec![&ALL_EVENTS[32]], engine.state.active_events());
+    }
+
+    #[test]
+    fn advance_to_resolves_phases_until_the_given_one() {
+        let mut engine = EngineBuilder::new(11).on_turn(2).build();
+        let mut allies = PlayerDouble {
+            out: vec![],
+            inp: vec![Pass; 5],
+        };
+        let mut empires = PlayerDouble {
+            out: vec![],
+            inp: vec![Pass; 5],
+        };
+        engine.set_phase(Phase::Initiative(Allies));
+
+        engine.advance_to(Phase::LaunchOffensives(Allies), &mut allies, &mut empires);
+
+        assert_eq!(Phase::LaunchOffensives(Allies), engine.state.phase);
+        assert_eq!(Some(Allies), engine.state.side_to_play());
+        assert_eq!(2, engine.state.current_turn);
+    }
+
+    #[test]
+    fn advance_to_tells_player_why_reinforcement_is_invalid() {
+        let mut engine = EngineBuilder::new(11)
+            .on_turn(2)
+            .with_resources(Allies, 3)
+            .at_phase(Phase::Reinforcements(Allies))
+            .build();
+        let mut allies = PlayerDouble {
+            out: vec![],
+            inp: vec![Input::Reinforce(Nation::Germany, 1)],
+        };
+        let mut empires = PlayerDouble {
+            out: vec![],
+            inp: vec![],
+        };
+
+        engine.advance_to(Phase::Blockade, &mut allies, &mut empires);
+
+        assert_eq!(
+            vec![
+                Output::ReinforceNations,
+                Output::InvalidReinforcement(ReinforceError::WrongSide(Nation::Germany)),
+            ],
+            allies.out
+        );
+    }
+
+    #[test]
+    fn pr_spent_on_failed_technology_improvement_are_wasted() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_resources(Empires, 4)
+            .on_turn(2)
+            .build();
+
+        let result = engine.try_improve_technology(Empires, TechnologyType::Attack, 1);
+
+        assert_eq!(
+            TechnologyImprovement::FailedTechnology(TechnologyType::Attack, 1),
+            result
+        );
+        assert_eq!(1, engine.state.wasted_pr(&Empires));
+        assert_eq!(0, engine.state.wasted_pr(&Allies));
+
+        engine.new_turn();
+
+        assert_eq!(0, engine.state.wasted_pr(&Empires));
+    }
+
+    #[test]
+    fn failed_transaction_leaves_engine_unchanged() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 6)
+            .with_nation(Nation::France, NationState::AtWar(4))
+            .with_nation(Nation::Russia, NationState::AtWar(4))
+            .build();
+        let before = engine.state.clone();
+
+        let result: Result<(), &str> = engine.transaction(|engine| {
+            engine.reinforce(Allies, Nation::France, 1).unwrap();
+            engine.roll();
+            Err("Russia cannot be reinforced")
+        });
+
+        assert_eq!(Err("Russia cannot be reinforced"), result);
+        assert_eq!(before, engine.state);
+    }
+
+    #[test]
+    fn successful_transaction_keeps_changes() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 6)
+            .with_nation(Nation::France, NationState::AtWar(4))
+            .build();
+
+        let result: Result<(), ()> = engine.transaction(|engine| {
+            engine.reinforce(Allies, Nation::France, 1).unwrap();
+            Ok(())
+        });
+
+        assert_eq!(Ok(()), result);
+        assert_eq!(5, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn trench_warfare_reduces_hits_once_reached() {
+        let technologies = Technologies {
+            defense: 2,
+            ..ZERO_TECHNOLOGIES
+        };
+        let offensive = Offensive {
+            initiative: Empires,
+            from: Nation::Germany,
+            to: Nation::France,
+            pr: 3,
+        };
+        let mut without_trenches = EngineBuilder::new(14)
+            .with_resources(Empires, 3)
+            .with_technologies(Allies, technologies.clone())
+            .on_turn(5)
+            .build();
+        let mut with_trenches = EngineBuilder::new(14)
+            .with_resources(Empires, 3)
+            .with_technologies(Allies, technologies)
+            .with_trench_warfare_since(Allies, 1916)
+            .on_turn(5)
+            .build();
+
+        let without = without_trenches.resolve_offensive(&offensive);
+        let with = with_trenches.resolve_offensive(&offensive);
+
+        assert_eq!(
+            OffensiveOutcome::Hits(HitsResult::Hits(Nation::France, 1)),
+            without
+        );
+        assert_eq!(
+            OffensiveOutcome::Hits(HitsResult::Hits(Nation::France, 0)),
+            with
+        );
+    }
+
+    #[test]
+    fn trench_warfare_is_recorded_when_reaching_its_defense_level() {
+        let mut engine = EngineBuilder::new(11).on_turn(2).build();
+
+        engine.improve_technology(&Allies, &TechnologyType::Defense);
+        assert_eq!(0, engine.state.trench_warfare_bonus(&Allies));
+        engine.improve_technology(&Allies, &TechnologyType::Defense);
+
+        assert_eq!(
+            Some(1915),
+            engine
+                .state
+                .state_of_war
+                .get(&Allies)
+                .unwrap()
+                .trench_warfare_since
+        );
+        assert_eq!(1, engine.state.trench_warfare_bonus(&Allies));
+    }
+
+    #[test]
+    fn run_until_event_stops_once_event_is_drawn() {
+        let mut engine = EngineBuilder::new(11).build();
+        let mut allies = Robot::new(Allies, 1);
+        let mut empires = Robot::new(Empires, 1);
+
+        // Italy enters the war
+        let drawn = engine.run_until_event(10, &mut allies, &mut empires);
+
+        assert!(drawn);
+        assert!(engine
+            .state
+            .events_drawn_this_turn
+            .iter()
+            .any(|event| event.event_id == 10));
+        assert_eq!(Phase::CollectResources, engine.state.phase);
+    }
+
+    #[test]
+    fn tech_efficiency_is_levels_gained_over_pr_spent() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_resources(Empires, 10)
+            .on_turn(2)
+            .build();
+
+        let failed = engine.try_improve_technology(Empires, TechnologyType::Attack, 1);
+        let improved = engine.try_improve_technology(Empires, TechnologyType::Defense, 4);
+
+        assert_eq!(
+            TechnologyImprovement::FailedTechnology(TechnologyType::Attack, 1),
+            failed
+        );
+        assert_eq!(
+            TechnologyImprovement::ImprovedTechnology(TechnologyType::Defense, 4),
+            improved
+        );
+        assert_eq!(1.0 / 5.0, engine.state.tech_efficiency(&Empires));
+        assert_eq!(0.0, engine.state.tech_efficiency(&Allies));
+    }
+
+    #[test]
+    fn adjacency_override_rejects_offensive_between_nations_no_longer_adjacent() {
+        let offensive = Offensive {
+            initiative: Empires,
+            from: Nation::Germany,
+            to: Nation::France,
+            pr: 1,
+        };
+        let mut default = EngineBuilder::new(14).with_resources(Empires, 3).build();
+        let mut overridden = EngineBuilder::new(14)
+            .with_resources(Empires, 3)
+            .with_config(GameConfig {
+                adjacency_overrides: vec![AdjacencyOverride {
+                    between: (Nation::France, Nation::Germany),
+                    adjacency: Adjacency::Never,
+                }],
+                ..GameConfig::default()
+            })
+            .build();
+
+        assert!(matches!(
+            default.resolve_offensive(&offensive),
+            OffensiveOutcome::Hits(_)
+        ));
+        assert_eq!(
+            OffensiveOutcome::Rejected(OffensiveRejection::NotAdjacent(
+                Nation::Germany,
+                Nation::France
+            )),
+            overridden.resolve_offensive(&offensive)
+        );
+        assert_eq!(3, overridden.state.resources_for(&Empires));
+    }
+
+    #[test]
+    fn adjacency_override_can_require_a_technology() {
+        let engine = EngineBuilder::new(14)
+            .with_technologies(
+                Empires,
+                Technologies {
+                    attack: 1,
+                    ..ZERO_TECHNOLOGIES
+                },
+            )
+            .with_config(GameConfig {
+                adjacency_overrides: vec![AdjacencyOverride {
+                    between: (Nation::Germany, Nation::Russia),
+                    adjacency: Adjacency::WithTechnology(TechnologyType::Attack, 2),
+                }],
+                ..GameConfig::default()
+            })
+            .build();
+
+        assert!(!engine.state.adjacent(&Nation::Germany, &Nation::Russia));
+        assert!(!engine.state.adjacent(&Nation::Russia, &Nation::Germany));
+        assert!(engine.state.adjacent(&Nation::Germany, &Nation::France));
+    }
+
+    #[test]
+    fn adjacency_override_accepts_offensive_given_the_technology_level() {
+        let offensive = Offensive {
+            initiative: Empires,
+            from: Nation::Germany,
+            to: Nation::Serbia,
+            pr: 1,
+        };
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Empires, 3)
+            .with_technologies(
+                Empires,
+                Technologies {
+                    attack: 2,
+                    ..ZERO_TECHNOLOGIES
+                },
+            )
+            .with_config(GameConfig {
+                adjacency_overrides: vec![AdjacencyOverride {
+                    between: (Nation::Germany, Nation::Serbia),
+                    adjacency: Adjacency::WithTechnology(TechnologyType::Attack, 2),
+                }],
+                ..GameConfig::default()
+            })
+            .build();
+
+        assert!(!Nation::Germany.adjacent_to(&Nation::Serbia));
+        assert!(matches!(
+            engine.resolve_offensive(&offensive),
+            OffensiveOutcome::Hits(_)
+        ));
+        assert_eq!(2, engine.state.resources_for(&Empires));
+    }
+
+    #[test]
+    fn offensive_is_affordable_given_resources_and_operational_level() {
+        let engine = EngineBuilder::new(14)
+            .with_resources(Empires, 2)
+            .with_nation(Nation::Germany, NationState::AtWar(8))
+            .build();
+
+        assert!(engine.can_afford(
+            Empires,
+            &Input::Offensive(Nation::Germany, Nation::France, 2)
+        ));
+        assert!(!engine.can_afford(
+            Empires,
+            &Input::Offensive(Nation::Germany, Nation::France, 3)
+        ));
+    }
+
+    #[test]
+    fn offensive_is_not_affordable_beyond_operational_level() {
+        let engine = EngineBuilder::new(14)
+            .with_resources(Empires, 5)
+            .with_nation(Nation::Germany, NationState::AtWar(4))
+            .build();
+
+        assert!(!engine.can_afford(
+            Empires,
+            &Input::Offensive(Nation::Germany, Nation::France, 3)
+        ));
+    }
+
+    fn germany_attacks_france(pr: u8) -> Offensive {
+        Offensive {
+            initiative: Empires,
+            from: Nation::Germany,
+            to: Nation::France,
+            pr,
+        }
+    }
+
+    #[test]
+    fn valid_offensive_passes_validation() {
+        let engine = EngineBuilder::new(14).with_resources(Empires, 5).build();
+
+        assert_eq!(
+            Ok(()),
+            engine.validate_offensive(&germany_attacks_france(2))
+        );
+    }
+
+    #[test]
+    fn validation_rejects_offensive_against_non_adjacent_nation() {
+        let engine = EngineBuilder::new(14).with_resources(Empires, 5).build();
+        let offensive = Offensive {
+            to: Nation::Italy,
+            ..germany_attacks_france(2)
+        };
+
+        assert_eq!(
+            Err(OffensiveRejection::NotAdjacent(
+                Nation::Germany,
+                Nation::Italy
+            )),
+            engine.validate_offensive(&offensive)
+        );
+    }
+
+    #[test]
+    fn validation_rejects_offensive_without_pr() {
+        let engine = EngineBuilder::new(14).with_resources(Empires, 5).build();
+
+        assert_eq!(
+            Err(OffensiveRejection::NoPr(Nation::Germany, Nation::France)),
+            engine.validate_offensive(&germany_attacks_france(0))
+        );
+    }
+
+    #[test]
+    fn validation_rejects_second_offensive_from_same_nation_in_a_turn() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Empires, 5)
+            .at_phase(Phase::LaunchOffensives(Empires))
+            .build();
+        engine.resolve_offensive(&germany_attacks_france(1));
+
+        assert_eq!(
+            Err(OffensiveRejection::AlreadyAttacked(Nation::Germany)),
+            engine.validate_offensive(&germany_attacks_france(1))
+        );
+
+        engine.new_turn();
+
+        assert_eq!(
+            Ok(()),
+            engine.validate_offensive(&germany_attacks_france(1))
+        );
+    }
+
+    #[test]
+    fn previewing_offensive_leaves_the_game_untouched() {
+        let mut engine = EngineBuilder::new(14).with_resources(Empires, 5).build();
+        let offensive = germany_attacks_france(3);
+
+        let first = engine.preview_offensive(&offensive);
+        let second = engine.preview_offensive(&offensive);
+
+        assert_eq!(first, second);
+        assert_eq!(5, engine.state.resources_for(&Empires));
+        assert_eq!(first.0, engine.resolve_offensive(&offensive));
+    }
+
+    #[test]
+    fn previewed_change_only_covers_resources() {
+        let engine = EngineBuilder::new(14).with_resources(Empires, 5).build();
+        let offensive = germany_attacks_france(3);
+
+        let (_, change) = engine.preview_offensive(&offensive);
+        let mut resolved = engine.clone();
+        resolved.resolve_offensive(&offensive);
+        let previewed = engine.state.preview_change(&change);
+
+        assert_eq!(
+            StateChange::MoreChanges(vec![StateChange::ChangeResources {
+                side: Empires,
+                pr: -3
+            }]),
+            change
+        );
+        assert_eq!(2, previewed.resources_for(&Empires));
+        assert_eq!(
+            engine.nation_state(Nation::France),
+            previewed.nations[&Nation::France]
+        );
+        assert_ne!(
+            resolved.nation_state(Nation::France),
+            previewed.nations[&Nation::France]
+        );
+    }
+
+    #[test]
+    fn legal_technology_improvements_spend_up_to_available_resources() {
+        let engine = EngineBuilder::new(14)
+            .at_phase(Phase::ImproveTechnologies(Allies))
+            .with_resources(Allies, 2)
+            .build();
+        let available = engine.state.available_technologies(&Allies);
+
+        let legal = engine.legal_inputs();
+
+        assert_eq!(available.len() * 2 + 1, legal.len());
+        for tech in available {
+            assert!(legal.contains(&Input::Select(tech.category, 1)));
+            assert!(legal.contains(&Input::Select(tech.category, 2)));
+        }
+    }
+
+    #[test]
+    fn validation_rejects_offensive_beyond_operational_level() {
+        let engine = EngineBuilder::new(14)
+            .with_resources(Empires, 5)
+            .with_nation(Nation::Germany, NationState::AtWar(4))
+            .build();
+
+        assert_eq!(
+            Err(OffensiveRejection::OperationalLevelTooLow(2, 3)),
+            engine.validate_offensive(&germany_attacks_france(3))
+        );
+    }
+
+    #[test]
+    fn validation_rejects_offensive_beyond_resources() {
+        let engine = EngineBuilder::new(14).with_resources(Empires, 1).build();
+
+        assert_eq!(
+            Err(OffensiveRejection::NotEnoughResources(2, 1)),
+            engine.validate_offensive(&germany_attacks_france(2))
+        );
+    }
+
+    #[test]
+    fn technology_selection_is_not_affordable_without_resources() {
+        let engine = EngineBuilder::new(14).with_resources(Allies, 1).build();
+
+        assert!(!engine.can_afford(Allies, &Input::Select(TechnologyType::Attack, 2)));
+        assert!(engine.can_afford(Allies, &Input::Pass));
+    }
+
+    #[test]
+    fn turn_end_callbacks_fire_after_each_new_turn() {
+        let mut engine = EngineBuilder::new(11).build();
+        let turns = Rc::new(RefCell::new(Vec::new()));
+        let recorded = turns.clone();
+
+        engine.on_turn_end(move |state| recorded.borrow_mut().push(state.current_turn));
+        engine.new_turn();
+        engine.new_turn();
+
+        assert_eq!(vec![2, 3], *turns.borrow());
+    }
+
+    #[test]
+    fn turn_end_callbacks_are_not_cloned_with_engine() {
+        let mut engine = EngineBuilder::new(11).build();
+        let turns = Rc::new(RefCell::new(Vec::new()));
+        let recorded = turns.clone();
+
+        engine.on_turn_end(move |state| recorded.borrow_mut().push(state.current_turn));
+        engine.clone().new_turn();
+
+        assert!(turns.borrow().is_empty());
+    }
+
+    #[test]
+    fn resources_collected_each_turn_are_recorded_given_config_enables_it() {
+        let mut engine = EngineBuilder::new(11)
+            .with_config(GameConfig {
+                record_resources: true,
+                ..GameConfig::default()
+            })
+            .build();
+
+        for _ in 0..3 {
+            engine.collect_resources();
+            engine.reduce_pr(Allies, 14).reduce_pr(Empires, 9);
+            engine.new_turn();
+        }
+
+        let summary = engine.state.summary();
+        assert_eq!(vec![14, 14, 14], summary.allies_collected);
+        assert_eq!(vec![9, 9, 9], summary.empires_collected);
+    }
+
+    #[test]
+    fn resources_can_exceed_20_pr_during_mobilization_window_only() {
+        let mut engine = EngineBuilder::new(11)
+            .with_config(GameConfig {
+                mobilization_window: Some(2..=3),
+                ..GameConfig::default()
+            })
+            .on_turn(2)
+            .with_resources(Allies, 19)
+            .build();
+
+        engine.increase_pr(Allies, 5);
+        assert_eq!(24, engine.state.resources_for(&Allies));
+
+        engine.new_turn();
+        engine.increase_pr(Allies, 2);
+        assert_eq!(26, engine.state.resources_for(&Allies));
+
+        engine.new_turn();
+        engine.collect_resources();
+        assert_eq!(20, engine.state.resources_for(&Allies));
+        assert!(engine.state.wasted_pr(&Allies) >= 6);
+    }
+
+    #[test]
+    fn resources_are_not_collected_on_turns_not_configured_for_collection() {
+        let mut engine = EngineBuilder::new(11)
+            .with_config(GameConfig {
+                collect_on: CollectOn::Turns(vec![1, 3, 5, 7, 9, 11, 13]),
+                ..GameConfig::default()
+            })
+            .on_turn(2)
+            .with_resources(Allies, 3)
+            .with_resources(Empires, 4)
+            .build();
+
+        engine.collect_resources();
+
+        assert_eq!(3, engine.state.resources_for(&Allies));
+        assert_eq!(4, engine.state.resources_for(&Empires));
+
+        engine.new_turn();
+        engine.collect_resources();
+
+        assert!(engine.state.resources_for(&Allies) > 3);
+        assert!(engine.state.resources_for(&Empires) > 4);
+    }
+
+    #[test]
+    fn resources_collected_are_not_recorded_by_default() {
+        let mut engine = EngineBuilder::new(11).build();
+
+        engine.collect_resources();
+
+        assert!(engine.state.summary().allies_collected.is_empty());
+    }
+
+    #[test]
+    fn excess_hits_are_lost_by_default() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_nation(Nation::Serbia, NationState::AtWar(2))
+            .build();
+
+        let result = engine.apply_hits(&Nation::Serbia, 5);
+
+        assert_eq!(HitsResult::Surrenders(Nation::Serbia), result);
+        assert_eq!(1, engine.vp_for(Empires));
+    }
+
+    #[test]
+    fn excess_hits_give_victory_points_given_overkill_policy_is_victory_points() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_nation(Nation::Serbia, NationState::AtWar(2))
+            .with_config(GameConfig {
+                overkill_policy: OverkillPolicy::VictoryPoints,
+                ..GameConfig::default()
+            })
+            .build();
+
+        let result = engine.apply_hits(&Nation::Serbia, 5);
+
+        assert_eq!(HitsResult::Surrenders(Nation::Serbia), result);
+        assert_eq!(4, engine.vp_for(Empires));
+    }
+
+    #[test]
+    fn played_event_is_active_until_end_of_turn() {
+        let mut engine = EngineBuilder::new(11).build();
+
+        // activate "Race to the sea"
+        engine.play_events(&ALL_EVENTS[3]);
+
+        assert_eq!(vec![&ALL_EVENTS[3]], engine.state.active_events());
+
+        engine.new_turn();
+
+        assert!(engine.state.active_events().is_empty());
+    }
+
+    #[test]
+    fn undo_reverts_last_resources_change() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 6)
+            .with_resources(Empires, 3)
+            .build();
+        let loss = StateChange::MoreChanges(vec![
+            StateChange::ChangeResources {
+                side: Allies,
+                pr: -4,
+            },
+            StateChange::ChangeResources {
+                side: Empires,
+                pr: 2,
+            },
+        ]);
+        engine.apply_change(&StateChange::ChangeResources {
+            side: Allies,
+            pr: 1,
+        });
+        engine.apply_change(&loss);
+
+        assert_eq!(Some(loss), engine.undo());
+
+        assert_eq!(7, engine.state.resources_for(&Allies));
+        assert_eq!(3, engine.state.resources_for(&Empires));
+
+        engine.undo();
+        assert_eq!(6, engine.state.resources_for(&Allies));
+        assert_eq!(None, engine.undo());
+    }
+
+    #[test]
+    fn undo_does_not_refund_resources_spent_on_reinforcements() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 6)
+            .with_nation(Nation::France, NationState::AtWar(4))
+            .build();
+        engine.reinforce(Allies, Nation::France, 3).unwrap();
+        let reinforced = engine.state.clone();
+
+        assert_eq!(None, engine.undo());
+        assert_eq!(reinforced, engine.state);
+    }
+
+    #[test]
+    fn active_events_are_deactivated_independently() {
+        let mut engine = EngineBuilder::new(11).on_turn(1).build();
+        engine
+            .state
+            .activate(ActiveEvent::for_one_turn(ALL_EVENTS[3].clone()));
+        engine.state.activate(ActiveEvent {
+            event: ALL_EVENTS[4].clone(),
+            deactivation: Deactivation::AfterTurn(2),
+            layer: None,
+        });
+
+        engine.new_turn();
+
+        assert_eq!(vec![&ALL_EVENTS[4]], engine.state.active_events());
+
+        engine.new_turn();
+
+        assert!(engine.state.active_events().is_empty());
+    }
+
+    #[test]
+    fn lafayette_increases_by_1_every_turn() {
+        let mut engine = EngineBuilder::new(14).build();
+
+        engine.us_enters_war();
+        engine.collect_resources();
+
+        assert_eq!(Some(1), engine.lafayette());
+        assert_eq!(15, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn us_bring_the_allies_a_growing_pr_bonus_from_the_turn_after_they_enter_war() {
+        let mut engine = EngineBuilder::new(14).build();
+        let tally = engine.state.tally_resources(&Allies);
+        let collected = |engine: &mut GameEngine| {
+            engine.reduce_pr(Allies, engine.state.resources_for(&Allies));
+            engine.collect_resources();
+            engine.state.resources_for(&Allies)
+        };
+
+        engine.play_events(&ALL_EVENTS[11]);
+        engine.play_events(&ALL_EVENTS[28]);
+
+        assert!(!engine.state.us_at_war());
+        assert_eq!(
+            NationState::AtPeace,
+            engine.nation_state(Nation::UnitedStates)
+        );
+        assert_eq!(tally, collected(&mut engine));
+
+        engine.new_turn();
+
+        assert!(engine.state.us_at_war());
+        assert_eq!(
+            NationState::AtWar(0),
+            engine.nation_state(Nation::UnitedStates)
+        );
+        assert_eq!(tally + 1, collected(&mut engine));
+        assert_eq!(tally + 2, collected(&mut engine));
+    }
+
+    #[test]
+    fn us_at_war_can_neither_be_reinforced_nor_weigh_in_the_valuation() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 5)
+            .at_phase(Phase::Reinforcements(Allies))
+            .build();
+        let valuation = engine.state.valuation_components().allies.breakdowns;
+
+        engine.us_enters_war();
+
+        assert_eq!(
+            valuation,
+            engine.state.valuation_components().allies.breakdowns
+        );
+        assert!(!engine
+            .legal_inputs()
+            .contains(&Input::Reinforce(Nation::UnitedStates, 1)));
+        assert_eq!(
+            Err(ReinforceError::MaximumBreakdown(Nation::UnitedStates)),
+            engine.reinforce(Allies, Nation::UnitedStates, 1)
+        );
+    }
+
+    #[test]
+    fn when_turn_reaches_14_end_the_game() {
+        let mut engine = EngineBuilder::new(14).on_turn(14).build();
+
+        engine.new_turn();
+
+        assert!(engine.game_ends());
+    }
+
+    #[test]
+    fn reinforcing_spends_increasing_pr_per_level() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 5)
+            .with_nation(Nation::France, NationState::AtWar(3))
+            .build();
+
+        let result = engine.reinforce(Allies, Nation::France, 4);
+
+        assert_eq!(
+            Ok(ReinforceResult {
+                nation: Nation::France,
+                levels: 2,
+                spent: 3,
+                capped: false
+            }),
+            result
+        );
+        assert_eq!(2, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn reinforcement_cost_grows_by_1_pr_per_level_up_to_maximum_breakdown() {
+        let engine = EngineBuilder::new(11)
+            .with_nation(Nation::France, NationState::AtWar(3))
+            .build();
+
+        assert_eq!(0, engine.reinforcement_cost(Nation::France, 0));
+        assert_eq!(1, engine.reinforcement_cost(Nation::France, 1));
+        assert_eq!(3, engine.reinforcement_cost(Nation::France, 2));
+        assert_eq!(6, engine.reinforcement_cost(Nation::France, 3));
+        assert_eq!(10, engine.reinforcement_cost(Nation::France, 4));
+        assert_eq!(10, engine.reinforcement_cost(Nation::France, 5));
+    }
+
+    #[test]
+    fn cannot_reinforce_nation_at_peace() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 5)
+            .with_nation(Nation::Italy, NationState::AtPeace)
+            .build();
+
+        let result = engine.reinforce(Allies, Nation::Italy, 2);
+
+        assert_eq!(Err(ReinforceError::NationNotAtWar(Nation::Italy)), result);
+        assert_eq!(5, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn cannot_reinforce_enemy_nation() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 5)
+            .with_resources(Empires, 5)
+            .build();
+
+        let result = engine.reinforce(Allies, Nation::Germany, 2);
+
+        assert_eq!(Err(ReinforceError::WrongSide(Nation::Germany)), result);
+        assert_eq!(5, engine.state.resources_for(&Empires));
+    }
+
+    #[test]
+    fn cannot_reinforce_without_resources() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 0)
+            .with_nation(Nation::France, NationState::AtWar(3))
+            .build();
+
+        let result = engine.reinforce(Allies, Nation::France, 2);
+
+        assert_eq!(
+            Err(ReinforceError::InsufficientResources(Nation::France)),
+            result
+        );
     }
 
     #[test]
@@ -587,10 +2279,10 @@ mod engine_test {
             .with_nation(Nation::Russia, NationState::AtWar(5))
             .build();
 
-        engine.reinforce(Nation::Russia, 1);
+        engine.reinforce(Allies, Nation::Russia, 1).unwrap();
 
         assert_eq!(
-            6,
+            Some(6),
             engine
                 .state
                 .nations
@@ -608,10 +2300,10 @@ mod engine_test {
             .with_nation(Nation::Russia, NationState::AtWar(2))
             .build();
 
-        engine.reinforce(Nation::Russia, 6);
+        engine.reinforce(Allies, Nation::Russia, 6).unwrap();
 
         assert_eq!(
-            5,
+            Some(5),
             engine
                 .state
                 .nations
@@ -622,6 +2314,63 @@ mod engine_test {
         assert_eq!(2, engine.state.russian_revolution);
     }
 
+    #[test]
+    fn hits_on_russia_roll_a_die_per_level_lost_for_the_revolution() {
+        let mut engine = EngineBuilder::new(7) // die roll = 3 1 1
+            .with_nation(Nation::Russia, NationState::AtWar(5))
+            .build();
+
+        let result = engine.apply_hits(&Nation::Russia, 2);
+
+        assert_eq!(HitsResult::Hits(Nation::Russia, 2), result);
+        assert_eq!(1, engine.state.russian_revolution);
+    }
+
+    #[test]
+    fn russia_leaves_the_war_and_stops_providing_pr_at_the_end_of_the_revolution_track() {
+        let mut engine = EngineBuilder::new(14)
+            .with_russian_revolution(RUSSIAN_REVOLUTION_LIMIT - 1)
+            .with_nation(Nation::Russia, NationState::AtWar(3))
+            .build();
+        let tally = engine.state.tally_resources(&Allies);
+
+        engine.advance_russian_revolution(1);
+
+        assert!(!engine.all_nations_at_war(Allies).contains(&Nation::Russia));
+        assert_eq!(tally - 2, engine.state.tally_resources(&Allies));
+    }
+
+    #[test]
+    fn distributed_hits_are_applied_to_each_nation_in_turn() {
+        let mut engine = EngineBuilder::new(14)
+            .with_nation(Nation::Serbia, NationState::AtWar(1))
+            .build();
+
+        let results = engine.apply_hits_distributed(&[(Nation::Serbia, 3), (Nation::France, 2)]);
+
+        assert_eq!(
+            vec![
+                HitsResult::Surrenders(Nation::Serbia),
+                HitsResult::Hits(Nation::France, 2)
+            ],
+            results
+        );
+        assert_eq!(1, engine.vp_for(Empires));
+        assert_eq!(5, engine.state.breakdown_level(&Nation::France));
+    }
+
+    #[test]
+    fn near_decided_game_is_estimated_shorter_than_fresh_game() {
+        let fresh = EngineBuilder::new(11).build();
+        let mut decided = EngineBuilder::new(11)
+            .with_nation(Nation::France, NationState::AtWar(1))
+            .build();
+        decided.state.state_of_war.get_mut(&Empires).unwrap().vp = 6;
+
+        assert!(decided.estimate_remaining_turns() < fresh.estimate_remaining_turns());
+        assert!(fresh.estimate_remaining_turns() <= (TURN_LIMIT - 1) as f64);
+    }
+
     #[test]
     fn russia_surrenders_when_revolution_track_reaches_6() {
         let mut engine = EngineBuilder::new(7) // die roll = 3 1 1
@@ -630,13 +2379,13 @@ mod engine_test {
             .with_nation(Nation::Russia, NationState::AtWar(2))
             .build();
 
-        engine.reinforce(Nation::Russia, 6);
+        engine.reinforce(Allies, Nation::Russia, 6).unwrap();
 
         assert_eq!(
-            &NationState::AtPeace,
-            engine.state.nations.get(&Nation::Russia).unwrap()
+            NationState::Surrendered,
+            engine.nation_state(Nation::Russia)
         );
-        assert_eq!(3, engine.state.state_of_war.get(&Empires).unwrap().vp);
+        assert_eq!(3, engine.vp_for(Empires));
         assert_eq!(6, engine.state.russian_revolution);
     }
 }
@@ -1,9 +1,65 @@
-#[derive(Eq, PartialEq, Clone, Debug)]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::state::GameState;
+
+#[derive(Clone, Debug)]
 pub struct Event {
     pub event_id: u8,
     pub year: u16,
     pub not_after: Option<u16>,
     pub title: &'static str,
+    /// The event is only drawn and applied when this holds, e.g. when some nation is at war
+    pub precondition: fn(&GameState) -> bool,
+    /// How likely the event is to be drawn relative to the other events of the pool,
+    /// unless the game overrides it, see `GameState::set_event_weight`
+    pub weight: u8,
+}
+
+impl PartialEq for Event {
+    fn eq(&self, other: &Self) -> bool {
+        self.event_id == other.event_id
+            && self.year == other.year
+            && self.not_after == other.not_after
+            && self.title == other.title
+    }
+}
+
+impl Eq for Event {}
+
+impl Event {
+    /// Whether the event can be drawn and applied in the given state
+    pub fn applies_to(&self, state: &GameState) -> bool {
+        (self.precondition)(state)
+    }
+
+    /// The event with the given id, including the Armistice
+    pub fn by_id(event_id: u8) -> Option<Event> {
+        ALL_EVENTS
+            .iter()
+            .chain(std::iter::once(&ARMISTICE))
+            .find(|event| event.event_id == event_id)
+            .cloned()
+    }
+}
+
+/// Events are saved by id, the rest of their definition being part of the game
+impl Serialize for Event {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.event_id)
+    }
+}
+
+impl<'de> Deserialize<'de> for Event {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let event_id = u8::deserialize(deserializer)?;
+        Event::by_id(event_id)
+            .ok_or_else(|| de::Error::custom(format!("unknown event {}", event_id)))
+    }
+}
+
+/// Precondition of events which can always be drawn
+pub fn always(_: &GameState) -> bool {
+    true
 }
 
 pub const ALL_EVENTS: [Event; 40] = [
@@ -12,240 +68,320 @@ pub const ALL_EVENTS: [Event; 40] = [
         year: 1914,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 2,
         year: 1914,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 3,
         year: 1914,
         not_after: Some(1914),
         title: "Schlieffen plan",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 4,
         year: 1914,
         not_after: Some(1914),
         title: "Race to the sea",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 5,
         year: 1915,
         not_after: None,
         title: "Shells crisis",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 6,
         year: 1915,
         not_after: None,
         title: "Gas!",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 7,
         year: 1915,
         not_after: None,
         title: "Von Lettow in Africa",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 8,
         year: 1915,
         not_after: None,
         title: "Gallipoli",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 9,
         year: 1915,
         not_after: None,
         title: "Towards separated peace?",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 10,
         year: 1915,
         not_after: None,
         title: "Italy enters the war!",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 11,
         year: 1915,
         not_after: None,
         title: "Bulgaria enters the war!",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 12,
         year: 1915,
         not_after: None,
         title: "Lusitiania sank",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 13,
         year: 1915,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 14,
         year: 1916,
         not_after: None,
         title: "Battle of Jutland",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 15,
         year: 1916,
         not_after: None,
         title: "Trentin offensive",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 16,
         year: 1916,
         not_after: None,
         title: "Woodrow Wilson intervention",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 17,
         year: 1916,
         not_after: None,
         title: "Battle of Verdun",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 18,
         year: 1916,
         not_after: None,
         title: "Battle of Somme",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 19,
         year: 1916,
         not_after: None,
         title: "Brusilov offensive",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 20,
         year: 1916,
         not_after: None,
         title: "Romania enters the war!",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 21,
         year: 1916,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 22,
         year: 1917,
         not_after: None,
         title: "Repeated mutinies",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 23,
         year: 1917,
         not_after: None,
         title: "Battle of Gaza",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 24,
         year: 1917,
         not_after: None,
         title: "Lawrence of Arabia",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 25,
         year: 1917,
         not_after: None,
         title: "Battle of Caporetto",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 26,
         year: 1917,
         not_after: None,
         title: "U-Boot campaign",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 27,
         year: 1917,
         not_after: Some(1917),
         title: "Flying Circus",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 28,
         year: 1917,
         not_after: None,
         title: "Greece enters the war!",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 29,
         year: 1917,
         not_after: None,
         title: "Zimmerman telegram",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 30,
         year: 1917,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 31,
         year: 1918,
         not_after: None,
         title: "Brest-Litovsk treaty",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 32,
         year: 1918,
         not_after: None,
         title: "Friedensturm on the Marne",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 33,
         year: 1918,
         not_after: None,
         title: "Unified command",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 34,
         year: 1918,
         not_after: None,
         title: "Tanks!",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 35,
         year: 1918,
         not_after: None,
         title: "Battle of Megiddo",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 36,
         year: 1918,
         not_after: None,
         title: "Saloniki expedition",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 37,
         year: 1918,
         not_after: None,
         title: "Spanish flu",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 38,
         year: 1918,
         not_after: None,
         title: "Peace negotiations",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 39,
         year: 1918,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
     Event {
         event_id: 40,
         year: 1918,
         not_after: None,
         title: "All is quiet",
+        precondition: always,
+        weight: 1,
     },
 ];
 
@@ -254,4 +390,6 @@ pub const ARMISTICE: Event = Event {
     year: 1918,
     not_after: None,
     title: "Armistice",
+    precondition: always,
+    weight: 1,
 };
@@ -1,16 +1,73 @@
+use serde::{Deserialize, Serialize};
+
 use crate::logic::*;
 use crate::side::*;
 use crate::state::StateChange::*;
 use crate::state::*;
 use crate::TechEffects;
 
+/// The game logic an event layers on top of the current one when played, recorded
+/// with the active event so that a loaded game can rebuild its logic
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Layer {
+    RaceToTheSea,
+    ShellCrisis,
+    Gas,
+    VonLettowInAfrica,
+    Gallipoli,
+    SeparatePeace,
+    LusitaniaSunk,
+    GermanFleetDefeated,
+    GermanFleetDestroyed,
+    AustrianOffensive,
+    WoodrowWilson,
+    BrusilovOffensive,
+    Mutinies,
+    GazaOffensive,
+    UBoot,
+    FlyingCircus,
+    ZimmermanTelegram,
+    Friedensturm,
+    UnifiedCommand,
+    BattleOfMegiddo,
+    SalonikiExpedition,
+}
+
+impl Layer {
+    pub fn wrap(self, previous: Box<dyn GameLogic>) -> Box<dyn GameLogic> {
+        match self {
+            Layer::RaceToTheSea => Box::new(RaceToTheSea::new(previous)),
+            Layer::ShellCrisis => Box::new(ShellCrisis::new(previous)),
+            Layer::Gas => Box::new(Gas::new(previous)),
+            Layer::VonLettowInAfrica => Box::new(VonLettowInAfrica::new(previous)),
+            Layer::Gallipoli => Box::new(Gallipoli::new(previous)),
+            Layer::SeparatePeace => Box::new(SeparatePeace::new(previous)),
+            Layer::LusitaniaSunk => Box::new(LusitaniaSunk::new(previous)),
+            Layer::GermanFleetDefeated => Box::new(GermanFleetDefeated::new(previous)),
+            Layer::GermanFleetDestroyed => Box::new(GermanFleetDestroyed::new(previous)),
+            Layer::AustrianOffensive => Box::new(AustrianOffensive::new(previous)),
+            Layer::WoodrowWilson => Box::new(WoodrowWilson::new(previous)),
+            Layer::BrusilovOffensive => Box::new(BrusilovOffensive::new(previous)),
+            Layer::Mutinies => Box::new(Mutinies::new(previous)),
+            Layer::GazaOffensive => Box::new(GazaOffensive::new(previous)),
+            Layer::UBoot => Box::new(UBoot::new(previous)),
+            Layer::FlyingCircus => Box::new(FlyingCircus::new(previous)),
+            Layer::ZimmermanTelegram => Box::new(ZimmermanTelegram::new(previous)),
+            Layer::Friedensturm => Box::new(Friedensturm::new(previous)),
+            Layer::UnifiedCommand => Box::new(UnifiedCommand::new(previous)),
+            Layer::BattleOfMegiddo => Box::new(BattleOfMegiddo::new(previous)),
+            Layer::SalonikiExpedition => Box::new(SalonikiExpedition::new(previous)),
+        }
+    }
+}
+
 impl GameLogic for RaceToTheSea {
     fn previous(&mut self) -> Option<&mut dyn GameLogic> {
         Some(&mut *self.previous)
     }
 
     fn compute_bonus(&mut self, state: &GameState, offensive: &Offensive) -> TechEffects {
-        if self.active
+        if state.is_event_active(RACE_TO_THE_SEA)
             && (offensive.from == Nation::France && offensive.to == Nation::Germany
                 || (offensive.from == Nation::Germany && offensive.to == Nation::France))
         {
@@ -21,25 +78,19 @@ impl GameLogic for RaceToTheSea {
             self.previous.compute_bonus(state, offensive)
         }
     }
-
-    fn new_turn(&mut self, state: &mut GameState) {
-        self.active = false;
-        self.previous.new_turn(state);
-    }
 }
 
+/// Id of "Race to the sea", whose bonus lasts as long as the event is active
+const RACE_TO_THE_SEA: u8 = 4;
+
 #[derive(Debug, Clone)]
 pub struct RaceToTheSea {
-    pub active: bool,
     pub previous: Box<dyn GameLogic>,
 }
 
 impl RaceToTheSea {
     pub fn new(previous: Box<dyn GameLogic>) -> Self {
-        RaceToTheSea {
-            active: true,
-            previous,
-        }
+        RaceToTheSea { previous }
     }
 }
 
@@ -206,22 +257,8 @@ impl GameLogic for GermanFleetDefeated {
     }
 
     fn uboot_losses(&mut self, state: &mut GameState, bonus: u8) -> StateChange {
-        let die = (state.roll() - 1 + bonus).max(1);
-        let loss = match die {
-            1..=4 => 0,
-            5 => 2,
-            _ => 4,
-        };
-        MoreChanges(vec![
-            ChangeResources {
-                side: Side::Allies,
-                pr: -loss,
-            },
-            ChangeResources {
-                side: Side::Empires,
-                pr: -(bonus as i8),
-            },
-        ])
+        let die = state.modified_roll(bonus as i8 - 1);
+        UBootResult::new(die, bonus).into()
     }
 }
 
@@ -583,22 +620,8 @@ impl GameLogic for UBoot {
     }
 
     fn uboot_losses(&mut self, state: &mut GameState, bonus: u8) -> StateChange {
-        let die = state.roll() + 1 + bonus;
-        let loss = match die {
-            1..=4 => 0,
-            5 => 2,
-            _ => 4,
-        };
-        MoreChanges(vec![
-            ChangeResources {
-                side: Side::Allies,
-                pr: -loss,
-            },
-            ChangeResources {
-                side: Side::Empires,
-                pr: -(bonus as i8),
-            },
-        ])
+        let die = state.modified_roll(bonus as i8 + 1);
+        UBootResult::new(die, bonus).into()
     }
 }
 
@@ -619,7 +642,7 @@ impl GameLogic for FlyingCircus {
     }
 
     fn compute_bonus(&mut self, state: &GameState, offensive: &Offensive) -> TechEffects {
-        if self.number_of_turns_remaining > 0 && (offensive.initiative == Side::Empires) {
+        if state.is_event_active(FLYING_CIRCUS) && (offensive.initiative == Side::Empires) {
             let (artillery_bonus, attack_bonus, defense_malus, air_power) =
                 self.previous.compute_bonus(state, offensive);
             (artillery_bonus, attack_bonus, defense_malus, air_power + 2)
@@ -627,27 +650,19 @@ impl GameLogic for FlyingCircus {
             self.previous.compute_bonus(state, offensive)
         }
     }
-
-    fn new_turn(&mut self, state: &mut GameState) {
-        if self.number_of_turns_remaining > 0 {
-            self.number_of_turns_remaining -= 1;
-        }
-        self.previous.new_turn(state);
-    }
 }
 
+/// Id of "Flying circus", whose bonus lasts as long as the event is active
+const FLYING_CIRCUS: u8 = 27;
+
 #[derive(Debug, Clone)]
 pub struct FlyingCircus {
-    pub number_of_turns_remaining: u8,
     pub previous: Box<dyn GameLogic>,
 }
 
 impl FlyingCircus {
     pub fn new(previous: Box<dyn GameLogic>) -> Self {
-        FlyingCircus {
-            number_of_turns_remaining: 2,
-            previous,
-        }
+        FlyingCircus { previous }
     }
 }
 
@@ -664,9 +679,9 @@ impl GameLogic for LusitaniaSunk {
     }
 
     fn new_turn(&mut self, state: &mut GameState) {
-        if state.lafayette.is_none() && self.lafayette_nous_voila {
-            state.lafayette = Some(0);
-        };
+        if self.lafayette_nous_voila {
+            state.us_enters_war();
+        }
         self.previous.new_turn(state);
     }
 }
@@ -699,9 +714,9 @@ impl GameLogic for ZimmermanTelegram {
     }
 
     fn new_turn(&mut self, state: &mut GameState) {
-        if state.lafayette.is_none() && self.lafayette_nous_voila {
-            state.lafayette = Some(0);
-        };
+        if self.lafayette_nous_voila {
+            state.us_enters_war();
+        }
         self.previous.new_turn(state);
     }
 }
@@ -1043,10 +1058,7 @@ mod game_events_tests {
 
         engine.play_events(&ALL_EVENTS[9]);
 
-        assert_eq!(
-            NationState::AtWar(5),
-            *engine.state.nations.get(&Italy).unwrap()
-        );
+        assert_eq!(NationState::AtWar(5), engine.nation_state(Italy));
     }
 
     #[test]
@@ -1055,12 +1067,37 @@ mod game_events_tests {
 
         engine.play_events(&ALL_EVENTS[10]);
 
+        assert_eq!(NationState::AtWar(3), engine.nation_state(Bulgaria));
+    }
+
+    #[test]
+    fn italy_entering_war_increases_allies_next_collection() {
+        let mut engine = EngineBuilder::new(12).build();
+        let before = engine.state.tally_resources(&Allies);
+
+        engine.play_events(&ALL_EVENTS[9]);
+        engine.collect_resources();
+
+        assert_eq!(NationState::AtWar(5), engine.nation_state(Italy));
+        assert!(engine.state.resources_for(&Allies) > before);
         assert_eq!(
-            NationState::AtWar(3),
-            *engine.state.nations.get(&Bulgaria).unwrap()
+            before + engine.state.countries.get(&Italy).unwrap().resources,
+            engine.state.resources_for(&Allies)
         );
     }
 
+    #[test]
+    fn bulgaria_entering_war_increases_empires_next_collection() {
+        let mut engine = EngineBuilder::new(12).build();
+        let before = engine.state.tally_resources(&Empires);
+
+        engine.play_events(&ALL_EVENTS[10]);
+        engine.collect_resources();
+
+        assert_eq!(NationState::AtWar(3), engine.nation_state(Bulgaria));
+        assert_eq!(before + 1, engine.state.resources_for(&Empires));
+    }
+
     #[test]
     fn battle_of_jutland_adds_3_pr_to_empires_on_roll_of_1() {
         let mut engine = EngineBuilder::new(6).build(); // die roll = 1
@@ -1218,10 +1255,7 @@ mod game_events_tests {
 
         engine.play_events(&ALL_EVENTS[19]);
 
-        assert_eq!(
-            NationState::AtWar(3),
-            *engine.state.nations.get(&Romania).unwrap()
-        );
+        assert_eq!(NationState::AtWar(3), engine.nation_state(Romania));
     }
 
     #[test]
@@ -1376,10 +1410,7 @@ mod game_events_tests {
 
         engine.play_events(&ALL_EVENTS[27]);
 
-        assert_eq!(
-            NationState::AtWar(3),
-            *engine.state.nations.get(&Greece).unwrap()
-        );
+        assert_eq!(NationState::AtWar(3), engine.nation_state(Greece));
     }
 
     #[test]
@@ -1390,7 +1421,8 @@ mod game_events_tests {
         engine.play_events(&ALL_EVENTS[28]);
         engine.new_turn();
 
-        assert_eq!(Some(0), engine.state.lafayette);
+        assert_eq!(Some(0), engine.lafayette());
+        assert_eq!(NationState::AtWar(0), engine.nation_state(UnitedStates));
     }
 
     #[test]
@@ -1401,7 +1433,7 @@ mod game_events_tests {
         engine.play_events(&ALL_EVENTS[11]);
         engine.new_turn();
 
-        assert_eq!(Some(0), engine.state.lafayette);
+        assert_eq!(Some(0), engine.lafayette());
     }
 
     #[test]
@@ -1414,7 +1446,7 @@ mod game_events_tests {
         engine.state.lafayette = Some(1);
         engine.new_turn();
 
-        assert_eq!(Some(1), engine.state.lafayette);
+        assert_eq!(Some(1), engine.lafayette());
     }
 
     #[test]
@@ -1423,10 +1455,7 @@ mod game_events_tests {
 
         engine.play_events(&ALL_EVENTS[30]);
 
-        assert_eq!(
-            NationState::AtPeace,
-            *engine.state.nations.get(&Russia).unwrap()
-        );
+        assert_eq!(NationState::AtPeace, engine.nation_state(Russia));
     }
 
     #[test]
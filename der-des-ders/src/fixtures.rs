@@ -1,5 +1,5 @@
 use crate::{engine::GameEngine, GameState, Nation, NationState, Output, Player, Side::*};
-use crate::{Input, Phase, Players, Side};
+use crate::{GameConfig, Input, Phase, Players, Side};
 
 pub struct PlayerDouble {
     pub out: Vec<Output>,
@@ -79,8 +79,14 @@ impl EngineBuilder {
         self
     }
 
+    /// Like `on_turn`, with the events pool of a game started on that turn
+    pub(crate) fn starting_on_turn(&mut self, turn: u8) -> &mut Self {
+        self.state.start_on_turn(turn);
+        self
+    }
+
     pub fn build(&self) -> GameEngine {
-        GameEngine::with_state(self.state.to_owned())
+        GameEngine::from_state(self.state.to_owned())
     }
 
     pub(crate) fn with_nation(&mut self, nation: Nation, status: NationState) -> &mut Self {
@@ -88,6 +94,13 @@ impl EngineBuilder {
         self
     }
 
+    /// Make the next `draw_events` yield exactly these events, whatever the seed
+    #[cfg(test)]
+    pub(crate) fn force_next_events(&mut self, event_ids: Vec<u8>) -> &mut Self {
+        self.state.force_next_events(event_ids);
+        self
+    }
+
     pub(crate) fn with_initiative(&mut self, initiative: Side) -> &mut Self {
         self.state.initiative = initiative;
         self
@@ -102,11 +115,47 @@ impl EngineBuilder {
         self
     }
 
+    pub(crate) fn with_trench_warfare_since(&mut self, side: Side, year: u16) -> &mut Self {
+        self.state
+            .state_of_war
+            .get_mut(&side)
+            .unwrap()
+            .trench_warfare_since = Some(year);
+        self
+    }
+
     pub(crate) fn with_russian_revolution(&mut self, index: u8) -> &mut Self {
         self.state.russian_revolution = index;
         self
     }
 
+    pub(crate) fn with_config(&mut self, config: GameConfig) -> &mut Self {
+        self.state.config = config;
+        self
+    }
+
+    pub(crate) fn with_max_resources(&mut self, max_resources: u8) -> &mut Self {
+        self.state.config.max_resources = max_resources;
+        self
+    }
+
+    pub(crate) fn with_victory_threshold(&mut self, threshold: u8) -> &mut Self {
+        self.state.config.victory_threshold = threshold;
+        self
+    }
+
+    pub(crate) fn with_sudden_death(&mut self, sudden_death: bool) -> &mut Self {
+        self.state.config.sudden_death = sudden_death;
+        self
+    }
+
+    /// Change how likely the given event is to be drawn
+    #[cfg(test)]
+    pub(crate) fn with_event_weight(&mut self, event_id: u8, weight: u8) -> &mut Self {
+        self.state.set_event_weight(event_id, weight);
+        self
+    }
+
     pub(crate) fn at_phase(&mut self, phase: Phase) -> &mut Self {
         self.state.phase = phase;
         self
@@ -1,18 +1,25 @@
 use core::fmt;
+use nom::multi::separated_list1;
 use nom::sequence::{separated_pair, tuple};
+use std::cell::Cell;
 use std::fmt::{Display, Formatter};
 use std::io::{prelude::*, Stdin, Stdout};
+use std::rc::Rc;
 
 use nom::branch::alt;
-use nom::bytes::complete::tag_no_case;
+use nom::bytes::complete::{tag, tag_no_case};
 use nom::character::complete::{char, digit1};
 use nom::combinator::{all_consuming, map, map_res};
 use nom::{IResult, Parser};
+use serde::{Deserialize, Serialize};
 
-use crate::{side::*, GameEngine, OffensiveOutcome, TechnologyImprovement};
-use crate::{tech::*, GameState};
+use crate::{
+    side::*, GameEngine, OffensiveOutcome, OffensiveRejection, Phase, ReinforceError,
+    TechnologyImprovement, WarState,
+};
+use crate::{tech::*, GameResult, GameState};
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum Output {
     CurrentState(GameState),
     ChooseInitiative,
@@ -20,21 +27,78 @@ pub enum Output {
     ReinforceNations,
     LaunchOffensive(Vec<Nation>),
     WrongInput(Input),
-    CountryAlreadyAttacked(Nation),
-    AttackingNonAdjacentCountry(Nation, Nation),
+    OffensiveRejected(OffensiveRejection),
     OffensiveResult {
         from: Nation,
         to: Nation,
         result: OffensiveOutcome,
     },
     IncreaseUBoot,
-    UBootResult(u8),
+    UBootResult {
+        roll: u8,
+        bonus: u8,
+        loss: u8,
+        hits: u8,
+    },
     IncreaseBlockade,
-    BlockadeResult(u8),
+    BlockadeResult {
+        roll: u8,
+        bonus: u8,
+        gain: u8,
+    },
+    InvalidReinforcement(ReinforceError),
+    ReinforcementPlanRejected {
+        cost: u8,
+        resources: u8,
+    },
     SelectNationForHit,
+    /// The nation cannot take the hit: it is not a nation of the side at war
+    InvalidHit(Nation),
     EventDrawn(u8, String),
+    /// The drawn event's precondition does not hold, it has no effect
+    EventNotApplicable(u8),
     TechnologyResult(TechnologyImprovement),
     TurnFor(Side, u8),
+    PassKeyboard(Side),
+    TooManyRejectedInputs(Side),
+    WastedResources(Side, u8),
+    /// PR a side gained when collecting resources, and the PR each of its nations at
+    /// war brought to the base tally, before the resources cap and active events apply
+    ResourcesCollected {
+        side: Side,
+        collected: u8,
+        breakdown: Vec<(Nation, u8)>,
+    },
+    Resigned(Side),
+    InitiativeBids {
+        allies: u8,
+        empires: u8,
+    },
+    /// Guidance from a scenario, see `scenario::Tutor`
+    Hint(String),
+    /// Final tally of a finished game
+    GameOver {
+        result: GameResult,
+        allies_vp: u8,
+        empires_vp: u8,
+        turns: u8,
+    },
+}
+
+impl Output {
+    /// Whether this output requires an input from the player
+    pub fn is_prompt(&self) -> bool {
+        matches!(
+            self,
+            Output::ChooseInitiative
+                | Output::ImproveTechnologies(_)
+                | Output::ReinforceNations
+                | Output::LaunchOffensive(_)
+                | Output::IncreaseUBoot
+                | Output::IncreaseBlockade
+                | Output::SelectNationForHit
+        )
+    }
 }
 
 impl Display for Output {
@@ -65,69 +129,351 @@ impl Display for Output {
                 )
             }
             Output::WrongInput(inp) => write!(f, "Invalid input: {:?}", inp),
-            Output::CountryAlreadyAttacked(country) => {
-                write!(f, "Country already attacked: {}", country)
-            }
-            Output::AttackingNonAdjacentCountry(from, to) => {
-                write!(f, "{} is not adjacent to  {}", from, to)
-            }
+            Output::OffensiveRejected(rejection) => write!(f, "{}", rejection),
             Output::OffensiveResult { from, to, result } => {
                 write!(f, "Offensive from {} to {} result: {}", from, to, result)
             }
             Output::ReinforceNations => write!(f, "Assign PR to reinforce one nation, or Pass"),
             Output::IncreaseUBoot => write!(f, "Select PR to increase U-Boot level"),
-            Output::UBootResult(level) => write!(f, "Allies lose {} pr to U-Boot", level),
+            Output::UBootResult {
+                roll,
+                bonus,
+                loss,
+                hits,
+            } => {
+                write!(
+                    f,
+                    "U-Boot roll {} (+{} pr): Allies lose {} pr to U-Boot",
+                    roll, bonus, loss
+                )?;
+                if *hits > 0 {
+                    write!(f, ", {} hits applied to nations", hits)?;
+                }
+                Ok(())
+            }
             Output::IncreaseBlockade => write!(f, "Select PR to increase blockade level"),
-            Output::BlockadeResult(level) => {
-                write!(f, "Empires win {} pr from failed blockade", level)
+            Output::BlockadeResult { roll, bonus, gain } => {
+                write!(
+                    f,
+                    "Blockade roll {} (+{} pr): Empires win {} pr from failed blockade",
+                    roll, bonus, gain
+                )
             }
+            Output::InvalidReinforcement(err) => write!(f, "{}", err),
+            Output::ReinforcementPlanRejected { cost, resources } => write!(
+                f,
+                "Reinforcement plan rejected: costs {} pr but only {} available",
+                cost, resources
+            ),
             Output::SelectNationForHit => write!(f, "Select nation to apply hit"),
+            Output::InvalidHit(nation) => write!(f, "{} cannot take a hit", nation),
             Output::EventDrawn(event_id, event) => write!(f, "Event {} drawn: {}", event_id, event),
+            Output::EventNotApplicable(event_id) => {
+                write!(f, "Event {} does not apply, ignored", event_id)
+            }
             Output::TurnFor(side, turn) => write!(f, "Turn for {} ({})", side, turn),
+            Output::Resigned(side) => write!(f, "{} resigned", side),
+            Output::InitiativeBids { allies, empires } => {
+                write!(
+                    f,
+                    "Initiative bids: Allies {} PR, Empires {} PR",
+                    allies, empires
+                )
+            }
+            Output::WastedResources(side, pr) => {
+                write!(f, "{} wasted {} PR this turn", side, pr)
+            }
+            Output::ResourcesCollected {
+                side,
+                collected,
+                breakdown,
+            } => {
+                let total: u8 = breakdown.iter().map(|(_, pr)| pr).sum();
+                let nations: Vec<String> = breakdown
+                    .iter()
+                    .map(|(nation, pr)| format!("{} {}", nation, pr))
+                    .collect();
+                write!(
+                    f,
+                    "{} collect {} PR (tally {}: {})",
+                    side,
+                    collected,
+                    total,
+                    nations.join(", ")
+                )
+            }
+            Output::TooManyRejectedInputs(side) => {
+                write!(f, "Too many invalid inputs from {}, passing", side)
+            }
+            Output::PassKeyboard(side) => {
+                write!(f, "Pass the keyboard to {}, then type 'next'", side)
+            }
             Output::TechnologyResult(result) => {
                 write!(f, "Technology improvement result: {}", result)
             }
+            Output::Hint(text) => write!(f, "Hint: {}", text),
+            Output::GameOver {
+                result,
+                allies_vp,
+                empires_vp,
+                turns,
+            } => write!(
+                f,
+                "Game over after {} turns: {} (VP {} for Allies, {} for Empires)",
+                turns, result, allies_vp, empires_vp
+            ),
         }
     }
 }
 
-#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum Input {
     Number(u8),
     Pass,
     Select(TechnologyType, u8),
     Offensive(Nation, Nation, u8),
     Reinforce(Nation, u8),
+    /// Reinforce several nations at once, spending at most the given PR on each
+    ReinforceMany(Vec<(Nation, u8)>),
     ApplyHit(Nation),
     Next,
+    /// Concede the game to the other side
+    Resign,
 }
 
 pub trait Player {
     fn output(&mut self, message: &Output, engine: &GameEngine);
     fn input(&mut self) -> Input;
     fn out(&self) -> Vec<Output>;
+
+    /// Outputs until `end_batch` belong together, e.g. so that a UI can render them at once
+    fn begin_batch(&mut self) {}
+
+    fn end_batch(&mut self) {}
 }
 
 pub struct Console {
+    pub side: Side,
+    pub inp: Box<dyn BufRead>,
+    pub outp: Box<dyn Write>,
+    pub out: Vec<Output>,
+    /// The last prompt shown, telling which commands a `help` command lists
+    pub prompt: Option<Output>,
+    /// Accept a `peek` command listing the ids of the next events to be drawn
+    pub reveal_events: bool,
+    pub upcoming_events: Vec<u8>,
+    /// When set, a `handoff` command raises this flag so that the driver hands this
+    /// side over to a robot from the next turn
+    pub handoff: Option<Rc<Cell<bool>>>,
+}
+
+impl Console {
+    /// Text shown for the given output. Prompts for offensives list the enemy nations
+    /// each attacker can reach.
+    pub fn render(&self, message: &Output, engine: &GameEngine) -> String {
+        let mut text = format!("{}: {}\n", self.side, message);
+        if let Output::LaunchOffensive(sources) = message {
+            let state = &engine.state;
+            for source in sources {
+                let targets: Vec<String> = state
+                    .neighbours(source)
+                    .iter()
+                    .filter(|target| state.side_of(target) != state.side_of(source))
+                    .map(|target| target.to_string())
+                    .collect();
+                text.push_str(&format!("  {} -> {}\n", source, targets.join(", ")));
+            }
+        }
+        text
+    }
+
+    /// Syntax of the commands valid in answer to the last prompt, and of those always
+    /// available
+    pub fn help(&self) -> String {
+        let commands: &[&str] = match &self.prompt {
+            Some(Output::ChooseInitiative) => &["<pr>: bid PR for the initiative"],
+            Some(Output::ImproveTechnologies(_)) => &[
+                "attack|defense|artillery|air <pr>: spend PR to improve a technology",
+                "pass: stop improving technologies",
+            ],
+            Some(Output::LaunchOffensive(_)) => &[
+                "off <from> <to> <pr>: attack a nation, rolling one die per PR",
+                "pass: stop launching offensives",
+            ],
+            Some(Output::ReinforceNations) => &[
+                "reinforce <nation> <pr>: spend PR to reinforce a nation",
+                "reinforce <nation> <pr>, <nation> <pr>: reinforce several nations",
+                "pass: stop reinforcing",
+            ],
+            Some(Output::IncreaseUBoot) | Some(Output::IncreaseBlockade) => {
+                &["<pr>: spend PR on the naval war"]
+            }
+            Some(Output::SelectNationForHit) => &["hit <nation>: apply a hit to a nation"],
+            _ => &["next: continue"],
+        };
+        let mut text = String::from("Commands:\n");
+        for command in commands {
+            text.push_str(&format!("  {}\n", command));
+        }
+        text.push_str("  resign: concede the game\n");
+        if self.reveal_events {
+            text.push_str("  peek: list the next events to be drawn\n");
+        }
+        if self.handoff.is_some() {
+            text.push_str("  handoff: let a robot play from next turn\n");
+        }
+        let names: Vec<&str> = NATION_NAMES.iter().map(|(name, _)| *name).collect();
+        text.push_str(&format!("Nations: {}\n", names.join(", ")));
+        text
+    }
+
+    fn write(&mut self, text: &str) {
+        self.outp
+            .write_all(text.as_bytes())
+            .expect("Failed to write to stdout");
+        self.outp.flush().expect("Failed to write to stdout");
+    }
+}
+
+impl Player for Console {
+    fn output(&mut self, message: &Output, engine: &GameEngine) {
+        if self.reveal_events {
+            self.upcoming_events = engine.peek_next_draw(3);
+        }
+        if message.is_prompt() {
+            self.prompt = Some(message.clone());
+        }
+        self.out.push(message.clone());
+        let text = self.render(message, engine);
+        self.write(&text);
+    }
+
+    /// Read commands until one is a valid input, answering `help`, `peek` and `handoff`
+    /// along the way. Reaching the end of the input resigns the game.
+    fn input(&mut self) -> Input {
+        loop {
+            let mut command_string: String = String::new();
+            if self.inp.read_line(&mut command_string).unwrap() == 0 {
+                return Input::Resign;
+            }
+            let command = command_string.trim();
+            if command.eq_ignore_ascii_case("help") {
+                let help = self.help();
+                self.write(&help);
+                continue;
+            }
+            if self.reveal_events && command.eq_ignore_ascii_case("peek") {
+                let upcoming = format!("Next events: {:?}\n", self.upcoming_events);
+                self.write(&upcoming);
+                continue;
+            }
+            if let Some(handoff) = &self.handoff {
+                if command.eq_ignore_ascii_case("handoff") {
+                    handoff.set(true);
+                    self.write("A robot takes over from next turn\n");
+                    continue;
+                }
+            }
+            if let Ok(input) = parse(command) {
+                return input;
+            }
+        }
+    }
+
+    fn out(&self) -> Vec<Output> {
+        self.out.clone()
+    }
+}
+
+/// Plays through JSON lines for non-interactive clients: each output is written as one
+/// line on stdout and each input is read as one line from stdin. Lines which are not a
+/// valid input are ignored, and reaching the end of the input resigns the game.
+pub struct JsonIO {
     pub side: Side,
     pub inp: Stdin,
     pub outp: Stdout,
     pub out: Vec<Output>,
 }
 
-impl Player for Console {
-    fn output(&mut self, message: &Output, _: &GameEngine) {
+/// What JSON clients see of the game's state: the board, tracks and sides, without
+/// the dice, the events left to draw nor anything else players cannot see
+#[derive(Eq, PartialEq, Clone, Debug, Serialize)]
+pub struct StateView {
+    pub turn: u8,
+    pub phase: Phase,
+    pub initiative: Side,
+    pub winner: Option<Side>,
+    pub russian_revolution: u8,
+    pub lafayette: Option<u8>,
+    pub nations: Vec<(Nation, NationState)>,
+    pub sides: Vec<(Side, WarState)>,
+    /// Ids of the events drawn this turn and of the events still in effect
+    pub events_drawn: Vec<u8>,
+    pub active_events: Vec<u8>,
+}
+
+impl From<&GameState> for StateView {
+    fn from(state: &GameState) -> Self {
+        StateView {
+            turn: state.current_turn,
+            phase: state.phase.clone(),
+            initiative: state.initiative,
+            winner: state.winner,
+            russian_revolution: state.russian_revolution,
+            lafayette: state.lafayette,
+            nations: Nation::values()
+                .filter_map(|nation| Some((*nation, state.nations.get(nation)?.clone())))
+                .collect(),
+            sides: Side::iter()
+                .map(|side| (side, state.state_of_war.get(&side).unwrap().clone()))
+                .collect(),
+            events_drawn: state
+                .events_drawn_this_turn
+                .iter()
+                .map(|event| event.event_id)
+                .collect(),
+            active_events: state
+                .active_events()
+                .iter()
+                .map(|event| event.event_id)
+                .collect(),
+        }
+    }
+}
+
+/// The JSON line sent for an output to the player of the given side. Both sides may
+/// share stdout, hence the side, and states are sent as a `StateView`.
+fn json_line(side: Side, message: &Output) -> String {
+    let output = match message {
+        Output::CurrentState(state) => {
+            serde_json::json!({ "CurrentState": StateView::from(state) })
+        }
+        other => serde_json::to_value(other).expect("Failed to serialize output"),
+    };
+    serde_json::json!({ "side": side, "output": output }).to_string()
+}
+
+impl Player for JsonIO {
+    fn output(&mut self, message: &Output, _engine: &GameEngine) {
         self.out.push(message.clone());
+        let line = json_line(self.side, message);
         let mut stdout = self.outp.lock();
         stdout
-            .write_all(format!("{}: {}\n", self.side, message).as_bytes())
+            .write_all(format!("{}\n", line).as_bytes())
             .expect("Failed to write to stdout");
+        stdout.flush().expect("Failed to write to stdout");
     }
 
     fn input(&mut self) -> Input {
-        let mut command_string: String = String::new();
-        self.inp.read_line(&mut command_string).unwrap();
-        parse(command_string.trim()).unwrap_or_else(|_| self.input())
+        loop {
+            let mut line = String::new();
+            if self.inp.read_line(&mut line).unwrap() == 0 {
+                return Input::Resign;
+            }
+            if let Ok(input) = serde_json::from_str(line.trim()) {
+                return input;
+            }
+        }
     }
 
     fn out(&self) -> Vec<Output> {
@@ -135,28 +481,118 @@ impl Player for Console {
     }
 }
 
+/// Wraps the player of one side when two humans share a terminal.
+///
+/// Before a side is prompted for some input, the keyboard must be handed over to
+/// its player who acknowledges with `Next`. Outputs which do not require any input
+/// are only shown to the player holding the keyboard.
+pub struct Hotseat {
+    inner: Box<dyn Player>,
+    side: Side,
+    keyboard: Rc<Cell<Side>>,
+    /// Resignation typed instead of `Next`, given as the next input
+    resigned: bool,
+}
+
+impl Hotseat {
+    /// Wrap both sides' players, the Allies holding the keyboard first
+    pub fn pair(allies: Box<dyn Player>, empires: Box<dyn Player>) -> (Hotseat, Hotseat) {
+        let keyboard = Rc::new(Cell::new(Side::Allies));
+        (
+            Hotseat {
+                inner: allies,
+                side: Side::Allies,
+                keyboard: keyboard.clone(),
+                resigned: false,
+            },
+            Hotseat {
+                inner: empires,
+                side: Side::Empires,
+                keyboard,
+                resigned: false,
+            },
+        )
+    }
+}
+
+impl Player for Hotseat {
+    fn output(&mut self, message: &Output, engine: &GameEngine) {
+        if message.is_prompt() && self.keyboard.get() != self.side {
+            self.inner.output(&Output::PassKeyboard(self.side), engine);
+            loop {
+                match self.inner.input() {
+                    Input::Next => break,
+                    Input::Resign => {
+                        self.resigned = true;
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+            self.keyboard.set(self.side);
+        }
+        if self.keyboard.get() == self.side {
+            self.inner.output(message, engine);
+        }
+    }
+
+    fn input(&mut self) -> Input {
+        if self.resigned {
+            self.resigned = false;
+            return Input::Resign;
+        }
+        self.inner.input()
+    }
+
+    fn out(&self) -> Vec<Output> {
+        self.inner.out()
+    }
+
+    fn begin_batch(&mut self) {
+        self.inner.begin_batch()
+    }
+
+    fn end_batch(&mut self) {
+        self.inner.end_batch()
+    }
+}
+
 fn num(input: &str) -> IResult<&str, Input> {
     map(map_res(digit1, |s: &str| s.parse::<u8>()), |n| {
         Input::Number(n)
     })(input)
 }
 
+/// Names of the nations in commands, as listed by `Console::help`
+const NATION_NAMES: [(&str, Nation); 14] = [
+    ("france", Nation::France),
+    ("germany", Nation::Germany),
+    ("italy", Nation::Italy),
+    ("austria", Nation::AustriaHungary),
+    ("russia", Nation::Russia),
+    ("serbia", Nation::Serbia),
+    ("ottoman", Nation::OttomanEmpire),
+    ("bulgaria", Nation::Bulgaria),
+    ("egypt", Nation::Egypt),
+    ("romania", Nation::Romania),
+    ("greece", Nation::Greece),
+    ("aef", Nation::FrenchAfrica),
+    ("usa", Nation::UnitedStates),
+    ("germanafrica", Nation::GermanAfrica),
+];
+
 fn country(input: &str) -> IResult<&str, Nation> {
-    alt((
-        tag_no_case("france").map(|_| Nation::France),
-        tag_no_case("germany").map(|_| Nation::Germany),
-        tag_no_case("italy").map(|_| Nation::Italy),
-        tag_no_case("austria").map(|_| Nation::AustriaHungary),
-        tag_no_case("russia").map(|_| Nation::Russia),
-        tag_no_case("serbia").map(|_| Nation::Serbia),
-        tag_no_case("ottoman").map(|_| Nation::OttomanEmpire),
-        tag_no_case("bulgaria").map(|_| Nation::Bulgaria),
-        tag_no_case("egypt").map(|_| Nation::Egypt),
-        tag_no_case("romania").map(|_| Nation::Romania),
-        tag_no_case("greece").map(|_| Nation::Greece),
-        tag_no_case("aef").map(|_| Nation::FrenchAfrica),
-        tag_no_case("germanafrica").map(|_| Nation::GermanAfrica),
-    ))(input)
+    NATION_NAMES
+        .iter()
+        .find_map(|(name, nation)| {
+            tag_no_case::<_, _, nom::error::Error<&str>>(*name)(input)
+                .ok()
+                .map(|(rest, _)| (rest, *nation))
+        })
+        .ok_or(nom::Err::Error(nom::error::Error::new(
+            input,
+            nom::error::ErrorKind::Tag,
+        )))
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -174,6 +610,7 @@ pub fn parse(string: &str) -> Result<Input, ParseError> {
         alt((all_consuming(tag_no_case("p")), tag_no_case("pass"))),
         |_| Input::Pass,
     );
+    let resign = map(all_consuming(tag_no_case("resign")), |_| Input::Resign);
     let select_tech = map(
         all_consuming(separated_pair(
             alt((
@@ -229,6 +666,24 @@ pub fn parse(string: &str) -> Result<Input, ParseError> {
         },
     );
 
+    let reinforce_many = map(
+        all_consuming(tuple((
+            tag_no_case("reinforce"),
+            char(' '),
+            separated_list1(tag(", "), separated_pair(country, char(' '), num)),
+        ))),
+        |(_, _, plan)| {
+            Input::ReinforceMany(
+                plan.into_iter()
+                    .map(|(nation, num)| match num {
+                        Input::Number(n) => (nation, n),
+                        _ => panic!("Invalid input"), // never reached
+                    })
+                    .collect(),
+            )
+        },
+    );
+
     let apply_hit = map(
         all_consuming(tuple((
             alt((tag_no_case("hit").map(|_| ()), tag_no_case("h").map(|_| ()))),
@@ -241,9 +696,11 @@ pub fn parse(string: &str) -> Result<Input, ParseError> {
     let res = alt((
         next,
         pass,
+        resign,
         select_tech,
         offensive,
         reinforce,
+        reinforce_many,
         apply_hit,
         num,
     ))(string);
@@ -257,7 +714,146 @@ pub fn parse(string: &str) -> Result<Input, ParseError> {
 #[cfg(test)]
 mod tests {
 
-    use super::{parse, Input::*, Nation::*, TechnologyType::*};
+    use std::io::{empty, sink, Cursor};
+
+    use super::{
+        country, json_line, parse, Console, Input::*, Nation, Nation::*, Output, Player, StateView,
+        TechnologyType::*,
+    };
+    use crate::{GameEngine, GameState, Side};
+
+    #[test]
+    fn outputs_and_inputs_are_exchanged_as_json() {
+        assert_eq!(
+            r#""ChooseInitiative""#,
+            serde_json::to_string(&Output::ChooseInitiative).unwrap()
+        );
+        assert_eq!(
+            r#"{"LaunchOffensive":["France","Russia"]}"#,
+            serde_json::to_string(&Output::LaunchOffensive(vec![France, Russia])).unwrap()
+        );
+        assert_eq!(
+            r#"{"InitiativeBids":{"allies":1,"empires":2}}"#,
+            serde_json::to_string(&Output::InitiativeBids {
+                allies: 1,
+                empires: 2
+            })
+            .unwrap()
+        );
+
+        for input in [
+            Pass,
+            Number(3),
+            Select(Attack, 2),
+            Offensive(France, Germany, 2),
+            ReinforceMany(vec![(France, 1), (Russia, 2)]),
+        ] {
+            let json = serde_json::to_string(&input).unwrap();
+            assert_eq!(input, serde_json::from_str(&json).unwrap());
+        }
+        assert_eq!(
+            Offensive(France, Germany, 2),
+            serde_json::from_str(r#"{"Offensive":["France","Germany",2]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_lines_are_tagged_with_the_side_and_hide_the_state_internals() {
+        let state = GameState::new(42);
+
+        let line = json_line(Side::Empires, &Output::CurrentState(state.clone()));
+
+        let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!("Empires", json["side"]);
+        assert_eq!(
+            serde_json::to_value(StateView::from(&state)).unwrap(),
+            json["output"]["CurrentState"]
+        );
+        for hidden in ["rng", "seed", "events_pool", "forced_events"] {
+            assert!(!line.contains(hidden), "{}", hidden);
+        }
+        assert_eq!(
+            r#"{"output":"ChooseInitiative","side":"Allies"}"#,
+            json_line(Side::Allies, &Output::ChooseInitiative)
+        );
+    }
+
+    #[test]
+    fn offensive_prompt_lists_reachable_enemies_of_each_attacker() {
+        let engine = GameEngine::new(42);
+        let console = Console {
+            side: Side::Allies,
+            inp: Box::new(empty()),
+            outp: Box::new(sink()),
+            out: vec![],
+            prompt: None,
+            reveal_events: false,
+            upcoming_events: vec![],
+            handoff: None,
+        };
+
+        let text = console.render(&Output::LaunchOffensive(vec![France, Russia]), &engine);
+
+        assert!(text.contains("France -> Germany"), "{}", text);
+        assert!(text.contains("Russia -> "), "{}", text);
+    }
+
+    #[test]
+    fn help_lists_commands_of_the_last_prompt_and_asks_again() {
+        let engine = GameEngine::new(42);
+        let mut console = Console {
+            side: Side::Allies,
+            inp: Box::new(Cursor::new("help\noff france germany 2\n")),
+            outp: Box::new(sink()),
+            out: vec![],
+            prompt: None,
+            reveal_events: false,
+            upcoming_events: vec![],
+            handoff: None,
+        };
+
+        console.output(&Output::LaunchOffensive(vec![France]), &engine);
+
+        assert!(console.help().contains("off <from> <to> <pr>"));
+        assert_eq!(Offensive(France, Germany, 2), console.input());
+    }
+
+    #[test]
+    fn console_resigns_at_the_end_of_the_input() {
+        let mut console = Console {
+            side: Side::Allies,
+            inp: Box::new(Cursor::new("help\nfoo\n")),
+            outp: Box::new(sink()),
+            out: vec![],
+            prompt: None,
+            reveal_events: false,
+            upcoming_events: vec![],
+            handoff: None,
+        };
+
+        assert_eq!(Resign, console.input());
+    }
+
+    #[test]
+    fn help_lists_the_nation_names_commands_accept() {
+        let console = Console {
+            side: Side::Allies,
+            inp: Box::new(empty()),
+            outp: Box::new(sink()),
+            out: vec![],
+            prompt: None,
+            reveal_events: false,
+            upcoming_events: vec![],
+            handoff: None,
+        };
+        let help = console.help();
+        let names = help.lines().last().unwrap().trim_start_matches("Nations: ");
+
+        for name in names.split(", ") {
+            assert!(country(name).is_ok(), "{}", name);
+        }
+        assert_eq!(Nation::values().count(), names.split(", ").count());
+    }
 
     #[test]
     fn parses_next_command() {
@@ -273,6 +869,13 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parses_resign_command() {
+        for command in &["resign", "Resign", "RESIGN"] {
+            assert_eq!(parse(command), Ok(Resign));
+        }
+    }
+
     #[test]
     fn parses_select_command() {
         assert_eq!(parse("attack 2"), Ok(Select(Attack, 2)));
@@ -295,6 +898,14 @@ mod tests {
         assert_eq!(parse("reinforce France 2"), Ok(Reinforce(France, 2)));
     }
 
+    #[test]
+    fn parses_reinforce_command_for_several_nations() {
+        assert_eq!(
+            parse("reinforce France 2, Russia 1"),
+            Ok(ReinforceMany(vec![(France, 2), (Russia, 1)]))
+        );
+    }
+
     #[test]
     fn parses_apply_hit_command() {
         assert_eq!(parse("hit France"), Ok(ApplyHit(France)));
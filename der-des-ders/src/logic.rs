@@ -254,3 +254,30 @@ impl GameLogic for DummyLogic {
         panic!("dummy logic")
     }
 }
+
+/// Dice rolled by an offensive: one per PR spent, plus one per level of artillery the
+/// attacking nation can use, which is no more than its maximum technology level
+pub fn effective_dice(pr: u8, artillery: u8, max_tech_level: u8) -> u8 {
+    pr + artillery.min(max_tech_level)
+}
+
+#[cfg(test)]
+mod logic_tests {
+    use super::effective_dice;
+
+    #[test]
+    fn artillery_below_maximum_technology_level_adds_a_die_per_level() {
+        assert_eq!(4, effective_dice(3, 1, 2));
+    }
+
+    #[test]
+    fn artillery_at_maximum_technology_level_adds_a_die_per_level() {
+        assert_eq!(5, effective_dice(3, 2, 2));
+    }
+
+    #[test]
+    fn artillery_above_maximum_technology_level_is_capped() {
+        assert_eq!(5, effective_dice(3, 4, 2));
+        assert_eq!(1, effective_dice(1, 3, 0));
+    }
+}
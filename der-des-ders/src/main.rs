@@ -1,9 +1,18 @@
 use clap::Parser;
 use clap::ValueEnum;
-use minimax::Robot;
-use robot::RobotIO;
+use minimax::{Resignation, Robot, RobotConfig};
+use robot::{possible_hits, RobotIO};
+use std::cell::Cell;
 use std::io::{stdin, stdout};
+use std::path::PathBuf;
 use std::process::exit;
+use std::rc::Rc;
+
+mod action_log;
+mod config;
+use config::*;
+
+mod countries;
 
 mod tech;
 use tech::TechnologyType::*;
@@ -29,12 +38,20 @@ mod fixtures;
 mod logic;
 mod minimax;
 mod robot;
-
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+mod save;
+mod scenario;
+use scenario::{Scenario, Tutor};
+use sim::BatchPlayer;
+mod sim;
+mod turn_log;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, ValueEnum)]
 enum PlayerType {
     Human,
     Robot,
     Search,
+    /// JSON lines on stdin and stdout, for non-interactive clients
+    Json,
 }
 
 /// Sets types of player for allies and empires and optionally provide a seed
@@ -53,6 +70,54 @@ struct Options {
     /// Optional depth for minimax algorithm
     #[arg(short, long, default_value_t = 10)]
     depth: u8,
+    /// Compare search robots of --depth and this depth instead of playing a game
+    #[arg(long)]
+    compare: Option<u8>,
+    /// Number of seeds to play when comparing robots, starting at --seed: each seed is
+    /// played twice, the robots swapping sides
+    #[arg(long, default_value_t = 10)]
+    games: u64,
+    /// When comparing robots, a robot resigns after 3 consecutive turns with a game
+    /// valuation worse than minus this threshold
+    #[arg(long)]
+    resign: Option<f64>,
+    /// Developer flag letting human players `peek` at the upcoming events
+    #[arg(long)]
+    reveal_events: bool,
+    /// JSON file replacing the built-in nations' data
+    #[arg(long)]
+    countries: Option<PathBuf>,
+    /// Search robots sample moves by valuation at this temperature instead of always
+    /// playing the best one
+    #[arg(long, default_value_t = 0.0)]
+    robot_temperature: f64,
+    /// Let human players type `handoff` to have a search robot play for them from the
+    /// next turn
+    #[arg(long)]
+    handoff: bool,
+    /// Start from a built-in scenario instead of the standard setup, ignoring --seed
+    #[arg(long, value_enum)]
+    scenario: Option<Scenario>,
+    /// Start from the scenario in this TOML file instead of the standard setup,
+    /// ignoring --seed, see `scenarios/`
+    #[arg(long, conflicts_with = "scenario")]
+    scenario_file: Option<PathBuf>,
+    /// Continue the game saved in this file, ignoring the options setting up a new game
+    #[arg(long)]
+    load: Option<PathBuf>,
+    /// Save the game to this file at the end of every turn
+    #[arg(long)]
+    save: Option<PathBuf>,
+    /// Play this many games between non-human players, with seeds starting at --seed,
+    /// and print their statistics instead of playing a game
+    #[arg(long)]
+    batch: Option<usize>,
+    /// End the game after this turn, scoring it as at the end of the war
+    #[arg(long, default_value_t = 14)]
+    max_turns: u8,
+    /// Never end the game on a surrender's sudden death roll, play until the turn limit
+    #[arg(long)]
+    no_sudden_death: bool,
 }
 
 impl Default for Options {
@@ -62,6 +127,20 @@ impl Default for Options {
             empires: PlayerType::Human,
             seed: 42,
             depth: 10,
+            compare: None,
+            games: 10,
+            resign: None,
+            reveal_events: false,
+            countries: None,
+            robot_temperature: 0.0,
+            handoff: false,
+            scenario: None,
+            scenario_file: None,
+            load: None,
+            save: None,
+            batch: None,
+            max_turns: 14,
+            no_sudden_death: false,
         }
     }
 }
@@ -73,27 +152,187 @@ struct Players {
 
 fn main() {
     let options = Options::parse();
-    let mut game_engine = GameEngine::new(options.seed);
-    let mut players = initialise_players(&options);
+    if let Err(err) = validate_tech_tables() {
+        eprintln!("Invalid technology tables: {}", err);
+        exit(2);
+    }
+    if let Err(err) = robot_config(&options).validate() {
+        eprintln!("Invalid robot configuration: {}", err);
+        exit(2);
+    }
+    if let Some(depth) = options.compare {
+        let resign = options.resign.map(|threshold| Resignation {
+            threshold,
+            turns: 3,
+        });
+        let comparison = sim::compare_ais(
+            RobotConfig {
+                depth: options.depth,
+                resign,
+                temperature: options.robot_temperature,
+            },
+            RobotConfig {
+                depth,
+                resign,
+                temperature: options.robot_temperature,
+            },
+            options.seed..options.seed + options.games,
+        );
+        println!("{}", comparison);
+        return;
+    }
+    if let Some(games) = options.batch {
+        let (Ok(allies), Ok(empires)) = (
+            BatchPlayer::try_from(options.allies),
+            BatchPlayer::try_from(options.empires),
+        ) else {
+            eprintln!("Batch games need robot or search players on both sides, see --allies and --empires");
+            exit(2);
+        };
+        let stats = sim::run_batch(games, options.seed, allies, empires, options.depth);
+        println!("{}", stats);
+        return;
+    }
+    let mut game_engine = match &options.load {
+        Some(path) => GameEngine::load(path).unwrap_or_else(|err| {
+            eprintln!("Invalid saved game: {}", err);
+            exit(2);
+        }),
+        None => new_game(&options),
+    };
+    if let Some(path) = options.save.clone() {
+        game_engine.on_turn_end(move |state| {
+            if let Err(err) = save::save_game(state, &path) {
+                eprintln!("Cannot save game: {}", err);
+            }
+        });
+    }
+    let handoffs = HandoffRequests::default();
+    let mut players = initialise_players(&options, &handoffs);
+    let mut resumed = options
+        .load
+        .as_ref()
+        .map(|_| TurnStep::of(&game_engine.state));
     while !game_engine.game_ends() {
-        run_turn(&mut players, &mut game_engine);
+        match resumed.take() {
+            Some(step) => play_turn_from(step, &mut players, &mut game_engine),
+            None => run_turn(&mut players, &mut game_engine),
+        }
+        hand_over(&mut players, &handoffs, &options);
+    }
+    game_over(&mut players, &game_engine);
+    println!("{}", game_engine.state.explain_winner());
+    match game_engine.result() {
+        GameResult::AlliesWin => exit(1),
+        GameResult::EmpiresWin => exit(-1),
+        GameResult::Draw => exit(0),
+    }
+}
+
+/// Tell both players the game is over, with its final tally
+fn game_over(players: &mut Players, game_engine: &GameEngine) {
+    let state = &game_engine.state;
+    tell(
+        players,
+        &Output::GameOver {
+            result: game_engine.result(),
+            allies_vp: game_engine.vp_for(Side::Allies),
+            empires_vp: game_engine.vp_for(Side::Empires),
+            turns: state.turns_played(),
+        },
+        game_engine,
+    );
+}
+
+/// Start a game from the options' scenario or seed, and countries
+fn new_game(options: &Options) -> GameEngine {
+    let mut game_engine = match (options.scenario, &options.scenario_file) {
+        (Some(scenario), _) => GameEngine::from_state(scenario.state()),
+        (None, Some(path)) => GameEngine::from_scenario(path).unwrap_or_else(|err| {
+            eprintln!("Invalid scenario: {}", err);
+            exit(2);
+        }),
+        (None, None) => GameEngine::new(options.seed),
+    };
+    game_engine.state.config.countries_path = options.countries.clone();
+    game_engine.state.config.max_turns = options.max_turns;
+    game_engine.state.config.sudden_death = !options.no_sudden_death;
+    if let Err(err) = game_engine.state.load_countries() {
+        eprintln!("Invalid countries: {}", err);
+        exit(2);
+    }
+    game_engine
+}
+
+/// Sides whose human player asked for a robot to take over, see `--handoff`
+#[derive(Default)]
+struct HandoffRequests {
+    allies: Rc<Cell<bool>>,
+    empires: Rc<Cell<bool>>,
+}
+
+impl HandoffRequests {
+    fn of(&self, side: Side) -> &Rc<Cell<bool>> {
+        match side {
+            Side::Allies => &self.allies,
+            Side::Empires => &self.empires,
+        }
     }
-    match game_engine.winner() {
-        Side::Allies => exit(1),
-        Side::Empires => exit(-1),
+}
+
+/// Let search robots play for the sides whose human player asked for a handoff
+fn hand_over(players: &mut Players, handoffs: &HandoffRequests, options: &Options) {
+    for side in Side::iter() {
+        if handoffs.of(side).take() {
+            players.set_player(
+                side,
+                Box::new(Robot::with_config(side, robot_config(options))),
+            );
+        }
     }
 }
 
-fn initialise_players(options: &Options) -> Players {
-    let allies_player = make_player(Side::Allies, options);
-    let empires_player = make_player(Side::Empires, options);
+fn initialise_players(options: &Options, handoffs: &HandoffRequests) -> Players {
+    let mut allies_player = make_player(Side::Allies, options, handoffs);
+    if let Some(scenario) = options.scenario {
+        allies_player = Box::new(Tutor::new(allies_player, scenario.hints()));
+    }
+    let empires_player = make_player(Side::Empires, options, handoffs);
+    if options.allies == PlayerType::Human && options.empires == PlayerType::Human {
+        let (allies_player, empires_player) = Hotseat::pair(allies_player, empires_player);
+        return Players {
+            allies_player: Box::new(allies_player),
+            empires_player: Box::new(empires_player),
+        };
+    }
     Players {
         allies_player,
         empires_player,
     }
 }
 
-fn make_player(side: Side, options: &Options) -> Box<dyn Player> {
+fn robot_config(options: &Options) -> RobotConfig {
+    RobotConfig {
+        depth: options.depth,
+        resign: None,
+        temperature: options.robot_temperature,
+    }
+}
+
+/// Seed of a random robot, derived from the game's seed so that the seed determines
+/// the whole game. Seeds are mixed with splitmix64 so that robots never draw from the
+/// dice's stream, nor from each other's or from the robots of a neighbouring seed.
+fn robot_seed(seed: u64, side: Side) -> u64 {
+    fn splitmix64(x: u64) -> u64 {
+        let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+    splitmix64(splitmix64(seed) ^ (side as u64 + 1))
+}
+
+fn make_player(side: Side, options: &Options, handoffs: &HandoffRequests) -> Box<dyn Player> {
     let player_type: PlayerType = match side {
         Side::Allies => options.allies,
         Side::Empires => options.empires,
@@ -101,16 +340,44 @@ fn make_player(side: Side, options: &Options) -> Box<dyn Player> {
 
     match player_type {
         PlayerType::Human => Box::new(Console {
+            side,
+            inp: Box::new(stdin().lock()),
+            outp: Box::new(stdout()),
+            out: vec![],
+            prompt: None,
+            reveal_events: options.reveal_events,
+            upcoming_events: vec![],
+            handoff: options.handoff.then(|| handoffs.of(side).clone()),
+        }),
+        PlayerType::Robot => Box::new(RobotIO::new(&side, robot_seed(options.seed, side))),
+        PlayerType::Search => Box::new(Robot::with_config(side, robot_config(options))),
+        PlayerType::Json => Box::new(JsonIO {
             side,
             inp: stdin(),
             outp: stdout(),
             out: vec![],
         }),
-        PlayerType::Robot => Box::new(RobotIO::new(&side, 42)),
-        PlayerType::Search => Box::new(Robot::new(side, options.depth)),
     }
 }
 
+impl Players {
+    /// Replace the player of the given side, returning the previous one
+    fn set_player(&mut self, side: Side, player: Box<dyn Player>) -> Box<dyn Player> {
+        let current = match side {
+            Side::Allies => &mut self.allies_player,
+            Side::Empires => &mut self.empires_player,
+        };
+        std::mem::replace(current, player)
+    }
+}
+
+/// Send an output to a player, or to both players through `Players`, invoking the
+/// engine's output hooks once with it
+fn tell(player: &mut dyn Player, message: &Output, game_engine: &GameEngine) {
+    game_engine.notify_output(message);
+    player.output(message, game_engine);
+}
+
 impl Player for Players {
     fn output(&mut self, message: &Output, engine: &GameEngine) {
         self.allies_player.output(message, engine);
@@ -129,47 +396,154 @@ impl Player for Players {
             .cloned()
             .collect()
     }
+
+    fn begin_batch(&mut self) {
+        self.allies_player.begin_batch();
+        self.empires_player.begin_batch();
+    }
+
+    fn end_batch(&mut self) {
+        self.allies_player.end_batch();
+        self.empires_player.end_batch();
+    }
+}
+
+/// Steps of a player's turn, in order
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+enum PlayerStep {
+    Technologies,
+    Offensives,
+    Reinforcements,
+    SeaControl,
+}
+
+/// Steps of a turn, in order
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+enum TurnStep {
+    Initiative,
+    DrawEvents,
+    CollectResources,
+    FirstPlayer(PlayerStep),
+    SecondPlayer(PlayerStep),
+}
+
+impl TurnStep {
+    /// Step of the turn the given state is in, e.g. for a game loaded in the middle of
+    /// a turn to resume where it was saved
+    fn of(state: &GameState) -> TurnStep {
+        let step = match state.phase {
+            Phase::Initiative(_) | Phase::NewTurn => return TurnStep::Initiative,
+            Phase::DrawEvents => return TurnStep::DrawEvents,
+            Phase::CollectResources => return TurnStep::CollectResources,
+            Phase::ImproveTechnologies(_) => PlayerStep::Technologies,
+            Phase::LaunchOffensives(_) => PlayerStep::Offensives,
+            Phase::Reinforcements(_) => PlayerStep::Reinforcements,
+            Phase::UBoot | Phase::Blockade => PlayerStep::SeaControl,
+        };
+        if state.side_to_play() == Some(state.initiative) {
+            TurnStep::FirstPlayer(step)
+        } else {
+            TurnStep::SecondPlayer(step)
+        }
+    }
 }
 
 fn run_turn(players: &mut Players, game_engine: &mut GameEngine) {
-    players.output(
+    play_turn_from(TurnStep::Initiative, players, game_engine);
+}
+
+/// Play a turn from the given step, e.g. for a game loaded in the middle of a turn, a
+/// step interrupted being played again from its start
+fn play_turn_from(resumed: TurnStep, players: &mut Players, game_engine: &mut GameEngine) {
+    tell(
+        players,
         &Output::CurrentState(game_engine.state.clone()),
         &game_engine,
     );
-    determine_initiative(players, game_engine);
-    draw_events(players, game_engine);
-    collect_resources(game_engine);
+    if resumed <= TurnStep::Initiative {
+        determine_initiative(players, game_engine);
+    }
+    if resumed <= TurnStep::DrawEvents {
+        draw_events(players, game_engine);
+    }
+    if resumed <= TurnStep::CollectResources {
+        collect_resources(players, game_engine);
+    }
 
-    players.output(
+    tell(
+        players,
         &Output::CurrentState(game_engine.state.clone()),
         &game_engine,
     );
 
-    run_player_turn(game_engine.state.initiative, players, game_engine);
-    run_player_turn(game_engine.state.initiative.other(), players, game_engine);
+    let initiative = game_engine.state.initiative;
+    match resumed {
+        TurnStep::SecondPlayer(_) => {}
+        TurnStep::FirstPlayer(step) => run_player_turn(initiative, step, players, game_engine),
+        _ => run_player_turn(initiative, PlayerStep::Technologies, players, game_engine),
+    }
+    let step = match resumed {
+        TurnStep::SecondPlayer(step) => step,
+        _ => PlayerStep::Technologies,
+    };
+    run_player_turn(initiative.other(), step, players, game_engine);
+
+    for side in Side::iter() {
+        let wasted = game_engine.state.wasted_pr(&side);
+        if wasted > 0 {
+            tell(players, &Output::WastedResources(side, wasted), game_engine);
+        }
+    }
 
     game_engine.set_phase(Phase::NewTurn);
     game_engine.new_turn();
 }
 
-fn collect_resources(game_engine: &mut GameEngine) {
+fn collect_resources(players: &mut Players, game_engine: &mut GameEngine) {
     game_engine.set_phase(Phase::CollectResources);
-    game_engine.collect_resources()
+    if !game_engine.collects_resources_this_turn() {
+        return;
+    }
+    let gained = game_engine.collect_resources();
+    players.begin_batch();
+    for (side, collected) in Side::iter().zip(gained) {
+        tell(
+            players,
+            &Output::ResourcesCollected {
+                side,
+                collected,
+                breakdown: game_engine.state.resource_breakdown(&side),
+            },
+            game_engine,
+        );
+    }
+    players.end_batch();
 }
 
 fn draw_events(players: &mut Players, game_engine: &mut GameEngine) {
     game_engine.set_phase(Phase::DrawEvents);
     let events = game_engine.draw_events();
+    players.begin_batch();
     for event in events.iter() {
-        players.output(
+        tell(
+            players,
             &Output::EventDrawn(event.event_id, event.title.to_string()),
             &game_engine,
         );
         apply_event(players, game_engine, event);
     }
+    players.end_batch();
 }
 
 fn apply_event(players: &mut Players, game_engine: &mut GameEngine, event: &Event) {
+    if !event.applies_to(&game_engine.state) {
+        tell(
+            players,
+            &Output::EventNotApplicable(event.event_id),
+            game_engine,
+        );
+        return;
+    }
     match event.event_id {
         3 => {
             let offensive = Offensive {
@@ -180,7 +554,8 @@ fn apply_event(players: &mut Players, game_engine: &mut GameEngine, event: &Even
             };
             game_engine.increase_pr(Side::Empires, 2);
             let result = game_engine.resolve_offensive(&offensive);
-            players.output(
+            tell(
+                players,
                 &Output::OffensiveResult {
                     from: Nation::Germany,
                     to: Nation::France,
@@ -193,21 +568,85 @@ fn apply_event(players: &mut Players, game_engine: &mut GameEngine, event: &Even
     }
 }
 
-fn run_player_turn(initiative: Side, players: &mut Players, game_engine: &mut GameEngine) {
+fn run_player_turn(
+    initiative: Side,
+    from: PlayerStep,
+    players: &mut Players,
+    game_engine: &mut GameEngine,
+) {
     notify_turn(initiative, players, game_engine);
-    improve_technologies(initiative, players, game_engine);
-    launch_offensives(initiative, players, game_engine);
-    reinforcements(initiative, players, game_engine);
+    if from <= PlayerStep::Technologies {
+        improve_technologies(initiative, players, game_engine);
+        check_side_to_play(initiative, game_engine);
+    }
+    if from <= PlayerStep::Offensives {
+        launch_offensives(initiative, players, game_engine);
+        check_side_to_play(initiative, game_engine);
+    }
+    if from <= PlayerStep::Reinforcements {
+        reinforcements(initiative, players, game_engine);
+        check_side_to_play(initiative, game_engine);
+    }
     sea_control(initiative, players, game_engine);
+    check_side_to_play(initiative, game_engine);
+}
+
+/// In debug builds, check the phase just played belongs to the side whose turn it is
+fn check_side_to_play(side: Side, game_engine: &GameEngine) {
+    debug_assert_eq!(
+        Some(side),
+        game_engine.state.side_to_play(),
+        "{:?} played during {}'s turn",
+        game_engine.state.phase,
+        side
+    );
 }
 
 fn notify_turn(initiative: Side, players: &mut Players, game_engine: &GameEngine) {
-    players.output(
+    tell(
+        players,
         &Output::TurnFor(initiative, game_engine.state.current_turn),
         &game_engine,
     );
 }
 
+/// The given side concedes the game
+fn resign(side: Side, player: &mut Box<dyn Player>, game_engine: &mut GameEngine) {
+    game_engine.resign(side);
+    tell(player.as_mut(), &Output::Resigned(side), game_engine);
+}
+
+/// Maximum number of inputs from a player a phase rejects before forcing the player
+/// to pass, so that a faulty player cannot loop forever
+const MAX_REJECTED_INPUTS: u8 = 10;
+
+/// Counts inputs rejected in a phase
+struct RejectionGuard {
+    side: Side,
+    rejected: u8,
+}
+
+impl RejectionGuard {
+    fn new(side: Side) -> Self {
+        RejectionGuard { side, rejected: 0 }
+    }
+
+    /// Record a rejected input, returning `true` and notifying the player if it must pass
+    fn reject(&mut self, player: &mut Box<dyn Player>, game_engine: &GameEngine) -> bool {
+        self.rejected += 1;
+        if self.rejected >= MAX_REJECTED_INPUTS {
+            tell(
+                player.as_mut(),
+                &Output::TooManyRejectedInputs(self.side),
+                game_engine,
+            );
+            true
+        } else {
+            false
+        }
+    }
+}
+
 fn improve_technologies(initiative: Side, players: &mut Players, game_engine: &mut GameEngine) {
     let player = match initiative {
         Side::Allies => &mut players.allies_player,
@@ -217,23 +656,41 @@ fn improve_technologies(initiative: Side, players: &mut Players, game_engine: &m
     game_engine.set_phase(Phase::ImproveTechnologies(initiative));
 
     let mut available: Vec<TechnologyType> = vec![Attack, Defense, Artillery, Air];
+    let mut guard = RejectionGuard::new(initiative);
 
     while !available.is_empty() {
-        player.output(
+        tell(
+            player.as_mut(),
             &Output::ImproveTechnologies(available.clone()),
             &game_engine,
         );
-        match player.input() {
+        match read_input(player, game_engine) {
             Input::Select(tech, n) => {
                 if !available.contains(&tech) || n == 0 {
+                    if guard.reject(player, game_engine) {
+                        break;
+                    }
                     continue;
                 }
                 let result = game_engine.try_improve_technology(initiative, tech, n);
-                player.output(&Output::TechnologyResult(result), &game_engine);
+                tell(
+                    player.as_mut(),
+                    &Output::TechnologyResult(result),
+                    &game_engine,
+                );
                 available.retain(|&t| t != tech);
             }
             Input::Pass => break,
-            other => player.output(&Output::WrongInput(other), &game_engine),
+            Input::Resign => {
+                resign(initiative, player, game_engine);
+                break;
+            }
+            other => {
+                tell(player.as_mut(), &Output::WrongInput(other), &game_engine);
+                if guard.reject(player, game_engine) {
+                    break;
+                }
+            }
         }
     }
 }
@@ -246,17 +703,29 @@ fn launch_offensives(initiative: Side, players: &mut Players, game_engine: &mut
 
     game_engine.set_phase(Phase::LaunchOffensives(initiative));
 
-    let mut nations = game_engine.all_nations_at_war(initiative);
-    nations.sort();
+    let sources = game_engine.state.offensive_sources(initiative);
+    let mut guard = RejectionGuard::new(initiative);
 
-    while !nations.is_empty() {
-        player.output(&Output::LaunchOffensive(nations.clone()), &game_engine);
-        match player.input() {
-            Input::Offensive(from, _, _) if !nations.contains(&from) => {
-                player.output(&Output::CountryAlreadyAttacked(from), &game_engine);
-            }
-            Input::Offensive(from, to, _) if !from.adjacent_to(&to) => {
-                player.output(&Output::AttackingNonAdjacentCountry(from, to), &game_engine);
+    loop {
+        let mut nations = sources.clone();
+        nations.retain(|nation| !game_engine.state.has_attacked(nation));
+        if nations.is_empty() {
+            return;
+        }
+        tell(
+            player.as_mut(),
+            &Output::LaunchOffensive(nations.clone()),
+            &game_engine,
+        );
+        let rejected = match read_input(player, game_engine) {
+            Input::Offensive(from, _, _) if !sources.contains(&from) => {
+                let rejection = OffensiveRejection::AlreadyAttacked(from);
+                tell(
+                    player.as_mut(),
+                    &Output::OffensiveRejected(rejection),
+                    game_engine,
+                );
+                true
             }
             Input::Offensive(from, to, pr) => {
                 let offensive = Offensive {
@@ -265,17 +734,35 @@ fn launch_offensives(initiative: Side, players: &mut Players, game_engine: &mut
                     to,
                     pr,
                 };
-                let result = game_engine.resolve_offensive(&offensive);
-                match result {
-                    OffensiveOutcome::Hits(_) => {
-                        nations.retain(|&nat| nat != from);
+                match game_engine.validate_offensive(&offensive) {
+                    Err(rejection) => {
+                        tell(
+                            player.as_mut(),
+                            &Output::OffensiveRejected(rejection),
+                            game_engine,
+                        );
+                        true
+                    }
+                    Ok(()) => {
+                        let result = game_engine.resolve_offensive(&offensive);
+                        tell(
+                            player.as_mut(),
+                            &Output::OffensiveResult { from, to, result },
+                            game_engine,
+                        );
+                        false
                     }
-                    _ => {}
                 }
-                player.output(&Output::OffensiveResult { from, to, result }, &game_engine);
             }
             Input::Pass => return,
-            _ => (),
+            Input::Resign => {
+                resign(initiative, player, game_engine);
+                return;
+            }
+            _ => true,
+        };
+        if rejected && guard.reject(player, game_engine) {
+            return;
         }
     }
 }
@@ -291,14 +778,30 @@ fn uboot(players: &mut Players, game_engine: &mut GameEngine) {
     game_engine.set_phase(Phase::UBoot);
 
     let player = &mut players.empires_player;
-    player.output(&Output::IncreaseUBoot, &game_engine);
-    let bonus = match player.input() {
+    tell(player.as_mut(), &Output::IncreaseUBoot, &game_engine);
+    let bonus = match read_input(player, game_engine) {
         Input::Number(n) => n.min(game_engine.state.resources_for(&Side::Empires)),
+        Input::Resign => {
+            resign(Side::Empires, player, game_engine);
+            0
+        }
         _ => 0,
     };
 
     let change = game_engine.uboot_losses(bonus);
     let loss = change.allies_loss();
+    let pr = game_engine.state.resources_for(&Side::Allies);
+
+    tell(
+        players,
+        &Output::UBootResult {
+            roll: game_engine.state.last_roll(),
+            bonus,
+            loss,
+            hits: loss.saturating_sub(pr),
+        },
+        &game_engine,
+    );
 
     let pr_lost = apply_hits(players, game_engine, loss);
 
@@ -306,18 +809,46 @@ fn uboot(players: &mut Players, game_engine: &mut GameEngine) {
 }
 
 fn apply_hits(players: &mut Players, game_engine: &mut GameEngine, loss: u8) -> StateChange {
-    players.output(&Output::UBootResult(loss), &game_engine);
-
     let allies_player = &mut players.allies_player;
     let pr = game_engine.state.resources_for(&Side::Allies);
 
     if loss > pr {
         let mut hits = loss - pr;
+        let mut guard = RejectionGuard::new(Side::Allies);
         while hits > 0 {
-            allies_player.output(&Output::SelectNationForHit, &game_engine);
-            if let Input::ApplyHit(nation) = allies_player.input() {
-                game_engine.apply_hits(&nation, 1);
-                hits -= 1;
+            tell(
+                allies_player.as_mut(),
+                &Output::SelectNationForHit,
+                &game_engine,
+            );
+            let rejection = match read_input(allies_player, game_engine) {
+                Input::ApplyHit(nation)
+                    if game_engine.state.can_take_hit(Side::Allies, &nation) =>
+                {
+                    game_engine.apply_hits(&nation, 1);
+                    hits -= 1;
+                    continue;
+                }
+                Input::ApplyHit(nation) => Output::InvalidHit(nation),
+                Input::Resign => {
+                    resign(Side::Allies, allies_player, game_engine);
+                    break;
+                }
+                other => Output::WrongInput(other),
+            };
+            tell(allies_player.as_mut(), &rejection, game_engine);
+            if guard.reject(allies_player, game_engine) {
+                // hits cannot be dodged: the remaining ones go where a robot would put them
+                while hits > 0 {
+                    let Some((nation, _)) = possible_hits(&Side::Allies, &game_engine.state)
+                        .first()
+                        .copied()
+                    else {
+                        break;
+                    };
+                    game_engine.apply_hits(&nation, 1);
+                    hits -= 1;
+                }
             }
         }
         StateChange::ChangeResources {
@@ -333,16 +864,28 @@ fn blocus(players: &mut Players, game_engine: &mut GameEngine) {
     game_engine.set_phase(Phase::Blockade);
 
     let player = &mut players.allies_player;
-    player.output(&Output::IncreaseBlockade, &game_engine);
-    let bonus = match player.input() {
+    tell(player.as_mut(), &Output::IncreaseBlockade, &game_engine);
+    let bonus = match read_input(player, game_engine) {
         Input::Number(n) => n.min(game_engine.state.resources_for(&Side::Allies)),
+        Input::Resign => {
+            resign(Side::Allies, player, game_engine);
+            0
+        }
         _ => 0,
     };
 
     let change = game_engine.blockade_effect(bonus);
 
     game_engine.apply_change(&change);
-    players.output(&Output::BlockadeResult(change.empires_gain()), &game_engine);
+    tell(
+        players,
+        &Output::BlockadeResult {
+            roll: game_engine.state.last_roll(),
+            bonus,
+            gain: change.empires_gain(),
+        },
+        &game_engine,
+    );
 }
 
 const DEFAULT_INITIATIVE: [Side; 14] = [
@@ -369,22 +912,89 @@ const DEFAULT_INITIATIVE: [Side; 14] = [
 ///   total has the initiative. In case of a tie, the initiative is defined from the DEFAULT_INITIATIVE
 ///   array.
 fn determine_initiative(players: &mut Players, game_engine: &mut GameEngine) {
-    if game_engine.state.current_turn > 1 {
-        players.output(&Output::ChooseInitiative, &game_engine);
+    if game_engine.state.current_turn == 1 {
+        return;
+    }
+    if game_engine.state.config.simultaneous_initiative {
         game_engine.set_phase(Phase::Initiative(Side::Allies));
-        let allies_pr = match players.allies_player.input() {
-            Input::Number(pr) => pr,
-            _ => 0,
-        };
+        tell(
+            players.allies_player.as_mut(),
+            &Output::ChooseInitiative,
+            game_engine,
+        );
         game_engine.set_phase(Phase::Initiative(Side::Empires));
-        let empires_pr = match players.empires_player.input() {
-            Input::Number(pr) => pr,
-            _ => 0,
-        };
+        tell(
+            players.empires_player.as_mut(),
+            &Output::ChooseInitiative,
+            game_engine,
+        );
+        let allies_pr = read_bid(Side::Allies, &mut players.allies_player, game_engine);
+        let empires_pr = read_bid(Side::Empires, &mut players.empires_player, game_engine);
+        game_engine.determine_initiative(allies_pr, empires_pr);
+        tell(
+            players,
+            &Output::InitiativeBids {
+                allies: allies_pr,
+                empires: empires_pr,
+            },
+            game_engine,
+        );
+    } else {
+        game_engine.set_phase(Phase::Initiative(Side::Allies));
+        tell(
+            players.allies_player.as_mut(),
+            &Output::ChooseInitiative,
+            game_engine,
+        );
+        let allies_pr = read_bid(Side::Allies, &mut players.allies_player, game_engine);
+        game_engine.set_phase(Phase::Initiative(Side::Empires));
+        tell(
+            players.empires_player.as_mut(),
+            &Output::ChooseInitiative,
+            game_engine,
+        );
+        let empires_pr = read_bid(Side::Empires, &mut players.empires_player, game_engine);
         game_engine.determine_initiative(allies_pr, empires_pr);
     }
 }
 
+/// Read the next input of the given player, recording it in the game's action log
+fn read_input(player: &mut Box<dyn Player>, game_engine: &mut GameEngine) -> Input {
+    game_engine.read_input(player.as_mut())
+}
+
+fn read_bid(side: Side, player: &mut Box<dyn Player>, game_engine: &mut GameEngine) -> u8 {
+    match read_input(player, game_engine) {
+        Input::Number(pr) => pr,
+        Input::Resign => {
+            resign(side, player, game_engine);
+            0
+        }
+        _ => 0,
+    }
+}
+
+/// Reinforce the nation with the given PR, telling the player when the reinforcement is
+/// rejected or stops at the nation's maximum breakdown
+fn reinforce(
+    side: Side,
+    nation: Nation,
+    pr: u8,
+    player: &mut Box<dyn Player>,
+    game_engine: &mut GameEngine,
+) {
+    let rejection = match game_engine.reinforce(side, nation, pr) {
+        Ok(result) if result.capped => ReinforceError::MaximumBreakdown(nation),
+        Ok(_) => return,
+        Err(err) => err,
+    };
+    tell(
+        player.as_mut(),
+        &Output::InvalidReinforcement(rejection),
+        game_engine,
+    );
+}
+
 fn reinforcements(initiative: Side, players: &mut Players, game_engine: &mut GameEngine) {
     let player = match initiative {
         Side::Allies => &mut players.allies_player,
@@ -392,6 +1002,7 @@ fn reinforcements(initiative: Side, players: &mut Players, game_engine: &mut Gam
     };
 
     game_engine.set_phase(Phase::Reinforcements(initiative));
+    let mut guard = RejectionGuard::new(initiative);
 
     while game_engine
         .state
@@ -401,29 +1012,164 @@ fn reinforcements(initiative: Side, players: &mut Players, game_engine: &mut Gam
         .resources
         > 0
     {
-        player.output(&Output::ReinforceNations, &game_engine);
-        match player.input() {
+        tell(player.as_mut(), &Output::ReinforceNations, &game_engine);
+        let resources = game_engine.state.resources_for(&initiative);
+        match read_input(player, game_engine) {
             Input::Reinforce(nation, pr) => {
-                game_engine.reinforce(nation, pr);
+                reinforce(initiative, nation, pr, player, game_engine);
+            }
+            Input::ReinforceMany(plan) => {
+                let cost = plan.iter().map(|(_, pr)| *pr as u16).sum::<u16>();
+                if cost > resources as u16 {
+                    tell(
+                        player.as_mut(),
+                        &Output::ReinforcementPlanRejected {
+                            cost: cost.min(u8::MAX as u16) as u8,
+                            resources,
+                        },
+                        game_engine,
+                    );
+                } else {
+                    for (nation, pr) in plan {
+                        reinforce(initiative, nation, pr, player, game_engine);
+                    }
+                }
             }
             Input::Pass => break,
-            _ => continue,
+            Input::Resign => {
+                resign(initiative, player, game_engine);
+                break;
+            }
+            other => tell(player.as_mut(), &Output::WrongInput(other), game_engine),
+        }
+        let nothing_spent = game_engine.state.resources_for(&initiative) == resources;
+        if nothing_spent && guard.reject(player, game_engine) {
+            break;
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
     use crate::{
-        determine_initiative,
-        fixtures::{EngineBuilder, PlayersBuilder},
-        GameEngine,
+        collect_resources, determine_initiative,
+        fixtures::{EngineBuilder, PlayerDouble, PlayersBuilder},
+        game_over, hand_over,
+        minimax::{Resignation, Robot, RobotConfig},
+        play_turn_from, run_turn, GameConfig, GameEngine, GameResult, HandoffRequests, Hotseat,
         Input::*,
         Nation::*,
         NationState::*,
+        Options,
+        Output::{self, *},
+        Phase, Players,
         Side::*,
+        Technologies, TurnStep,
     };
 
+    #[test]
+    fn loaded_game_resumes_turn_at_saved_phase() {
+        let path = std::env::temp_dir().join("der-des-ders-mid-turn.json");
+        EngineBuilder::new(12)
+            .with_initiative(Allies)
+            .on_turn(2)
+            .at_phase(Phase::LaunchOffensives(Allies))
+            .build()
+            .save(&path)
+            .unwrap();
+        let mut engine = GameEngine::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Pass)
+            .with_input(Allies, Pass)
+            .with_input(Allies, Number(0))
+            .with_input(Empires, Pass)
+            .with_input(Empires, Pass)
+            .with_input(Empires, Pass)
+            .with_input(Empires, Number(0))
+            .build();
+
+        play_turn_from(TurnStep::of(&engine.state), &mut players, &mut engine);
+
+        let allies_out = players.allies_player.out();
+        assert_eq!(TurnFor(Allies, 2), allies_out[2]);
+        assert!(matches!(allies_out[3], LaunchOffensive(_)));
+        assert!(!allies_out
+            .iter()
+            .any(|out| matches!(out, ImproveTechnologies(_) | EventDrawn(_, _))));
+        assert_eq!(3, engine.state.current_turn);
+    }
+
+    #[test]
+    fn finished_game_tells_players_its_winner_once() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_nation(France, AtWar(4))
+            .build();
+        engine.apply_hits(&France, 4);
+        let mut players = PlayersBuilder::new().build();
+
+        game_over(&mut players, &engine);
+
+        let game_overs: Vec<Output> = players
+            .allies_player
+            .out()
+            .into_iter()
+            .filter(|out| matches!(out, GameOver { .. }))
+            .collect();
+        assert_eq!(
+            vec![GameOver {
+                result: GameResult::EmpiresWin,
+                allies_vp: 0,
+                empires_vp: 6,
+                turns: 1,
+            }],
+            game_overs
+        );
+    }
+
+    #[test]
+    fn finished_game_tells_players_a_draw_given_equal_vp() {
+        let engine = EngineBuilder::new(14).on_turn(15).build();
+        let mut players = PlayersBuilder::new().build();
+
+        game_over(&mut players, &engine);
+
+        let game_over = players
+            .empires_player
+            .out()
+            .into_iter()
+            .find(|out| matches!(out, GameOver { .. }))
+            .unwrap();
+        assert_eq!(
+            "Game over after 14 turns: Draw (VP 0 for Allies, 0 for Empires)",
+            game_over.to_string()
+        );
+    }
+
+    #[test]
+    fn output_callback_observes_outputs_sent_to_both_players_or_to_one_side() {
+        let mut engine = EngineBuilder::new(42).build();
+        let outputs = Rc::new(RefCell::new(Vec::new()));
+        let observed = outputs.clone();
+        engine.on_output(move |output| observed.borrow_mut().push(output.clone()));
+        let mut players = Players {
+            allies_player: Box::new(Robot::new(Allies, 1)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+
+        run_turn(&mut players, &mut engine);
+
+        assert!(matches!(
+            outputs.borrow().first(),
+            Some(CurrentState(state)) if state.current_turn == 1
+        ));
+        let outputs = outputs.borrow();
+        assert!(outputs.iter().any(|o| matches!(o, LaunchOffensive(_))));
+        assert!(outputs.iter().any(|o| matches!(o, ReinforceNations)));
+    }
+
     #[test]
     fn adjusts_resources_given_a_side_and_some_amount() {
         let mut engine = GameEngine::new(12);
@@ -464,71 +1210,206 @@ mod tests {
     }
 
     #[test]
-    fn empires_have_initiative_on_second_turn_given_they_bid_more_pr() {
-        let mut engine = EngineBuilder::new(12).on_turn(2).build();
+    fn side_bidding_below_minimum_concedes_initiative() {
+        let mut engine = EngineBuilder::new(12)
+            .with_resources(Allies, 3)
+            .with_resources(Empires, 3)
+            .with_config(GameConfig {
+                min_initiative_bid: 2,
+                ..GameConfig::default()
+            })
+            .on_turn(2)
+            .build();
         let mut players = PlayersBuilder::new()
-            .with_input(Allies, Number(1))
-            .with_input(Empires, Number(2))
+            .with_input(Allies, Number(2))
+            .with_input(Empires, Number(1))
             .build();
 
         determine_initiative(&mut players, &mut engine);
 
-        assert_eq!(Empires, engine.state.initiative)
+        assert_eq!(Allies, engine.state.initiative);
+        assert_eq!(1, engine.state.resources_for(&Allies));
+        assert_eq!(3, engine.state.resources_for(&Empires));
     }
 
     #[test]
-    fn initiative_consumes_bid_pr_from_both_sides() {
-        let allies_bid = 1;
-        let empires_bid = 2;
-        let initial_allies_resources = 4;
-        let initial_empires_resources = 5;
+    fn robot_plays_the_turn_after_a_side_is_handed_over_to_it() {
+        let mut engine = EngineBuilder::new(14)
+            .on_turn(2)
+            .with_resources(Allies, 5)
+            .with_resources(Empires, 5)
+            .build();
+        let options = Options {
+            depth: 1,
+            ..Options::default()
+        };
+        let handoffs = HandoffRequests::default();
+        // the human has no input left: the turn can only be played by the robot
+        let mut players = Players {
+            allies_player: Box::new(PlayerDouble {
+                out: vec![],
+                inp: vec![],
+            }),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+        handoffs.of(Allies).set(true);
 
+        hand_over(&mut players, &handoffs, &options);
+        run_turn(&mut players, &mut engine);
+
+        assert!(!handoffs.of(Allies).get());
+        assert_eq!(3, engine.state.current_turn);
+    }
+
+    #[test]
+    fn simultaneous_bids_are_only_revealed_once_both_sides_have_bid() {
         let mut engine = EngineBuilder::new(12)
-            .with_resources(Allies, initial_allies_resources)
-            .with_resources(Empires, initial_empires_resources)
+            .with_resources(Allies, 3)
+            .with_resources(Empires, 3)
+            .with_config(GameConfig {
+                simultaneous_initiative: true,
+                ..GameConfig::default()
+            })
             .on_turn(2)
             .build();
-
         let mut players = PlayersBuilder::new()
-            .with_input(Allies, Number(allies_bid))
-            .with_input(Empires, Number(empires_bid))
+            .with_input(Allies, Number(2))
+            .with_input(Empires, Number(1))
             .build();
 
         determine_initiative(&mut players, &mut engine);
 
+        let revealed = InitiativeBids {
+            allies: 2,
+            empires: 1,
+        };
         assert_eq!(
-            initial_allies_resources - allies_bid,
-            engine.state.state_of_war.get(&Allies).unwrap().resources
+            vec![ChooseInitiative, revealed.clone()],
+            players.empires_player.out()
         );
         assert_eq!(
-            initial_empires_resources - empires_bid,
-            engine.state.state_of_war.get(&Empires).unwrap().resources
-        )
+            vec![ChooseInitiative, revealed],
+            players.allies_player.out()
+        );
     }
 
     #[test]
-    fn allies_have_initiative_if_die_roll_is_better_given_equal_bid() {
-        let mut engine = EngineBuilder::new(14)
-            .with_resources(Allies, 4)
-            .with_resources(Empires, 5)
-            .on_turn(2)
-            .build();
-
-        let allies_bid = 2;
-        let empires_bid = 2;
-
+    fn empires_have_initiative_on_second_turn_given_they_bid_more_pr() {
+        let mut engine = EngineBuilder::new(12).on_turn(2).build();
         let mut players = PlayersBuilder::new()
-            .with_input(Allies, Number(allies_bid))
-            .with_input(Empires, Number(empires_bid))
+            .with_input(Allies, Number(1))
+            .with_input(Empires, Number(2))
             .build();
 
         determine_initiative(&mut players, &mut engine);
 
-        assert_eq!(Allies, engine.state.initiative)
+        assert_eq!(Empires, engine.state.initiative)
     }
 
     #[test]
-    fn empires_have_initiative_on_turn_2_given_equal_bid_and_die() {
+    fn hotseat_asks_to_pass_keyboard_before_prompting_other_side() {
+        let mut engine = EngineBuilder::new(12).on_turn(2).build();
+        let (allies, empires) = Hotseat::pair(
+            Box::new(PlayerDouble {
+                out: vec![],
+                inp: vec![Number(1)],
+            }),
+            Box::new(PlayerDouble {
+                out: vec![],
+                inp: vec![Number(2), Next],
+            }),
+        );
+        let mut players = Players {
+            allies_player: Box::new(allies),
+            empires_player: Box::new(empires),
+        };
+
+        determine_initiative(&mut players, &mut engine);
+
+        assert_eq!(vec![ChooseInitiative], players.allies_player.out());
+        assert_eq!(
+            vec![PassKeyboard(Empires), ChooseInitiative],
+            players.empires_player.out()
+        );
+    }
+
+    #[test]
+    fn hotseat_passes_on_resignation_instead_of_keyboard_handover() {
+        let mut engine = EngineBuilder::new(12).on_turn(2).build();
+        let (allies, empires) = Hotseat::pair(
+            Box::new(PlayerDouble {
+                out: vec![],
+                inp: vec![Number(1)],
+            }),
+            Box::new(PlayerDouble {
+                out: vec![],
+                inp: vec![Resign],
+            }),
+        );
+        let mut players = Players {
+            allies_player: Box::new(allies),
+            empires_player: Box::new(empires),
+        };
+
+        determine_initiative(&mut players, &mut engine);
+
+        assert!(engine.game_ends());
+        assert_eq!(GameResult::AlliesWin, engine.result());
+    }
+
+    #[test]
+    fn initiative_consumes_bid_pr_from_both_sides() {
+        let allies_bid = 1;
+        let empires_bid = 2;
+        let initial_allies_resources = 4;
+        let initial_empires_resources = 5;
+
+        let mut engine = EngineBuilder::new(12)
+            .with_resources(Allies, initial_allies_resources)
+            .with_resources(Empires, initial_empires_resources)
+            .on_turn(2)
+            .build();
+
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Number(allies_bid))
+            .with_input(Empires, Number(empires_bid))
+            .build();
+
+        determine_initiative(&mut players, &mut engine);
+
+        assert_eq!(
+            initial_allies_resources - allies_bid,
+            engine.state.state_of_war.get(&Allies).unwrap().resources
+        );
+        assert_eq!(
+            initial_empires_resources - empires_bid,
+            engine.state.state_of_war.get(&Empires).unwrap().resources
+        )
+    }
+
+    #[test]
+    fn allies_have_initiative_if_die_roll_is_better_given_equal_bid() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_resources(Empires, 5)
+            .on_turn(2)
+            .build();
+
+        let allies_bid = 2;
+        let empires_bid = 2;
+
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Number(allies_bid))
+            .with_input(Empires, Number(empires_bid))
+            .build();
+
+        determine_initiative(&mut players, &mut engine);
+
+        assert_eq!(Allies, engine.state.initiative)
+    }
+
+    #[test]
+    fn empires_have_initiative_on_turn_2_given_equal_bid_and_die() {
         let mut engine = EngineBuilder::new(14)
             .with_resources(Allies, 4)
             .with_resources(Empires, 5)
@@ -574,14 +1455,73 @@ mod tests {
     }
 
     #[test]
-    fn collect_resources_cannot_increase_pr_over_20() {
+    fn collect_resources_reports_what_each_nation_brings() {
         let mut engine = EngineBuilder::new(14).build();
+        let mut players = PlayersBuilder::new().build();
+        let russia = engine.state.operational_level(&Russia) * 2;
+
+        collect_resources(&mut players, &mut engine);
+
+        let allies = engine.state.resource_breakdown(&Allies);
+        let empires = engine.state.resource_breakdown(&Empires);
+        assert_eq!(14, allies.iter().map(|(_, pr)| pr).sum::<u8>());
+        assert_eq!(9, empires.iter().map(|(_, pr)| pr).sum::<u8>());
+        assert!(allies.contains(&(Russia, russia)), "{:?}", allies);
+        assert_eq!(
+            vec![
+                Output::ResourcesCollected {
+                    side: Allies,
+                    collected: 14,
+                    breakdown: allies
+                },
+                Output::ResourcesCollected {
+                    side: Empires,
+                    collected: 9,
+                    breakdown: empires
+                },
+            ],
+            players.allies_player.out()
+        );
+    }
+
+    #[test]
+    fn collect_resources_reports_pr_actually_gained_under_the_cap() {
+        let mut engine = EngineBuilder::new(14).with_resources(Allies, 18).build();
+        let mut players = PlayersBuilder::new().build();
+
+        collect_resources(&mut players, &mut engine);
+
+        assert!(matches!(
+            &players.allies_player.out()[0],
+            Output::ResourcesCollected { side: Allies, collected: 2, breakdown }
+                if breakdown.iter().map(|(_, pr)| pr).sum::<u8>() == 14
+        ));
+    }
+
+    #[test]
+    fn collect_resources_cannot_increase_pr_over_20() {
+        assert_eq!((20, 18), resources_after_two_collections(20));
+    }
+
+    #[test]
+    fn collect_resources_cannot_increase_pr_over_configured_maximum() {
+        assert_eq!((15, 15), resources_after_two_collections(15));
+    }
+
+    /// Allies and Empires resources after collecting twice from the start of the game,
+    /// a side stockpiling at most `max_resources`
+    fn resources_after_two_collections(max_resources: u8) -> (u8, u8) {
+        let mut engine = EngineBuilder::new(14)
+            .with_max_resources(max_resources)
+            .build();
 
         engine.collect_resources();
         engine.collect_resources();
 
-        assert_eq!(20, engine.state.resources_for(&Allies));
-        assert_eq!(18, engine.state.resources_for(&Empires));
+        (
+            engine.state.resources_for(&Allies),
+            engine.state.resources_for(&Empires),
+        )
     }
 
     #[test]
@@ -615,6 +1555,108 @@ mod tests {
         assert_eq!(14, engine.state.resources_for(&Allies));
         assert_eq!(10, engine.state.resources_for(&Empires));
     }
+
+    #[test]
+    fn robot_resigns_hopeless_game_and_opponent_wins() {
+        let mut engine = EngineBuilder::new(12)
+            .with_resources(Empires, 20)
+            .with_technologies(
+                Empires,
+                Technologies {
+                    attack: 3,
+                    defense: 4,
+                    artillery: 3,
+                    air: 3,
+                },
+            )
+            .build();
+        let resigning = RobotConfig {
+            depth: 1,
+            resign: Some(Resignation {
+                threshold: 0.05,
+                turns: 2,
+            }),
+            temperature: 0.0,
+        };
+        let mut players = Players {
+            allies_player: Box::new(Robot::with_config(Allies, resigning)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+
+        run_turn(&mut players, &mut engine);
+        assert_eq!(None, engine.state.winner);
+        run_turn(&mut players, &mut engine);
+
+        assert_eq!(Some(Empires), engine.state.winner);
+        assert_eq!(
+            "Empires win: Allies resigned",
+            engine.state.explain_winner()
+        );
+        assert!(engine.game_ends());
+    }
+}
+
+#[cfg(test)]
+mod turn_order {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        fixtures::EngineBuilder, minimax::Robot, run_turn, GameEngine, Input, Output, Phase,
+        Player, Players, Side, Side::*,
+    };
+
+    /// Records the side to play when each prompt but initiative bids is sent to the
+    /// wrapped player
+    struct SideRecorder {
+        inner: Robot,
+        sides: Rc<RefCell<Vec<Side>>>,
+    }
+
+    impl Player for SideRecorder {
+        fn output(&mut self, message: &Output, engine: &GameEngine) {
+            if message.is_prompt() && !matches!(engine.state.phase, Phase::Initiative(_)) {
+                let side = engine.state.side_to_play().unwrap();
+                self.sides.borrow_mut().push(side);
+            }
+            self.inner.output(message, engine);
+        }
+
+        fn input(&mut self) -> Input {
+            self.inner.input()
+        }
+
+        fn out(&self) -> Vec<Output> {
+            self.inner.out()
+        }
+    }
+
+    #[test]
+    fn initiative_side_plays_all_its_phases_before_the_other_side() {
+        let mut engine = EngineBuilder::new(12)
+            .with_resources(Allies, 6)
+            .with_resources(Empires, 2)
+            .on_turn(2)
+            .build();
+        let sides = Rc::new(RefCell::new(Vec::new()));
+        let mut players = Players {
+            allies_player: Box::new(SideRecorder {
+                inner: Robot::new(Allies, 1),
+                sides: sides.clone(),
+            }),
+            empires_player: Box::new(SideRecorder {
+                inner: Robot::new(Empires, 1),
+                sides: sides.clone(),
+            }),
+        };
+
+        run_turn(&mut players, &mut engine);
+
+        let initiative = engine.state.initiative;
+        let sides = sides.borrow();
+        let first_other = sides.iter().position(|side| *side != initiative).unwrap();
+        assert!(first_other > 0);
+        assert!(sides[first_other..].iter().all(|side| *side != initiative));
+    }
 }
 
 #[cfg(test)]
@@ -623,14 +1665,67 @@ mod events_tests {
         apply_event, draw_events,
         event::ALL_EVENTS,
         fixtures::{EngineBuilder, PlayersBuilder},
-        launch_offensives, HitsResult,
+        launch_offensives, Event, GameEngine, GameState, HitsResult, Input,
         Input::*,
         Nation::*,
-        NationState::*,
+        NationState::{self, *},
         OffensiveOutcome,
         Output::{self, *},
+        Player, Players,
         Side::*,
     };
+    use std::{cell::RefCell, rc::Rc};
+
+    /// Records outputs and batches boundaries in a log shared with the test
+    struct BatchRecorder {
+        log: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Player for BatchRecorder {
+        fn output(&mut self, message: &Output, _: &GameEngine) {
+            self.log.borrow_mut().push(format!("{:?}", message));
+        }
+
+        fn input(&mut self) -> Input {
+            Pass
+        }
+
+        fn out(&self) -> Vec<Output> {
+            vec![]
+        }
+
+        fn begin_batch(&mut self) {
+            self.log.borrow_mut().push("begin".to_string());
+        }
+
+        fn end_batch(&mut self) {
+            self.log.borrow_mut().push("end".to_string());
+        }
+    }
+
+    #[test]
+    fn drawn_events_outputs_are_batched() {
+        let mut engine = EngineBuilder::new(18).build();
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut players = Players {
+            allies_player: Box::new(BatchRecorder { log: log.clone() }),
+            empires_player: Box::new(BatchRecorder {
+                log: Rc::new(RefCell::new(Vec::new())),
+            }),
+        };
+
+        draw_events(&mut players, &mut engine);
+
+        let log = log.borrow();
+        assert_eq!(Some(&"begin".to_string()), log.first());
+        assert_eq!(Some(&"end".to_string()), log.last());
+        assert_eq!(
+            3,
+            log.iter()
+                .filter(|line| line.starts_with("EventDrawn"))
+                .count()
+        );
+    }
 
     #[test]
     fn draw_three_events_at_start_of_turn() {
@@ -655,6 +1750,70 @@ mod events_tests {
         );
     }
 
+    #[test]
+    fn forced_schlieffen_plan_is_drawn_and_played_whatever_the_seed() {
+        let mut engine = EngineBuilder::new(11).force_next_events(vec![3]).build();
+        let mut players = PlayersBuilder::new().build();
+
+        draw_events(&mut players, &mut engine);
+
+        let outputs = players.allies_player.out();
+        assert_eq!(EventDrawn(3, "Schlieffen plan".to_string()), outputs[0]);
+        assert!(matches!(
+            outputs[1],
+            OffensiveResult {
+                from: Germany,
+                to: France,
+                ..
+            }
+        ));
+        assert_eq!(2, outputs.len());
+        assert!(!engine.state.can_draw_event(&ALL_EVENTS[2]));
+    }
+
+    fn serbia_at_war(state: &GameState) -> bool {
+        state
+            .nations
+            .get(&Serbia)
+            .is_some_and(NationState::is_at_war)
+    }
+
+    const ITALY_JOINS_IF_SERBIA_FIGHTS: Event = Event {
+        event_id: 10,
+        year: 1915,
+        not_after: None,
+        title: "Italy enters the war!",
+        precondition: serbia_at_war,
+        weight: 1,
+    };
+
+    #[test]
+    fn event_is_ignored_given_its_precondition_is_unmet() {
+        let mut engine = EngineBuilder::new(11)
+            .with_nation(Serbia, AtPeace)
+            .with_nation(Italy, AtPeace)
+            .build();
+        let mut players = PlayersBuilder::new().build();
+
+        apply_event(&mut players, &mut engine, &ITALY_JOINS_IF_SERBIA_FIGHTS);
+
+        assert_eq!(AtPeace, engine.nation_state(Italy));
+        assert_eq!(vec![EventNotApplicable(10)], players.allies_player.out());
+    }
+
+    #[test]
+    fn event_is_applied_given_its_precondition_is_met() {
+        let mut engine = EngineBuilder::new(11)
+            .with_nation(Serbia, AtWar(3))
+            .with_nation(Italy, AtPeace)
+            .build();
+        let mut players = PlayersBuilder::new().build();
+
+        apply_event(&mut players, &mut engine, &ITALY_JOINS_IF_SERBIA_FIGHTS);
+
+        assert_eq!(AtWar(5), engine.nation_state(Italy));
+    }
+
     #[test]
     fn stop_drawing_events_if_no_events_are_available() {
         let mut engine = EngineBuilder::new(18).build();
@@ -709,7 +1868,7 @@ mod events_tests {
             },],
             players.allies_player.out()
         );
-        assert_eq!(AtWar(5), *engine.state.nations.get(&France).unwrap());
+        assert_eq!(AtWar(5), engine.nation_state(France));
     }
 
     #[test]
@@ -728,7 +1887,7 @@ mod events_tests {
         apply_event(&mut players, &mut engine, &ALL_EVENTS[3]);
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(7), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(7), engine.nation_state(Germany));
     }
 
     #[test]
@@ -748,7 +1907,7 @@ mod events_tests {
         engine.new_turn();
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(8), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(8), engine.nation_state(Germany));
     }
 
     #[test]
@@ -758,7 +1917,7 @@ mod events_tests {
 
         apply_event(&mut players, &mut engine, &ALL_EVENTS[9]);
 
-        assert_eq!(AtWar(5), *engine.state.nations.get(&Italy).unwrap());
+        assert_eq!(AtWar(5), engine.nation_state(Italy));
     }
 }
 
@@ -892,7 +2051,7 @@ mod technologies {
                 defense: 2,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(0, engine.state.resources_for(&Allies));
     }
@@ -923,7 +2082,7 @@ mod technologies {
                 defense: 1,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(4, engine.state.resources_for(&Allies));
     }
@@ -941,10 +2100,7 @@ mod technologies {
 
         improve_technologies(Allies, &mut players, &mut engine);
 
-        assert_eq!(
-            ZERO_TECHNOLOGIES,
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
-        );
+        assert_eq!(ZERO_TECHNOLOGIES, *engine.technologies_for(Allies));
         assert_eq!(4, engine.state.resources_for(&Allies));
     }
 
@@ -974,7 +2130,7 @@ mod technologies {
                 attack: 4,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(0, engine.state.resources_for(&Allies));
     }
@@ -1000,7 +2156,7 @@ mod technologies {
                 artillery: 1,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(0, engine.state.resources_for(&Allies));
     }
@@ -1031,7 +2187,7 @@ mod technologies {
                 defense: 3,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(
             vec![
@@ -1069,7 +2225,7 @@ mod technologies {
                 attack: 4,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(
             vec![
@@ -1101,7 +2257,7 @@ mod technologies {
                 defense: 1,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
     }
 
@@ -1124,7 +2280,7 @@ mod technologies {
                 air: 1,
                 ..ZERO_TECHNOLOGIES
             },
-            *engine.state.state_of_war.get(&Allies).unwrap().technologies
+            *engine.technologies_for(Allies)
         );
         assert_eq!(0, engine.state.resources_for(&Allies));
     }
@@ -1155,11 +2311,11 @@ mod offensives {
 
     use crate::{
         fixtures::{EngineBuilder, PlayersBuilder},
-        launch_offensives, HitsResult,
+        launch_offensives, GameConfig, HitsResult,
         Input::*,
         Nation::{self, *},
         NationState::*,
-        OffensiveOutcome, Output,
+        OffensiveOutcome, OffensiveRejection, Output, Phase,
         Side::*,
         Technologies, ZERO_TECHNOLOGIES,
     };
@@ -1180,7 +2336,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(7), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(7), engine.nation_state(Germany));
         assert_eq!(3, engine.state.resources_for(&Allies));
     }
 
@@ -1201,11 +2357,7 @@ mod offensives {
         assert_eq!(
             vec![
                 Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
-                Output::OffensiveResult {
-                    from: France,
-                    to: Germany,
-                    result: OffensiveOutcome::NotEnoughResources(3, 2)
-                },
+                Output::OffensiveRejected(OffensiveRejection::NotEnoughResources(3, 2)),
                 Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
             ],
             players.allies_player.out()
@@ -1214,83 +2366,222 @@ mod offensives {
     }
 
     #[test]
-    fn initiative_player_launch_several_offensives() {
-        let mut engine = EngineBuilder::new(16)
-            .with_resources(Allies, 4)
+    fn player_spends_available_pr_on_offensives_given_pr_are_clamped() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 2)
             .with_initiative(Allies)
             .on_turn(1)
             .build();
+        engine.set_clamp_offensive_pr(true);
         let mut players = PlayersBuilder::new()
-            .with_input(Allies, Offensive(France, Germany, 1))
-            .with_input(Allies, Offensive(Russia, OttomanEmpire, 1))
+            .with_input(Allies, Offensive(France, Germany, 3))
             .with_input(Allies, Pass)
             .build();
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(7), *engine.state.nations.get(&Germany).unwrap());
-        assert_eq!(AtWar(4), *engine.state.nations.get(&OttomanEmpire).unwrap());
-        assert_eq!(2, engine.state.resources_for(&Allies));
+        assert!(matches!(
+            players.allies_player.out()[1],
+            Output::OffensiveResult {
+                result: OffensiveOutcome::PartialSpend(3, 2, _),
+                ..
+            }
+        ));
+        assert_eq!(0, engine.state.resources_for(&Allies));
     }
 
     #[test]
-    fn prompt_list_possible_attacker() {
-        let mut engine = EngineBuilder::new(16)
-            .with_resources(Allies, 4)
+    fn player_offensive_is_rejected_given_pr_are_not_clamped() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 2)
             .with_initiative(Allies)
             .on_turn(1)
             .build();
+        engine.set_clamp_offensive_pr(false);
         let mut players = PlayersBuilder::new()
-            .with_input(Allies, Offensive(France, Germany, 1))
-            .with_input(Allies, Offensive(Russia, OttomanEmpire, 1))
+            .with_input(Allies, Offensive(France, Germany, 3))
             .with_input(Allies, Pass)
             .build();
 
         launch_offensives(Allies, &mut players, &mut engine);
 
         assert_eq!(
-            vec![
-                Output::LaunchOffensive(vec![France, Russia, Egypt, Serbia, FrenchAfrica]),
-                Output::OffensiveResult {
-                    from: France,
-                    to: Germany,
-                    result: OffensiveOutcome::Hits(HitsResult::Hits(Germany, 1))
-                },
-                Output::LaunchOffensive(vec![Russia, Egypt, Serbia, FrenchAfrica]),
-                Output::OffensiveResult {
-                    from: Russia,
-                    to: OttomanEmpire,
-                    result: OffensiveOutcome::Hits(HitsResult::Hits(OttomanEmpire, 1))
-                },
-                Output::LaunchOffensive(vec![Egypt, Serbia, FrenchAfrica])
-            ],
-            players.allies_player.out()
+            Output::OffensiveRejected(OffensiveRejection::NotEnoughResources(3, 2)),
+            players.allies_player.out()[1]
         );
+        assert!(!players
+            .allies_player
+            .out()
+            .iter()
+            .any(|output| matches!(output, Output::OffensiveResult { .. })));
+        assert_eq!(2, engine.state.resources_for(&Allies));
     }
 
     #[test]
-    fn cannot_launch_offensive_to_not_adjacent_country() {
+    fn initiative_player_launch_several_offensives() {
         let mut engine = EngineBuilder::new(16)
             .with_resources(Allies, 4)
             .with_initiative(Allies)
             .on_turn(1)
             .build();
         let mut players = PlayersBuilder::new()
-            .with_input(Allies, Offensive(France, AustriaHungary, 1))
+            .with_input(Allies, Offensive(France, Germany, 1))
+            .with_input(Allies, Offensive(Russia, OttomanEmpire, 1))
             .with_input(Allies, Pass)
             .build();
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(
-            vec![
-                Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
-                Output::AttackingNonAdjacentCountry(France, AustriaHungary),
-                Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
-            ],
+        assert_eq!(AtWar(7), engine.nation_state(Germany));
+        assert_eq!(AtWar(4), engine.nation_state(OttomanEmpire));
+        assert_eq!(2, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn exhausted_nations_cannot_launch_offensives() {
+        let mut engine = EngineBuilder::new(16)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(Serbia, AtWar(1))
+            .with_config(GameConfig {
+                exhaustion_threshold: 1,
+                ..GameConfig::default()
+            })
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new().with_input(Allies, Pass).build();
+
+        launch_offensives(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![Output::LaunchOffensive(vec![
+                France,
+                Russia,
+                Egypt,
+                FrenchAfrica
+            ])],
+            players.allies_player.out()
+        );
+    }
+
+    #[test]
+    fn prompt_list_possible_attacker() {
+        let mut engine = EngineBuilder::new(16)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Offensive(France, Germany, 1))
+            .with_input(Allies, Offensive(Russia, OttomanEmpire, 1))
+            .with_input(Allies, Pass)
+            .build();
+
+        launch_offensives(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![
+                Output::LaunchOffensive(vec![France, Russia, Egypt, Serbia, FrenchAfrica]),
+                Output::OffensiveResult {
+                    from: France,
+                    to: Germany,
+                    result: OffensiveOutcome::Hits(HitsResult::Hits(Germany, 1))
+                },
+                Output::LaunchOffensive(vec![Russia, Egypt, Serbia, FrenchAfrica]),
+                Output::OffensiveResult {
+                    from: Russia,
+                    to: OttomanEmpire,
+                    result: OffensiveOutcome::Hits(HitsResult::Hits(OttomanEmpire, 1))
+                },
+                Output::LaunchOffensive(vec![Egypt, Serbia, FrenchAfrica])
+            ],
+            players.allies_player.out()
+        );
+    }
+
+    #[test]
+    fn legal_offensives_are_the_ones_launch_offensives_accepts() {
+        let engine = EngineBuilder::new(14)
+            .with_resources(Empires, 3)
+            .with_initiative(Empires)
+            .on_turn(2)
+            .at_phase(Phase::LaunchOffensives(Empires))
+            .build();
+        let legal = engine.legal_inputs();
+
+        for from in Nation::values() {
+            for to in Nation::values() {
+                for pr in 0..=4 {
+                    let input = Offensive(*from, *to, pr);
+                    let mut engine = engine.clone();
+                    let mut players = PlayersBuilder::new()
+                        .with_input(Empires, input.clone())
+                        .with_input(Empires, Pass)
+                        .build();
+
+                    launch_offensives(Empires, &mut players, &mut engine);
+
+                    let accepted = players
+                        .empires_player
+                        .out()
+                        .iter()
+                        .any(|output| matches!(output, Output::OffensiveResult { .. }));
+                    assert_eq!(accepted, legal.contains(&input), "{:?}", input);
+                }
+            }
+        }
+        assert_eq!(Some(&Pass), legal.last());
+    }
+
+    #[test]
+    fn cannot_launch_offensive_to_not_adjacent_country() {
+        let mut engine = EngineBuilder::new(16)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Offensive(France, AustriaHungary, 1))
+            .with_input(Allies, Pass)
+            .build();
+
+        launch_offensives(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![
+                Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
+                Output::OffensiveRejected(OffensiveRejection::NotAdjacent(France, AustriaHungary)),
+                Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
+            ],
+            players.allies_player.out()
+        );
+        assert_eq!(4, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn cannot_launch_offensive_without_pr() {
+        let mut engine = EngineBuilder::new(16)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Offensive(France, Germany, 0))
+            .with_input(Allies, Pass)
+            .build();
+
+        launch_offensives(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![
+                Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
+                Output::OffensiveRejected(OffensiveRejection::NoPr(France, Germany)),
+                Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
+            ],
             players.allies_player.out()
         );
         assert_eq!(4, engine.state.resources_for(&Allies));
+        assert!(engine.state.offensive_sources(Allies).contains(&France));
     }
 
     #[test]
@@ -1308,7 +2599,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(7), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(7), engine.nation_state(Germany));
         assert_eq!(
             vec![
                 Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
@@ -1318,7 +2609,7 @@ mod offensives {
                     result: OffensiveOutcome::Hits(HitsResult::Hits(Germany, 1)),
                 },
                 Output::LaunchOffensive([France, Egypt, Serbia, FrenchAfrica].to_vec()),
-                Output::CountryAlreadyAttacked(Russia),
+                Output::OffensiveRejected(OffensiveRejection::AlreadyAttacked(Russia)),
                 Output::LaunchOffensive([France, Egypt, Serbia, FrenchAfrica].to_vec()),
             ],
             players.allies_player.out()
@@ -1343,11 +2634,7 @@ mod offensives {
         assert_eq!(
             vec![
                 Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
-                Output::OffensiveResult {
-                    from: Serbia,
-                    to: AustriaHungary,
-                    result: OffensiveOutcome::OperationalLevelTooLow(1, 2)
-                },
+                Output::OffensiveRejected(OffensiveRejection::OperationalLevelTooLow(1, 2)),
                 Output::LaunchOffensive(ALLIES_AT_START.to_vec()),
             ],
             players.allies_player.out()
@@ -1376,7 +2663,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(7), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(7), engine.nation_state(Germany));
     }
 
     #[test]
@@ -1407,7 +2694,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(8), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(8), engine.nation_state(Germany));
     }
 
     #[test]
@@ -1423,7 +2710,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(5), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(5), engine.nation_state(Germany));
     }
 
     #[test]
@@ -1446,7 +2733,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(5), *engine.state.nations.get(&Germany).unwrap());
+        assert_eq!(AtWar(5), engine.nation_state(Germany));
     }
 
     #[test]
@@ -1469,10 +2756,7 @@ mod offensives {
 
         launch_offensives(Allies, &mut players, &mut engine);
 
-        assert_eq!(
-            AtWar(5),
-            *engine.state.nations.get(&AustriaHungary).unwrap()
-        );
+        assert_eq!(AtWar(5), engine.nation_state(AustriaHungary));
     }
 
     #[test]
@@ -1498,10 +2782,7 @@ mod offensives {
         // max tech level of Serbia is 2
         // it should throw 4 dice but only 3 are taken into account
         // so no hit is inflicted
-        assert_eq!(
-            AtWar(5),
-            *engine.state.nations.get(&AustriaHungary).unwrap()
-        );
+        assert_eq!(AtWar(5), engine.nation_state(AustriaHungary));
     }
 
     #[test]
@@ -1535,7 +2816,7 @@ mod offensives {
         // attack factor of Russia is 5
         // result = 6 (die) + 1 (attack bonus) - 2 (defense bonus capped at max tech level) = 5
         // Russia inflicts 1 hit
-        assert_eq!(AtWar(4), *engine.state.nations.get(&OttomanEmpire).unwrap());
+        assert_eq!(AtWar(4), engine.nation_state(OttomanEmpire));
     }
 }
 
@@ -1544,11 +2825,15 @@ mod reinforcements {
 
     use crate::{
         fixtures::{EngineBuilder, PlayersBuilder},
-        reinforcements,
+        reinforcements, GameConfig, GameEngine, Input,
         Input::*,
         Nation::*,
         NationState::*,
+        Output,
+        Output::*,
+        Player, Players, ReinforceError,
         Side::*,
+        MAX_REJECTED_INPUTS,
     };
 
     #[test]
@@ -1566,10 +2851,106 @@ mod reinforcements {
 
         reinforcements(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(5), *engine.state.nations.get(&France).unwrap());
+        assert_eq!(AtWar(5), engine.nation_state(France));
         assert_eq!(3, engine.state.resources_for(&Allies));
     }
 
+    #[test]
+    fn player_is_told_why_reinforcing_enemy_nation_is_rejected() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Reinforce(Germany, 1))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert!(players
+            .allies_player
+            .out()
+            .contains(&InvalidReinforcement(ReinforceError::WrongSide(Germany))));
+        assert_eq!(4, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn exhausted_nation_can_still_be_reinforced() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(Serbia, AtWar(1))
+            .with_config(GameConfig {
+                exhaustion_threshold: 1,
+                ..GameConfig::default()
+            })
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Reinforce(Serbia, 1))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert_eq!(AtWar(2), engine.nation_state(Serbia));
+    }
+
+    #[test]
+    fn player_can_reinforce_several_nations_at_once_within_budget() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(France, AtWar(4))
+            .with_nation(Serbia, AtWar(1))
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, ReinforceMany(vec![(France, 3), (Serbia, 1)]))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert_eq!(AtWar(6), engine.nation_state(France));
+        assert_eq!(AtWar(2), engine.nation_state(Serbia));
+        assert_eq!(0, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn reinforcement_plan_exceeding_resources_is_rejected_as_a_whole() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(France, AtWar(4))
+            .with_nation(Serbia, AtWar(1))
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, ReinforceMany(vec![(France, 3), (Serbia, 3)]))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert_eq!(AtWar(4), engine.nation_state(France));
+        assert_eq!(AtWar(1), engine.nation_state(Serbia));
+        assert_eq!(4, engine.state.resources_for(&Allies));
+        assert_eq!(
+            vec![
+                ReinforceNations,
+                ReinforcementPlanRejected {
+                    cost: 6,
+                    resources: 4
+                },
+                ReinforceNations
+            ],
+            players.allies_player.out()
+        );
+    }
+
     #[test]
     fn reinforcements_cost_grows_quadratically() {
         let mut engine = EngineBuilder::new(14)
@@ -1587,8 +2968,8 @@ mod reinforcements {
 
         reinforcements(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(5), *engine.state.nations.get(&France).unwrap());
-        assert_eq!(AtWar(5), *engine.state.nations.get(&Russia).unwrap());
+        assert_eq!(AtWar(5), engine.nation_state(France));
+        assert_eq!(AtWar(5), engine.nation_state(Russia));
         assert_eq!(0, engine.state.resources_for(&Allies));
     }
 
@@ -1608,7 +2989,7 @@ mod reinforcements {
         reinforcements(Allies, &mut players, &mut engine);
 
         assert_eq!(1, engine.state.resources_for(&Allies));
-        assert_eq!(AtWar(6), *engine.state.nations.get(&France).unwrap());
+        assert_eq!(AtWar(6), engine.nation_state(France));
     }
 
     #[test]
@@ -1626,9 +3007,145 @@ mod reinforcements {
 
         reinforcements(Allies, &mut players, &mut engine);
 
-        assert_eq!(AtWar(7), *engine.state.nations.get(&France).unwrap());
+        assert_eq!(AtWar(7), engine.nation_state(France));
         assert_eq!(3, engine.state.resources_for(&Allies));
     }
+
+    #[test]
+    fn player_is_told_reinforcement_stopped_at_maximum_breakdown() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(France, AtWar(6))
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Reinforce(France, 3))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![
+                ReinforceNations,
+                InvalidReinforcement(ReinforceError::MaximumBreakdown(France)),
+                ReinforceNations
+            ],
+            players.allies_player.out()
+        );
+        assert_eq!(AtWar(7), engine.nation_state(France));
+    }
+
+    #[test]
+    fn player_is_told_nation_at_maximum_breakdown_cannot_be_reinforced() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(France, AtWar(7))
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Reinforce(France, 1))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![
+                ReinforceNations,
+                InvalidReinforcement(ReinforceError::MaximumBreakdown(France)),
+                ReinforceNations
+            ],
+            players.allies_player.out()
+        );
+        assert_eq!(4, engine.state.resources_for(&Allies));
+    }
+
+    #[test]
+    fn player_is_told_nation_at_peace_cannot_be_reinforced() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(Italy, AtPeace)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Reinforce(Italy, 1))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert!(players
+            .allies_player
+            .out()
+            .contains(&InvalidReinforcement(ReinforceError::NationNotAtWar(Italy))));
+    }
+
+    #[test]
+    fn message_player_given_input_is_inappropriate_for_reinforcements_phase() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Allies, Number(2))
+            .with_input(Allies, Pass)
+            .build();
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        assert_eq!(
+            vec![ReinforceNations, WrongInput(Number(2)), ReinforceNations],
+            players.allies_player.out()
+        );
+    }
+
+    /// A player which always answers with an input the reinforcements phase rejects
+    struct StubbornPlayer {
+        out: Vec<Output>,
+    }
+
+    impl Player for StubbornPlayer {
+        fn output(&mut self, message: &Output, _: &GameEngine) {
+            self.out.push(message.clone());
+        }
+
+        fn input(&mut self) -> Input {
+            Next
+        }
+
+        fn out(&self) -> Vec<Output> {
+            self.out.clone()
+        }
+    }
+
+    #[test]
+    fn player_is_forced_to_pass_after_too_many_rejected_inputs() {
+        let mut engine = EngineBuilder::new(14)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .with_nation(France, AtWar(4))
+            .on_turn(1)
+            .build();
+        let mut players = Players {
+            allies_player: Box::new(StubbornPlayer { out: vec![] }),
+            empires_player: Box::new(StubbornPlayer { out: vec![] }),
+        };
+
+        reinforcements(Allies, &mut players, &mut engine);
+
+        let out = players.allies_player.out();
+        assert_eq!(Some(&TooManyRejectedInputs(Allies)), out.last());
+        assert_eq!(
+            MAX_REJECTED_INPUTS as usize,
+            out.iter().filter(|o| **o == ReinforceNations).count()
+        );
+        assert_eq!(4, engine.state.resources_for(&Allies));
+    }
 }
 
 #[cfg(test)]
@@ -1640,7 +3157,9 @@ mod sea {
         Input::*,
         Nation::*,
         NationState::*,
+        Output::*,
         Side::*,
+        MAX_REJECTED_INPUTS,
     };
 
     #[test]
@@ -1687,6 +3206,158 @@ mod sea {
         assert_eq!(1, engine.state.resources_for(&Empires));
     }
 
+    #[test]
+    fn u_boot_result_reports_die_roll_and_pr_bonus() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 4)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new().with_input(Empires, Number(3)).build();
+
+        sea_control(Empires, &mut players, &mut engine);
+
+        assert!(players.allies_player.out().contains(&UBootResult {
+            roll: 2,
+            bonus: 3,
+            loss: 2,
+            hits: 0
+        }));
+    }
+
+    #[test]
+    fn u_boot_result_reports_overflow_hits_given_allies_lack_resources() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 1)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Empires, Pass)
+            .with_input(Allies, ApplyHit(France))
+            .with_input(Allies, ApplyHit(France))
+            .with_input(Allies, ApplyHit(France))
+            .build();
+
+        sea_control(Empires, &mut players, &mut engine);
+
+        assert!(players.allies_player.out().contains(&UBootResult {
+            roll: 6,
+            bonus: 0,
+            loss: 4,
+            hits: 3
+        }));
+    }
+
+    #[test]
+    fn allies_can_only_apply_hits_to_their_nations_at_war() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 3)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Empires, Pass)
+            .with_input(Allies, ApplyHit(Germany))
+            .with_input(Allies, ApplyHit(Italy))
+            .with_input(Allies, Pass)
+            .with_input(Allies, ApplyHit(France))
+            .build();
+
+        sea_control(Empires, &mut players, &mut engine);
+
+        let out = players.allies_player.out();
+        assert!(out.contains(&InvalidHit(Germany)), "{:?}", out);
+        assert!(out.contains(&InvalidHit(Italy)), "{:?}", out);
+        assert!(out.contains(&WrongInput(Pass)), "{:?}", out);
+        assert_eq!(AtWar(6), engine.nation_state(France));
+        assert_eq!(AtWar(8), engine.nation_state(Germany));
+        assert_eq!(AtPeace, engine.nation_state(Italy));
+    }
+
+    #[test]
+    fn us_cannot_be_picked_for_a_hit() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 3)
+            .on_turn(1)
+            .build();
+        engine.us_enters_war();
+        let mut players = PlayersBuilder::new()
+            .with_input(Empires, Pass)
+            .with_input(Allies, ApplyHit(UnitedStates))
+            .with_input(Allies, ApplyHit(France))
+            .build();
+
+        sea_control(Empires, &mut players, &mut engine);
+
+        assert!(players
+            .allies_player
+            .out()
+            .contains(&InvalidHit(UnitedStates)));
+        assert_eq!(AtWar(0), engine.nation_state(UnitedStates));
+        assert_eq!(AtWar(6), engine.nation_state(France));
+    }
+
+    #[test]
+    fn allies_resigning_while_applying_hits_ends_the_game() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 1)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new()
+            .with_input(Empires, Pass)
+            .with_input(Allies, Resign)
+            .build();
+
+        sea_control(Empires, &mut players, &mut engine);
+
+        assert_eq!(Some(Empires), engine.state.winner);
+    }
+
+    #[test]
+    fn hits_are_applied_anyway_given_too_many_rejected_inputs() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 3)
+            .on_turn(1)
+            .build();
+        let mut builder = PlayersBuilder::new();
+        builder.with_input(Empires, Pass);
+        for _ in 0..MAX_REJECTED_INPUTS {
+            builder.with_input(Allies, Next);
+        }
+        let mut players = builder.build();
+
+        sea_control(Empires, &mut players, &mut engine);
+
+        assert!(players
+            .allies_player
+            .out()
+            .contains(&TooManyRejectedInputs(Allies)));
+        assert_eq!(AtWar(6), engine.nation_state(France));
+    }
+
+    #[test]
+    fn blockade_result_reports_die_roll_and_pr_bonus() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_resources(Empires, 4)
+            .with_resources(Allies, 4)
+            .with_initiative(Allies)
+            .on_turn(1)
+            .build();
+        let mut players = PlayersBuilder::new().with_input(Allies, Number(1)).build();
+
+        sea_control(Allies, &mut players, &mut engine);
+
+        assert!(players.empires_player.out().contains(&BlockadeResult {
+            roll: 2,
+            bonus: 1,
+            gain: 0
+        }));
+    }
+
     #[test]
     fn modified_u_boot_die_roll_greater_than_6_is_6() {
         let mut engine = EngineBuilder::new(14)
@@ -1767,7 +3438,112 @@ mod sea {
         sea_control(Empires, &mut players, &mut engine);
 
         assert_eq!(0, engine.state.resources_for(&Allies));
-        assert_eq!(AtWar(6), *engine.state.nations.get(&France).unwrap());
-        assert_eq!(AtWar(5), *engine.state.nations.get(&Russia).unwrap());
+        assert_eq!(AtWar(6), engine.nation_state(France));
+        assert_eq!(AtWar(5), engine.nation_state(Russia));
+    }
+}
+
+#[cfg(test)]
+mod replay {
+    use crate::{
+        initialise_players, minimax::RobotConfig, new_game, robot_seed, run_turn, sim, GameConfig,
+        GameEngine, GameSummary, HandoffRequests, Options, PlayerType, Side::*,
+    };
+
+    #[test]
+    fn robots_do_not_share_their_seed_with_the_dice_nor_with_other_robots() {
+        for seed in [0, 1, 42, 1914] {
+            let allies = robot_seed(seed, Allies);
+            let empires = robot_seed(seed, Empires);
+
+            assert_ne!(seed, allies);
+            assert_ne!(seed, empires);
+            assert_ne!(allies, empires);
+            assert_ne!(allies, robot_seed(seed ^ 1, Empires));
+            assert_ne!(empires, robot_seed(seed ^ 1, Allies));
+        }
+    }
+
+    /// Play a game between random robots set up from the command line's seed
+    fn play_random_robots(seed: u64) -> GameEngine {
+        let options = Options {
+            allies: PlayerType::Robot,
+            empires: PlayerType::Robot,
+            seed,
+            ..Options::default()
+        };
+        let mut engine = new_game(&options);
+        let mut players = initialise_players(&options, &HandoffRequests::default());
+        while !engine.game_ends() {
+            run_turn(&mut players, &mut engine);
+        }
+        engine
+    }
+
+    #[test]
+    fn seed_determines_games_between_random_robots() {
+        assert_eq!(
+            play_random_robots(7).state.fingerprint(),
+            play_random_robots(7).state.fingerprint()
+        );
+        assert_ne!(
+            play_random_robots(7).state.fingerprint(),
+            play_random_robots(8).state.fingerprint()
+        );
+    }
+
+    fn play_game(seed: u64, depth: u8) -> GameEngine {
+        let config = RobotConfig {
+            depth,
+            resign: None,
+            temperature: 0.0,
+        };
+        sim::play_game(seed, config, config)
+    }
+
+    // Golden values for robot games: any change to the rules or to the robot's
+    // behaviour changes them, in which case they must be updated deliberately
+    // after checking the new behaviour is the expected one.
+
+    #[test]
+    fn robot_game_replays_identically_from_seed_42() {
+        let engine = play_game(42, 2);
+
+        assert_eq!(
+            GameSummary {
+                winner: Empires,
+                turns: 13,
+                allies_vp: 0,
+                empires_vp: 5,
+                allies_resources: 9,
+                empires_resources: 15,
+                allies_collected: vec![],
+                empires_collected: vec![],
+                config: GameConfig::default(),
+            },
+            engine.state.summary()
+        );
+        assert_eq!(745022782533254358, engine.state.fingerprint());
+    }
+
+    #[test]
+    fn robot_game_replays_identically_from_seed_1914() {
+        let engine = play_game(1914, 2);
+
+        assert_eq!(
+            GameSummary {
+                winner: Empires,
+                turns: 12,
+                allies_vp: 0,
+                empires_vp: 3,
+                allies_resources: 0,
+                empires_resources: 6,
+                allies_collected: vec![],
+                empires_collected: vec![],
+                config: GameConfig::default(),
+            },
+            engine.state.summary()
+        );
+        assert_eq!(420876077378038241, engine.state.fingerprint());
     }
 }
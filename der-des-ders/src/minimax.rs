@@ -1,22 +1,203 @@
-use std::fmt::{self, Display, Formatter};
+use std::cell::Cell;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::rc::Rc;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::{
-    robot::possible_hits, Event, GameEngine, Input, Nation, Offensive, Output, Phase, Player, Side,
-    TechnologyType,
+    robot::possible_hits, robot_seed, Event, GameEngine, GameState, Input, Nation, Offensive,
+    Output, Phase, Player, Side, TechnologyType,
 };
 
+/// Heuristic value of a state for the search, between -1 and +1 where positive values
+/// are better for the Allies
+pub trait Evaluator: Debug {
+    fn evaluate(&self, state: &GameState) -> f64;
+}
+
+/// The game's own valuation, see `GameState::valuation`
+#[derive(Debug)]
+pub struct StateValuation;
+
+impl Evaluator for StateValuation {
+    fn evaluate(&self, state: &GameState) -> f64 {
+        state.valuation()
+    }
+}
+
+/// `GameState::valuation` with victory points weighing the given times more, for
+/// robots aiming at a sudden death win rather than at wearing the enemy down
+#[cfg(test)]
+#[derive(Debug)]
+pub struct VictoryPointsFirst {
+    pub weight: f64,
+}
+
+#[cfg(test)]
+impl Evaluator for VictoryPointsFirst {
+    fn evaluate(&self, state: &GameState) -> f64 {
+        let components = state.valuation_components();
+        let total = |terms: &crate::ValuationTerms| {
+            terms.total() + terms.victory_points * (self.weight - 1.0)
+        };
+        let (allies, empires) = (total(&components.allies), total(&components.empires));
+        if allies + empires == 0.0 {
+            0.0
+        } else {
+            (allies - empires) / (allies + empires)
+        }
+    }
+}
+
 pub struct Robot {
     side: Side,
     depth: u8,
     next_move: Move,
+    resignation: Option<Resignation>,
+    /// Number of consecutive turns the game was hopeless for this robot
+    hopeless_turns: u8,
+    last_evaluated_turn: u8,
+    /// Whether the robot will resign on its next input
+    resigning: bool,
+    /// Softmax temperature used to pick a move, 0 always picks the best one
+    temperature: f64,
+    /// Dice for picking moves, seeded on first use from the game's seed and the robot's
+    /// side, see `robot_seed`
+    rng: Option<StdRng>,
+    evaluator: Rc<dyn Evaluator>,
+    /// Number of states searched to choose the last move
+    nodes_evaluated: Rc<Cell<u64>>,
+}
+
+/// Tunable parameters of a search robot
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct RobotConfig {
+    /// Depth of the minimax search
+    pub depth: u8,
+    /// Resign hopeless games, see `Resignation`
+    pub resign: Option<Resignation>,
+    /// Instead of always playing the best move, sample moves with a probability
+    /// given by the softmax of their valuation at this temperature. 0 is deterministic.
+    pub temperature: f64,
+}
+
+impl RobotConfig {
+    /// Check the parameters can be played with: the temperature cannot be negative
+    pub fn validate(&self) -> Result<(), String> {
+        if self.temperature >= 0.0 {
+            Ok(())
+        } else {
+            Err(format!(
+                "robot temperature must be 0 or more, got {}",
+                self.temperature
+            ))
+        }
+    }
+}
+
+/// A robot resigns when the game's valuation is worse than `-threshold` from its
+/// point of view at the start of `turns` consecutive turns
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct Resignation {
+    pub threshold: f64,
+    pub turns: u8,
 }
 
 impl Robot {
     pub fn new(side: Side, depth: u8) -> Self {
+        Robot::with_evaluator(side, depth, Box::new(StateValuation))
+    }
+
+    /// A robot searching moves with the given heuristic instead of the game's valuation
+    pub fn with_evaluator(side: Side, depth: u8, evaluator: Box<dyn Evaluator>) -> Self {
         Robot {
             side,
             depth,
             next_move: Move::Pass,
+            resignation: None,
+            hopeless_turns: 0,
+            last_evaluated_turn: 0,
+            resigning: false,
+            temperature: 0.0,
+            rng: None,
+            evaluator: Rc::from(evaluator),
+            nodes_evaluated: Rc::new(Cell::new(0)),
+        }
+    }
+
+    pub fn with_config(side: Side, config: RobotConfig) -> Self {
+        Robot {
+            resignation: config.resign,
+            temperature: config.temperature,
+            ..Robot::new(side, config.depth)
+        }
+    }
+
+    /// Number of states the search went through to choose the last move, which alpha-beta
+    /// pruning keeps well below the number of states reachable at the robot's depth
+    #[cfg(test)]
+    pub fn nodes_evaluated(&self) -> u64 {
+        self.nodes_evaluated.get()
+    }
+
+    /// Search moves and pick the next one according to the robot's temperature
+    fn choose_move(
+        &mut self,
+        engine: &GameEngine,
+        nations_played: Vec<Nation>,
+        available_tech: Vec<TechnologyType>,
+    ) {
+        self.nodes_evaluated.set(0);
+        let chosen = if self.temperature > 0.0 {
+            let scored = scored_moves(
+                self.side,
+                engine,
+                self.depth,
+                nations_played,
+                available_tech,
+                self.evaluator.clone(),
+                self.nodes_evaluated.clone(),
+            );
+            let rng = self.rng.get_or_insert_with(|| {
+                StdRng::seed_from_u64(robot_seed(engine.state.seed(), self.side))
+            });
+            sample_move(self.side, scored, self.temperature, rng)
+        } else {
+            pruned_best_move(
+                self.side,
+                engine,
+                self.depth,
+                nations_played,
+                available_tech,
+                self.evaluator.clone(),
+                self.nodes_evaluated.clone(),
+            )
+        };
+        if let Some(m) = chosen {
+            self.next_move = m;
+        }
+    }
+
+    fn evaluate_hopelessness(&mut self, engine: &GameEngine) {
+        let Some(resignation) = self.resignation else {
+            return;
+        };
+        if engine.state.current_turn == self.last_evaluated_turn {
+            return;
+        }
+        self.last_evaluated_turn = engine.state.current_turn;
+        let valuation = self.evaluator.evaluate(&engine.state);
+        let valuation = match self.side {
+            Side::Allies => valuation,
+            Side::Empires => -valuation,
+        };
+        if valuation < -resignation.threshold {
+            self.hopeless_turns += 1;
+        } else {
+            self.hopeless_turns = 0;
+        }
+        if self.hopeless_turns >= resignation.turns {
+            self.resigning = true;
         }
     }
 }
@@ -24,44 +205,33 @@ impl Robot {
 impl Player for Robot {
     fn output(&mut self, message: &Output, engine: &GameEngine) {
         match message {
-            Output::ChooseInitiative => {
-                let best_move = best_move(self.side, engine, self.depth, vec![], vec![]);
-                if let Some(m) = best_move {
-                    self.next_move = m;
-                }
-            }
+            Output::CurrentState(_) => self.evaluate_hopelessness(engine),
+            Output::ChooseInitiative => self.choose_move(engine, vec![], vec![]),
             Output::ImproveTechnologies(available) => {
-                let best_move =
-                    best_move(self.side, engine, self.depth, vec![], available.to_vec());
-                if let Some(m) = best_move {
-                    self.next_move = m;
-                }
+                self.choose_move(engine, vec![], available.to_vec())
             }
             Output::LaunchOffensive(available) => {
-                let mut played = engine.all_nations_at_war(self.side);
+                let mut played = engine.state.offensive_sources(self.side);
                 played.retain(|n| !available.contains(n));
-                let best_move = best_move(self.side, engine, self.depth, played, vec![]);
-                if let Some(m) = best_move {
-                    self.next_move = m;
-                }
-            }
-            Output::ReinforceNations => {
-                let best_move = best_move(self.side, engine, self.depth, vec![], vec![]);
-                if let Some(m) = best_move {
-                    self.next_move = m;
-                }
+                self.choose_move(engine, played, vec![])
             }
+            Output::ReinforceNations => self.choose_move(engine, vec![], vec![]),
             Output::SelectNationForHit => {
                 let nations = possible_hits(&self.side, &engine.state);
                 self.next_move = Move::Hit(nations[0].0);
             }
             other => {
-                println!("Robot received message: {:?}", other);
+                eprintln!("Robot received message: {:?}", other);
             }
         }
     }
 
     fn input(&mut self) -> Input {
+        if self.resigning {
+            self.resigning = false;
+            self.resignation = None;
+            return Input::Resign;
+        }
         match self.next_move {
             Move::BetForInitiative(_, pr) => Input::Number(pr as u8),
             Move::EventsDrawn(_) => panic!("Cannot input events"),
@@ -131,6 +301,12 @@ struct Search {
 
     /// TODO: This should be part of the state
     available_tech: Vec<TechnologyType>,
+
+    /// How to value the states at the leaves of the search
+    evaluator: Rc<dyn Evaluator>,
+
+    /// Number of states searched so far, shared by all the states of a search
+    nodes: Rc<Cell<u64>>,
 }
 
 struct SearchIterator<'a> {
@@ -144,7 +320,16 @@ impl Search {
     }
 
     fn valuation(&self) -> f64 {
-        self.engine.valuation()
+        self.evaluator.evaluate(&self.engine.state)
+    }
+
+    /// Whether the side to play maximizes the valuation, i.e. is the Allies
+    fn maximizing(&self) -> bool {
+        match self.engine.state.side_to_play() {
+            Some(Side::Allies) => true,
+            Some(Side::Empires) => false,
+            None => self.me == Side::Allies,
+        }
     }
 
     /// Returns an iterator over "all" the possible moves from the current engine
@@ -189,72 +374,66 @@ impl Search {
                 }
             }
             Phase::ImproveTechnologies(side) => {
-                let mut moves = vec![];
-                let resources = self.engine.state.resources_for(&side);
-                let mut available_techs = self.engine.state.available_technologies(&side);
-                available_techs.retain(|t| self.available_tech.contains(&t.category));
-
-                if resources > 0 && !available_techs.is_empty() {
-                    let tech = available_techs.pop().unwrap();
-                    let max_pr = resources.min(5);
-                    for pr in 1..=max_pr {
-                        moves.push(Move::ImproveTechnology(side, tech, pr));
-                    }
-                }
-                moves.push(Move::Pass);
+                // only search the last technology still available, with at most 5 PR
+                let tech = self
+                    .engine
+                    .state
+                    .available_technologies(&side)
+                    .into_iter()
+                    .rev()
+                    .find(|t| self.available_tech.contains(&t.category));
+                let moves = self
+                    .engine
+                    .legal_inputs()
+                    .into_iter()
+                    .filter_map(|input| match input {
+                        Input::Select(category, pr) => tech
+                            .filter(|t| t.category == category && pr <= 5)
+                            .map(|t| Move::ImproveTechnology(side, t, pr)),
+                        Input::Pass => Some(Move::Pass),
+                        _ => None,
+                    })
+                    .collect();
                 SearchIterator {
                     search: self,
                     moves,
                 }
             }
-            Phase::LaunchOffensives(side) => {
-                let mut moves = vec![];
-                let resources = self.engine.state.resources_for(&side);
-                let mut sources: Vec<Nation> = self.engine.all_nations_at_war(side);
-                sources.retain(|n| !self.nations_played.contains(n));
-
-                for source in sources.iter() {
-                    let mut targets: Vec<&Nation> = self.engine.state.neighbours(source);
-                    targets.sort();
-                    for target in targets.iter() {
-                        for pr in 1..=self.engine.state.operational_level(source) {
-                            if pr <= resources {
-                                moves.push(Move::Offensive(*source, **target, pr))
-                            };
+            Phase::LaunchOffensives(_) => {
+                let moves = self
+                    .engine
+                    .legal_inputs()
+                    .into_iter()
+                    .filter_map(|input| match input {
+                        // attacking a nation at peace is legal but only wastes PR
+                        Input::Offensive(from, to, pr)
+                            if !self.nations_played.contains(&from)
+                                && self.engine.state.is_at_war(&to) =>
+                        {
+                            Some(Move::Offensive(from, to, pr))
                         }
-                    }
-                }
-                moves.push(Move::Pass);
+                        Input::Pass => Some(Move::Pass),
+                        _ => None,
+                    })
+                    .collect();
                 SearchIterator {
                     search: self,
                     moves,
                 }
             }
-            Phase::Reinforcements(side) => {
-                let mut moves = vec![];
-                let resources = self.engine.state.resources_for(&side);
-                let nations = self
+            Phase::Reinforcements(_) => {
+                let moves = self
                     .engine
-                    .all_nations_at_war(side)
-                    .iter()
-                    .filter_map(|n| {
-                        let losses = n.maximum_breakdown() - self.engine.state.breakdown_level(n);
-                        if losses > 0 && !self.nations_played.contains(n) {
-                            Some((*n, losses))
-                        } else {
-                            None
+                    .legal_inputs()
+                    .into_iter()
+                    .filter_map(|input| match input {
+                        Input::Reinforce(nation, pr) if !self.nations_played.contains(&nation) => {
+                            Some(Move::Reinforce(nation, pr))
                         }
+                        Input::Pass => Some(Move::Pass),
+                        _ => None,
                     })
-                    .collect::<Vec<(Nation, u8)>>();
-
-                for (nation, losses) in nations.iter() {
-                    for pr in 1..=*losses {
-                        if pr <= resources {
-                            moves.push(Move::Reinforce(*nation, pr));
-                        }
-                    }
-                }
-                moves.push(Move::Pass);
+                    .collect();
                 SearchIterator {
                     search: self,
                     moves,
@@ -318,7 +497,8 @@ impl Search {
             }
             Move::Reinforce(nation, pr) => {
                 self.nations_played.push(*nation);
-                self.engine.reinforce(*nation, *pr);
+                let side = self.engine.state.side_of(nation);
+                let _ = self.engine.reinforce(side, *nation, *pr);
                 self.engine
                     .set_phase(Phase::Reinforcements(self.engine.state.initiative));
             }
@@ -396,6 +576,7 @@ fn alphabeta(
     mut alpha: f64,
     mut beta: f64,
 ) -> f64 {
+    search.nodes.set(search.nodes.get() + 1);
     if depth <= 0 || search.game_ends() {
         return search.valuation();
     }
@@ -403,12 +584,7 @@ fn alphabeta(
     if maximizing {
         let mut value: f64 = -1.0;
         for mut child in search.iter() {
-            let max = match child.engine.state.side_to_play() {
-                Some(Side::Allies) => true,
-                Some(Side::Empires) => false,
-                None => child.me == Side::Allies,
-            };
-
+            let max = child.maximizing();
             value = value.max(alphabeta(&mut child, max, depth - 1, alpha, beta));
             alpha = alpha.max(value);
             if value >= beta {
@@ -419,11 +595,7 @@ fn alphabeta(
     } else {
         let mut value: f64 = 1.0;
         for mut child in search.iter() {
-            let max = match child.engine.state.side_to_play() {
-                Some(Side::Allies) => true,
-                Some(Side::Empires) => false,
-                None => child.me == Side::Allies,
-            };
+            let max = child.maximizing();
             value = value.min(alphabeta(&mut child, max, depth - 1, alpha, beta));
             beta = beta.min(value);
             if value <= alpha {
@@ -434,6 +606,8 @@ fn alphabeta(
     }
 }
 
+/// The best move for `me` according to the game's own valuation, if it's `me` to play
+#[cfg(test)]
 pub fn best_move(
     me: Side,
     engine: &GameEngine,
@@ -441,46 +615,135 @@ pub fn best_move(
     nations_played: Vec<Nation>,
     available_tech: Vec<TechnologyType>,
 ) -> Option<Move> {
-    let side = engine.state.side_to_play();
-    let mut search = Search {
+    pruned_best_move(
+        me,
+        engine,
+        depth,
+        nations_played,
+        available_tech,
+        Rc::new(StateValuation),
+        Rc::new(Cell::new(0)),
+    )
+}
+
+/// The move with the best valuation for `me`, if any is better than losing and it's `me`
+/// to play.
+/// Each move is searched within the bounds set by the best move found so far, cutting
+/// the branches which cannot beat it. The valuation of the moves cut is thus not exact,
+/// use `scored_moves` to compare all moves.
+fn pruned_best_move(
+    me: Side,
+    engine: &GameEngine,
+    depth: u8,
+    nations_played: Vec<Nation>,
+    available_tech: Vec<TechnologyType>,
+    evaluator: Rc<dyn Evaluator>,
+    nodes: Rc<Cell<u64>>,
+) -> Option<Move> {
+    let mut search = root_search(me, engine, nations_played, available_tech, evaluator, nodes)?;
+    eprintln!("({:?}) searching for side: {:?} ", engine.state.phase, me);
+    let (mut alpha, mut beta) = (-1.0, 1.0);
+    let mut best_move = None;
+    for mut child in search.iter() {
+        let max = child.maximizing();
+        let value = alphabeta(&mut child, max, depth, alpha, beta);
+        eprintln!(" {:?} = {}", child.moved, value);
+        if me == Side::Allies && value > alpha {
+            alpha = value;
+            best_move = child.moved;
+        } else if me == Side::Empires && value < beta {
+            beta = value;
+            best_move = child.moved;
+        }
+    }
+    eprintln!(" => best move: {:?}", best_move);
+    best_move
+}
+
+/// The search from the given engine, if it's `me` to play
+fn root_search(
+    me: Side,
+    engine: &GameEngine,
+    nations_played: Vec<Nation>,
+    available_tech: Vec<TechnologyType>,
+    evaluator: Rc<dyn Evaluator>,
+    nodes: Rc<Cell<u64>>,
+) -> Option<Search> {
+    if engine.state.side_to_play() != Some(me) {
+        return None;
+    }
+    Some(Search {
         me,
         engine: engine.clone(),
         allies_initiative: 0,
         nations_played,
         moved: None,
         available_tech,
+        evaluator,
+        nodes,
+    })
+}
+
+/// Pick a move at random, each move's weight being the softmax of its valuation for `me`
+/// at the given temperature so that near-best moves are the most likely
+fn sample_move(
+    me: Side,
+    scored: Vec<(Move, f64)>,
+    temperature: f64,
+    rng: &mut StdRng,
+) -> Option<Move> {
+    let values: Vec<f64> = scored
+        .iter()
+        .map(|(_, value)| if me == Side::Allies { *value } else { -value })
+        .collect();
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let weights: Vec<f64> = values
+        .iter()
+        .map(|value| ((value - max) / temperature).exp())
+        .collect();
+    let mut pick = rng.gen::<f64>() * weights.iter().sum::<f64>();
+    let mut chosen = weights.len().checked_sub(1)?;
+    for (index, weight) in weights.iter().enumerate() {
+        if pick < *weight {
+            chosen = index;
+            break;
+        }
+        pick -= weight;
+    }
+    scored.into_iter().nth(chosen).map(|(m, _)| m)
+}
+
+/// Search all moves available to `me` and their valuation, if it's `me` to play
+fn scored_moves(
+    me: Side,
+    engine: &GameEngine,
+    depth: u8,
+    nations_played: Vec<Nation>,
+    available_tech: Vec<TechnologyType>,
+    evaluator: Rc<dyn Evaluator>,
+    nodes: Rc<Cell<u64>>,
+) -> Vec<(Move, f64)> {
+    let Some(mut search) =
+        root_search(me, engine, nations_played, available_tech, evaluator, nodes)
+    else {
+        return vec![];
     };
-    match side {
-        Some(s) if s == me => {
-            println!("({:?}) searching for side: {:?} ", engine.state.phase, me);
-            let mut best_value = if s == Side::Allies { -1.0 } else { 1.0 };
-            let mut best_move = None;
-            for mut child in search.iter() {
-                let max = match child.engine.state.side_to_play() {
-                    Some(Side::Allies) => true,
-                    Some(Side::Empires) => false,
-                    None => child.me == Side::Allies,
-                };
-                let value = alphabeta(&mut child, max, depth, -1.0, 1.0);
-                println!(" {:?} = {}", child.moved, value);
-                if s == Side::Allies && value > best_value {
-                    best_value = value;
-                    best_move = child.moved;
-                } else if s == Side::Empires && value < best_value {
-                    best_value = value;
-                    best_move = child.moved;
-                }
-            }
-            println!(" => best move: {:?}", best_move);
-            best_move
+    eprintln!("({:?}) searching for side: {:?} ", engine.state.phase, me);
+    let mut scored = vec![];
+    for mut child in search.iter() {
+        let max = child.maximizing();
+        let value = alphabeta(&mut child, max, depth, -1.0, 1.0);
+        eprintln!(" {:?} = {}", child.moved, value);
+        if let Some(m) = child.moved {
+            scored.push((m, value));
         }
-        _ => None,
     }
+    scored
 }
 
 #[cfg(test)]
 mod minimax_test {
-    use crate::fixtures::EngineBuilder;
+    use crate::{fixtures::EngineBuilder, NationState};
 
     use super::*;
 
@@ -494,6 +757,8 @@ mod minimax_test {
             nations_played: vec![],
             available_tech: vec![],
             moved: None,
+            evaluator: Rc::new(StateValuation),
+            nodes: Rc::new(Cell::new(0)),
         };
         let value = alphabeta(&mut search, true, 0, -1.0, 1.0);
         assert!((value - engine.valuation()).abs() < f64::EPSILON);
@@ -509,6 +774,8 @@ mod minimax_test {
             nations_played: vec![],
             available_tech: vec![],
             moved: None,
+            evaluator: Rc::new(StateValuation),
+            nodes: Rc::new(Cell::new(0)),
         };
         let value = alphabeta(&mut search, true, 10, -1.0, 1.0);
         assert!(value != engine.valuation());
@@ -572,4 +839,146 @@ mod minimax_test {
 
         assert_eq!(Input::Number(2), input);
     }
+
+    fn initiative_choice(temperature: f64) -> Input {
+        let engine = EngineBuilder::new(10)
+            .on_turn(2)
+            .at_phase(Phase::Initiative(Side::Empires))
+            .with_resources(Side::Empires, 5)
+            .build();
+        let mut robot = Robot::with_config(
+            Side::Empires,
+            RobotConfig {
+                depth: 6,
+                resign: None,
+                temperature,
+            },
+        );
+
+        robot.output(&Output::ChooseInitiative, &engine);
+
+        robot.input()
+    }
+
+    #[test]
+    fn robot_with_zero_temperature_plays_best_move() {
+        assert_eq!(Input::Number(2), initiative_choice(0.0));
+    }
+
+    #[test]
+    fn robot_config_rejects_negative_temperature() {
+        let config = |temperature| RobotConfig {
+            depth: 2,
+            resign: None,
+            temperature,
+        };
+
+        assert_eq!(Ok(()), config(0.0).validate());
+        assert!(config(-1.0).validate().is_err());
+        assert!(config(f64::NAN).validate().is_err());
+    }
+
+    #[test]
+    fn robot_with_high_temperature_can_play_suboptimal_move() {
+        assert_eq!(Input::Number(1), initiative_choice(10.0));
+        assert_eq!(initiative_choice(10.0), initiative_choice(10.0));
+    }
+
+    fn offensive_choice(evaluator: Box<dyn Evaluator>) -> Input {
+        let engine = EngineBuilder::new(14)
+            .on_turn(2)
+            .at_phase(Phase::LaunchOffensives(Side::Empires))
+            .with_nation(Nation::Serbia, NationState::AtWar(1))
+            .with_nation(Nation::Germany, NationState::AtWar(3))
+            .with_resources(Side::Empires, 4)
+            .build();
+        let mut robot = Robot::with_evaluator(Side::Empires, 2, evaluator);
+
+        robot.output(
+            &Output::LaunchOffensive(engine.state.offensive_sources(Side::Empires)),
+            &engine,
+        );
+
+        robot.input()
+    }
+
+    #[test]
+    fn robot_plays_differently_given_another_evaluator() {
+        assert_eq!(
+            Input::Offensive(Nation::AustriaHungary, Nation::Serbia, 1),
+            offensive_choice(Box::new(StateValuation))
+        );
+        assert_eq!(
+            Input::Offensive(Nation::OttomanEmpire, Nation::Egypt, 1),
+            offensive_choice(Box::new(VictoryPointsFirst { weight: 10.0 }))
+        );
+    }
+
+    /// The move with the best valuation for `me`, if any is better than losing
+    fn select_best_move(me: Side, scored: Vec<(Move, f64)>) -> Option<Move> {
+        let mut best_value = if me == Side::Allies { -1.0 } else { 1.0 };
+        let mut best_move = None;
+        for (m, value) in scored {
+            if (me == Side::Allies && value > best_value)
+                || (me == Side::Empires && value < best_value)
+            {
+                best_value = value;
+                best_move = Some(m);
+            }
+        }
+        eprintln!(" => best move: {:?}", best_move);
+        best_move
+    }
+
+    /// Full-width search without any pruning, as a reference for `alphabeta`
+    fn minimax(search: &mut Search, maximizing: bool, depth: u8) -> f64 {
+        search.nodes.set(search.nodes.get() + 1);
+        if depth == 0 || search.game_ends() {
+            return search.valuation();
+        }
+        let values = search.iter().map(|mut child| {
+            let max = child.maximizing();
+            minimax(&mut child, max, depth - 1)
+        });
+        if maximizing {
+            values.fold(-1.0, f64::max)
+        } else {
+            values.fold(1.0, f64::min)
+        }
+    }
+
+    #[test]
+    fn pruning_chooses_the_same_move_as_full_width_search_visiting_fewer_states() {
+        let engine = EngineBuilder::new(14)
+            .on_turn(2)
+            .at_phase(Phase::Initiative(Side::Allies))
+            .with_resources(Side::Allies, 5)
+            .with_resources(Side::Empires, 5)
+            .build();
+        let mut robot = Robot::new(Side::Allies, 4);
+        robot.output(&Output::ChooseInitiative, &engine);
+
+        let mut search = root_search(
+            Side::Allies,
+            &engine,
+            vec![],
+            vec![],
+            Rc::new(StateValuation),
+            Rc::new(Cell::new(0)),
+        )
+        .unwrap();
+        let scored = search
+            .iter()
+            .map(|mut child| {
+                let max = child.maximizing();
+                (child.moved.clone().unwrap(), minimax(&mut child, max, 4))
+            })
+            .collect();
+
+        assert_eq!(
+            select_best_move(Side::Allies, scored),
+            Some(robot.next_move.clone())
+        );
+        assert!(robot.nodes_evaluated() < search.nodes.get());
+    }
 }
@@ -105,8 +105,7 @@ impl Player for RobotIO {
                 self.phase = Some(message.clone());
             }
             Output::WrongInput(_) => {}
-            Output::CountryAlreadyAttacked(_) => {}
-            Output::AttackingNonAdjacentCountry(_, _) => {}
+            Output::OffensiveRejected(_) => {}
             Output::OffensiveResult {
                 from: _,
                 to: _,
@@ -115,17 +114,29 @@ impl Player for RobotIO {
             Output::IncreaseUBoot => {
                 self.phase = Some(message.clone());
             }
-            Output::UBootResult(_) => {}
+            Output::UBootResult { .. } => {}
             Output::IncreaseBlockade => {
                 self.phase = Some(message.clone());
             }
-            Output::BlockadeResult(_) => {}
+            Output::BlockadeResult { .. } => {}
+            Output::ReinforcementPlanRejected { .. } => {}
+            Output::InvalidReinforcement(_) => {}
+            Output::InvalidHit(_) => {}
+            Output::EventNotApplicable(_) => {}
             Output::SelectNationForHit => {
                 self.phase = Some(message.clone());
             }
             Output::EventDrawn(_, _) => {}
             Output::TechnologyResult(_) => {}
             Output::TurnFor(_, _) => {}
+            Output::PassKeyboard(_) => {}
+            Output::TooManyRejectedInputs(_) => {}
+            Output::WastedResources(_, _) => {}
+            Output::ResourcesCollected { .. } => {}
+            Output::Resigned(_) => {}
+            Output::InitiativeBids { .. } => {}
+            Output::Hint(_) => {}
+            Output::GameOver { .. } => {}
         }
     }
 
@@ -134,14 +145,14 @@ impl Player for RobotIO {
             Some(Output::ChooseInitiative) => self.possible_intiatives(),
             Some(Output::ImproveTechnologies(techs)) => {
                 let possible_plays = self.possible_tech_improvements(techs);
-                possible_plays[self.rng.gen_range(0..possible_plays.len())]
+                possible_plays[self.rng.gen_range(0..possible_plays.len())].clone()
             }
             Some(Output::LaunchOffensive(sources)) => {
                 let possible_plays = self.possible_offensives(sources);
                 if possible_plays.is_empty() {
                     Input::Pass
                 } else {
-                    possible_plays[self.rng.gen_range(0..possible_plays.len())]
+                    possible_plays[self.rng.gen_range(0..possible_plays.len())].clone()
                 }
             }
             Some(Output::ReinforceNations) => {
@@ -151,7 +162,11 @@ impl Player for RobotIO {
                 let mut nations = nations
                     .iter()
                     .filter_map(|n| {
-                        let losses = n.maximum_breakdown() - state.breakdown_level(n);
+                        let losses = state
+                            .countries
+                            .get(n)
+                            .map_or(0, |country| country.max_breakdown)
+                            .saturating_sub(state.breakdown_level(n));
                         if losses > 0 {
                             Some((*n, losses))
                         } else {
@@ -331,6 +346,41 @@ mod robot_tests {
         assert_eq!(Input::Number(0), input);
     }
 
+    #[test]
+    fn reinforcements_are_bounded_by_the_loaded_countries_table() {
+        let mut builder = EngineBuilder::new(14);
+        builder.with_resources(Side::Allies, 5);
+        for nation in [
+            Nation::Italy,
+            Nation::Russia,
+            Nation::Egypt,
+            Nation::Serbia,
+            Nation::Romania,
+            Nation::Greece,
+            Nation::FrenchAfrica,
+        ] {
+            builder.with_nation(nation, NationState::AtPeace);
+        }
+        let mut engine = builder
+            .with_nation(Nation::France, NationState::AtWar(5))
+            .build();
+        engine
+            .state
+            .countries
+            .get_mut(&Nation::France)
+            .unwrap()
+            .max_breakdown = 5;
+
+        for seed in 0..20 {
+            let mut robot = RobotIO::new(&Side::Allies, seed);
+
+            robot.output(&Output::CurrentState(engine.state.clone()), &engine);
+            robot.output(&Output::ReinforceNations, &engine);
+
+            assert_eq!(Input::Pass, robot.input(), "seed {}", seed);
+        }
+    }
+
     #[test]
     fn choose_nation_with_maximum_breakdown_when_applying_hits() {
         let engine = EngineBuilder::new(14)
@@ -0,0 +1,205 @@
+use core::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+#[cfg(test)]
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameConfig, GameState};
+
+/// What a saved game needs to continue exactly as it would have: the dice and the
+/// rules it was played with
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct SaveHeader {
+    pub seed: u64,
+    /// Position of the dice, see `GameState::rolls`
+    pub rolls: u64,
+    pub config: GameConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedGame {
+    header: SaveHeader,
+    state: GameState,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum SaveError {
+    /// The file could not be read or written
+    Io(String),
+    /// The file is not a saved game
+    Parse(String),
+    /// Slot names must be non empty and cannot contain paths
+    #[cfg(test)]
+    InvalidSlot(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "cannot access saved game: {}", err),
+            SaveError::Parse(err) => write!(f, "cannot parse saved game: {}", err),
+            #[cfg(test)]
+            SaveError::InvalidSlot(name) => write!(f, "invalid save slot name: '{}'", name),
+        }
+    }
+}
+
+/// Write the given state as JSON to the given file
+pub fn save_game(state: &GameState, path: &Path) -> Result<(), SaveError> {
+    let saved = SavedGame {
+        header: SaveHeader {
+            seed: state.seed(),
+            rolls: state.rolls(),
+            config: state.config.clone(),
+        },
+        state: state.clone(),
+    };
+    let content = serde_json::to_string(&saved).map_err(|e| SaveError::Parse(e.to_string()))?;
+    std::fs::write(path, content).map_err(|e| SaveError::Io(e.to_string()))
+}
+
+/// Read a state written by `save_game`, with its dice and configuration restored
+pub fn load_game(path: &Path) -> Result<GameState, SaveError> {
+    let content = std::fs::read_to_string(path).map_err(|e| SaveError::Io(e.to_string()))?;
+    let saved: SavedGame =
+        serde_json::from_str(&content).map_err(|e| SaveError::Parse(e.to_string()))?;
+    let mut state = saved.state;
+    state.config = saved.header.config;
+    state.restore_dice(saved.header.seed, saved.header.rolls);
+    Ok(state)
+}
+
+/// File of the slot with the given name in the given directory
+#[cfg(test)]
+pub fn slot_path(dir: &Path, name: &str) -> Result<PathBuf, SaveError> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.starts_with('.') {
+        return Err(SaveError::InvalidSlot(name.to_string()));
+    }
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Save the given state in the slot with the given name, replacing any previous save
+#[cfg(test)]
+pub fn save_slot(state: &GameState, dir: &Path, name: &str) -> Result<(), SaveError> {
+    let path = slot_path(dir, name)?;
+    std::fs::create_dir_all(dir).map_err(|e| SaveError::Io(e.to_string()))?;
+    save_game(state, &path)
+}
+
+/// Names of the slots saved in the given directory, in alphabetical order
+#[cfg(test)]
+pub fn list_slots(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Remove the slot with the given name from the given directory
+#[cfg(test)]
+pub fn delete_slot(dir: &Path, name: &str) -> Result<(), SaveError> {
+    std::fs::remove_file(slot_path(dir, name)?).map_err(|e| SaveError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod save_tests {
+    use super::SaveError;
+    use crate::{
+        event::ALL_EVENTS, fixtures::EngineBuilder, GameConfig, GameEngine, Nation, Offensive,
+        OverkillPolicy, Side::*,
+    };
+
+    #[test]
+    fn loaded_game_keeps_config_and_rolls_as_the_original() {
+        let path = std::env::temp_dir().join("der-des-ders-save.json");
+        let config = GameConfig {
+            die_faces: 8,
+            overkill_policy: OverkillPolicy::VictoryPoints,
+            ..GameConfig::default()
+        };
+        let mut engine = EngineBuilder::new(11).with_config(config.clone()).build();
+        engine.draw_events();
+        engine.roll();
+
+        engine.save(&path).unwrap();
+        let mut loaded = GameEngine::load(&path).unwrap();
+
+        assert_eq!(config, loaded.state.config);
+        assert_eq!(engine.state, loaded.state);
+        let original: Vec<u8> = (0..10).map(|_| engine.roll()).collect();
+        let continued: Vec<u8> = (0..10).map(|_| loaded.roll()).collect();
+        assert_eq!(original, continued);
+    }
+
+    #[test]
+    fn loaded_game_keeps_the_effects_of_active_events() {
+        let path = std::env::temp_dir().join("der-des-ders-save-active-events.json");
+        let mut engine = EngineBuilder::new(11).build();
+        // "Race to the sea" ends with this turn, "Unified command" lasts
+        engine.play_events(&ALL_EVENTS[3]);
+        engine.play_events(&ALL_EVENTS[32]);
+        let offensive = Offensive {
+            initiative: Allies,
+            from: Nation::France,
+            to: Nation::Germany,
+            pr: 1,
+        };
+
+        engine.save(&path).unwrap();
+        let mut loaded = GameEngine::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let bonus = engine.compute_bonus(&offensive);
+        assert_ne!(
+            EngineBuilder::new(11).build().compute_bonus(&offensive),
+            bonus
+        );
+        assert_eq!(bonus, loaded.compute_bonus(&offensive));
+
+        engine.new_turn();
+        loaded.new_turn();
+
+        assert_eq!(
+            engine.compute_bonus(&offensive),
+            loaded.compute_bonus(&offensive)
+        );
+    }
+
+    #[test]
+    fn games_are_saved_listed_loaded_and_deleted_by_slot_name() {
+        let dir = std::env::temp_dir().join("der-des-ders-slots");
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut engine = EngineBuilder::new(11).build();
+
+        engine.save_slot(&dir, "opening").unwrap();
+        engine.increase_pr(Allies, 3);
+        engine.save_slot(&dir, "later").unwrap();
+
+        assert_eq!(vec!["later", "opening"], GameEngine::list_slots(&dir));
+
+        engine.load_slot(&dir, "opening").unwrap();
+        assert_eq!(0, engine.state.resources_for(&Allies));
+
+        GameEngine::delete_slot(&dir, "later").unwrap();
+        assert_eq!(vec!["opening"], GameEngine::list_slots(&dir));
+    }
+
+    #[test]
+    fn slot_names_cannot_escape_saves_directory() {
+        let engine = EngineBuilder::new(11).build();
+
+        assert_eq!(
+            Err(SaveError::InvalidSlot("../game".to_string())),
+            engine.save_slot(&std::env::temp_dir(), "../game")
+        );
+    }
+}
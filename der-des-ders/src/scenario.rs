@@ -0,0 +1,309 @@
+use std::{collections::HashMap, path::Path};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use crate::{
+    side::{Nation, NationState},
+    state::TURN_LIMIT,
+    GameEngine, GameState, Input, Output, Phase, Player, Side, Technologies,
+};
+
+/// Seed of the tutorial, so that it always plays the same
+const TUTORIAL_SEED: u64 = 1914;
+
+/// Built-in starting positions, selected with `--scenario`
+#[derive(Copy, Clone, PartialEq, Eq, Debug, ValueEnum)]
+pub enum Scenario {
+    /// France against Germany, teaching the Allies to improve a technology and to
+    /// launch an offensive
+    Tutorial,
+}
+
+/// Guidance shown to a human player when the game reaches the given turn and phase
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct Hint {
+    pub turn: u8,
+    pub phase: Phase,
+    pub text: &'static str,
+}
+
+impl Scenario {
+    /// Starting state of the scenario, whatever the seed given on the command line
+    pub fn state(&self) -> GameState {
+        match self {
+            Scenario::Tutorial => {
+                let mut state = GameState::new(TUTORIAL_SEED);
+                for (nation, status) in state.nations.iter_mut() {
+                    *status = match nation {
+                        Nation::France => NationState::AtWar(5),
+                        Nation::Germany => NationState::AtWar(4),
+                        _ => NationState::AtPeace,
+                    };
+                }
+                state.initiative = Side::Allies;
+                state.increase_pr(Side::Allies, 4);
+                state.increase_pr(Side::Empires, 2);
+                state
+            }
+        }
+    }
+
+    /// Hints for the Allies player, in the order they are shown
+    pub fn hints(&self) -> Vec<Hint> {
+        match self {
+            Scenario::Tutorial => vec![
+                Hint {
+                    turn: 1,
+                    phase: Phase::DrawEvents,
+                    text: "Each turn starts with 3 events, then both sides collect PR from \
+                           their nations at war. The Allies have the initiative and play first.",
+                },
+                Hint {
+                    turn: 1,
+                    phase: Phase::ImproveTechnologies(Side::Allies),
+                    text: "Spend PR to try to improve a technology: type `attack 2` to roll \
+                           for the Attack technology with 2 PR, then `pass` when done.",
+                },
+                Hint {
+                    turn: 1,
+                    phase: Phase::LaunchOffensives(Side::Allies),
+                    text: "Launch an offensive: type `off france germany 2` to attack Germany \
+                           from France, rolling one die per PR. Each hit breaks Germany down.",
+                },
+                Hint {
+                    turn: 1,
+                    phase: Phase::Reinforcements(Side::Allies),
+                    text: "Reinforce France with leftover PR, or `pass` to end your turn and \
+                           let the Empires play.",
+                },
+            ],
+        }
+    }
+}
+
+/// A starting position read from a TOML file, see `GameEngine::from_scenario`.
+/// Anything left out is as in a new game with the same seed.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScenarioFile {
+    seed: u64,
+    turn: Option<u8>,
+    initiative: Option<Side>,
+    #[serde(default)]
+    resources: HashMap<Side, u8>,
+    #[serde(default)]
+    nations: HashMap<Nation, NationState>,
+    #[serde(default)]
+    technologies: HashMap<Side, Technologies>,
+}
+
+impl ScenarioFile {
+    fn state(self) -> Result<GameState, String> {
+        let mut state = GameState::new(self.seed);
+        if let Some(turn) = self.turn {
+            if !(1..TURN_LIMIT).contains(&turn) {
+                return Err(format!(
+                    "turn {} is not between 1 and {}",
+                    turn,
+                    TURN_LIMIT - 1
+                ));
+            }
+            state.start_on_turn(turn);
+        }
+        if let Some(initiative) = self.initiative {
+            state.initiative = initiative;
+        }
+        for (side, pr) in self.resources {
+            state.increase_pr(side, pr);
+        }
+        state.nations.extend(self.nations);
+        for (side, technologies) in self.technologies {
+            *state.state_of_war.get_mut(&side).unwrap().technologies = technologies;
+        }
+        Ok(state)
+    }
+}
+
+impl GameEngine {
+    /// Start a game from the scenario in the given TOML file
+    pub fn from_scenario(path: &Path) -> Result<GameEngine, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+        let scenario: ScenarioFile = toml::from_str(&content)
+            .map_err(|e| format!("cannot parse {}: {}", path.display(), e))?;
+        let state = scenario
+            .state()
+            .map_err(|e| format!("invalid scenario {}: {}", path.display(), e))?;
+        Ok(GameEngine::from_state(state))
+    }
+}
+
+/// Shows a scenario's hints to the wrapped player, each one once, before the first
+/// output of the phase it belongs to
+pub struct Tutor {
+    inner: Box<dyn Player>,
+    hints: Vec<Hint>,
+}
+
+impl Tutor {
+    pub fn new(inner: Box<dyn Player>, hints: Vec<Hint>) -> Self {
+        Tutor { inner, hints }
+    }
+}
+
+impl Player for Tutor {
+    fn output(&mut self, message: &Output, engine: &GameEngine) {
+        let state = &engine.state;
+        if let Some(index) = self
+            .hints
+            .iter()
+            .position(|hint| hint.turn == state.current_turn && hint.phase == state.phase)
+        {
+            let hint = self.hints.remove(index);
+            self.inner
+                .output(&Output::Hint(hint.text.to_string()), engine);
+        }
+        self.inner.output(message, engine);
+    }
+
+    fn input(&mut self) -> Input {
+        self.inner.input()
+    }
+
+    fn out(&self) -> Vec<Output> {
+        self.inner.out()
+    }
+
+    fn begin_batch(&mut self) {
+        self.inner.begin_batch()
+    }
+
+    fn end_batch(&mut self) {
+        self.inner.end_batch()
+    }
+}
+
+#[cfg(test)]
+mod scenario_tests {
+    use super::*;
+    use crate::{
+        event::ALL_EVENTS,
+        fixtures::{EngineBuilder, PlayerDouble},
+        Nation::*,
+        NationState::*,
+        Side::*,
+        Technologies,
+    };
+
+    #[test]
+    fn tutorial_starts_with_france_against_germany() {
+        let state = Scenario::Tutorial.state();
+
+        assert_eq!(vec![France], state.all_nations_at_war(Allies));
+        assert_eq!(vec![Germany], state.all_nations_at_war(Empires));
+        assert_eq!(4, state.resources_for(&Allies));
+        assert_eq!(2, state.resources_for(&Empires));
+        assert_eq!(Allies, state.initiative);
+    }
+
+    #[test]
+    fn scenario_file_sets_up_the_same_engine_as_the_builder() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios/eastern_front.toml");
+
+        let engine = GameEngine::from_scenario(&path).unwrap();
+
+        let expected = EngineBuilder::new(1915)
+            .starting_on_turn(2)
+            .with_initiative(Empires)
+            .with_resources(Allies, 5)
+            .with_resources(Empires, 7)
+            .with_nation(Russia, AtWar(6))
+            .with_nation(Serbia, AtWar(2))
+            .with_nation(France, AtPeace)
+            .with_nation(Italy, AtPeace)
+            .with_nation(Egypt, AtPeace)
+            .with_nation(FrenchAfrica, AtPeace)
+            .with_nation(Germany, AtWar(7))
+            .with_nation(AustriaHungary, AtWar(4))
+            .with_nation(OttomanEmpire, AtPeace)
+            .with_nation(GermanAfrica, AtPeace)
+            .with_technologies(
+                Empires,
+                Technologies {
+                    attack: 1,
+                    defense: 0,
+                    artillery: 1,
+                    air: 0,
+                },
+            )
+            .build();
+        assert_eq!(expected.state, engine.state);
+    }
+
+    #[test]
+    fn scenario_file_reports_unknown_fields() {
+        let path = std::env::temp_dir().join(format!(
+            "der-des-ders-scenario-unknown-fields-{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, "seed = 3\nweather = \"rain\"\n").unwrap();
+
+        let err = GameEngine::from_scenario(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(err.contains("weather"), "{}", err);
+    }
+
+    #[test]
+    fn scenario_file_rejects_turns_outside_the_war() {
+        let path = std::env::temp_dir().join(format!(
+            "der-des-ders-scenario-turn-{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(&path, "seed = 3\nturn = 15\n").unwrap();
+
+        let err = GameEngine::from_scenario(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(err.contains("turn 15"), "{}", err);
+    }
+
+    #[test]
+    fn scenario_starting_in_a_later_year_draws_the_events_of_that_year() {
+        let mut state = GameState::new(3);
+
+        state.start_on_turn(5);
+
+        for event in ALL_EVENTS.iter() {
+            let in_play = event.year <= 1916 && event.not_after.is_none_or(|year| year >= 1916);
+            assert_eq!(in_play, state.can_draw_event(event), "{:?}", event);
+        }
+    }
+
+    #[test]
+    fn tutor_shows_hint_once_on_reaching_its_phase() {
+        let mut engine = GameEngine::from_state(Scenario::Tutorial.state());
+        let mut tutor = Tutor::new(
+            Box::new(PlayerDouble {
+                out: Vec::new(),
+                inp: Vec::new(),
+            }),
+            Scenario::Tutorial.hints(),
+        );
+        engine.set_phase(Phase::LaunchOffensives(Allies));
+
+        tutor.output(&Output::LaunchOffensive(vec![France]), &engine);
+        tutor.output(&Output::LaunchOffensive(vec![France]), &engine);
+
+        let hint = Scenario::Tutorial.hints()[2].text.to_string();
+        assert_eq!(
+            vec![
+                Output::Hint(hint),
+                Output::LaunchOffensive(vec![France]),
+                Output::LaunchOffensive(vec![France]),
+            ],
+            tutor.out()
+        );
+    }
+}
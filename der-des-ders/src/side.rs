@@ -1,7 +1,11 @@
 use core::fmt;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
 
-#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Copy, Clone, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Nation {
     France,
     Italy,
@@ -11,6 +15,7 @@ pub enum Nation {
     Romania,
     Greece,
     FrenchAfrica,
+    UnitedStates,
     Germany,
     AustriaHungary,
     OttomanEmpire,
@@ -33,6 +38,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => true,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -48,6 +54,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => true,
                 OttomanEmpire => false,
@@ -63,6 +70,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => true,
                 AustriaHungary => true,
                 OttomanEmpire => true,
@@ -78,6 +86,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => true,
@@ -93,6 +102,7 @@ impl Nation {
                 Romania => true,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => true,
                 OttomanEmpire => false,
@@ -108,6 +118,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => true,
                 OttomanEmpire => false,
@@ -123,6 +134,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => true,
@@ -138,6 +150,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -153,6 +166,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -168,6 +182,7 @@ impl Nation {
                 Romania => true,
                 Greece => false,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -183,6 +198,7 @@ impl Nation {
                 Romania => false,
                 Greece => true,
                 FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -198,6 +214,23 @@ impl Nation {
                 Romania => true,
                 Greece => true,
                 FrenchAfrica => false,
+                UnitedStates => false,
+                Germany => false,
+                AustriaHungary => false,
+                OttomanEmpire => false,
+                Bulgaria => false,
+                GermanAfrica => false,
+            },
+            UnitedStates => match to {
+                France => false,
+                Italy => false,
+                Russia => false,
+                Egypt => false,
+                Serbia => false,
+                Romania => false,
+                Greece => false,
+                FrenchAfrica => false,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -213,6 +246,7 @@ impl Nation {
                 Romania => false,
                 Greece => false,
                 FrenchAfrica => true,
+                UnitedStates => false,
                 Germany => false,
                 AustriaHungary => false,
                 OttomanEmpire => false,
@@ -222,7 +256,9 @@ impl Nation {
         }
     }
 
-    pub(crate) fn side(&self) -> Side {
+    /// Side this nation fights for in the built-in `COUNTRIES` table
+    #[cfg(test)]
+    pub fn side(&self) -> Side {
         COUNTRIES
             .iter()
             .find(|(nation, _)| nation == self)
@@ -231,13 +267,10 @@ impl Nation {
             .side
     }
 
-    pub(crate) fn maximum_breakdown(&self) -> u8 {
-        COUNTRIES
-            .iter()
-            .find(|(nation, _)| nation == self)
-            .unwrap()
-            .1
-            .max_breakdown
+    /// Enemy nations this nation borders, whether they are at war or not
+    #[cfg(test)]
+    pub fn all_adjacent(&self) -> Vec<Nation> {
+        self.adjacent_nations().to_vec()
     }
 
     pub(crate) fn values() -> impl Iterator<Item = &'static Nation> {
@@ -250,6 +283,7 @@ impl Nation {
             Romania,
             Greece,
             FrenchAfrica,
+            UnitedStates,
             Germany,
             AustriaHungary,
             OttomanEmpire,
@@ -259,15 +293,70 @@ impl Nation {
         .iter()
     }
 
-    pub(crate) fn is_allies(&self) -> bool {
-        self.side() == Side::Allies
+    /// List (enemy) nations adjacent to this nation.
+    /// Adjacency is static, hence lists are computed once from `adjacent_to`.
+    pub(crate) fn adjacent_nations(&self) -> &'static [Nation] {
+        static ADJACENCY: OnceLock<HashMap<Nation, Vec<Nation>>> = OnceLock::new();
+        ADJACENCY
+            .get_or_init(|| {
+                Nation::values()
+                    .map(|nation| {
+                        let adjacent = Nation::values()
+                            .filter(|other| nation.adjacent_to(other))
+                            .cloned()
+                            .collect();
+                        (*nation, adjacent)
+                    })
+                    .collect()
+            })
+            .get(self)
+            .unwrap()
+    }
+
+    /// Compact code for this nation, e.g. for map rendering and logs
+    #[cfg(test)]
+    pub fn short_code(&self) -> &'static str {
+        SHORT_CODES
+            .iter()
+            .find(|(nation, _)| nation == self)
+            .map(|(_, code)| *code)
+            .unwrap()
+    }
+
+    #[cfg(test)]
+    pub fn from_short_code(code: &str) -> Option<Nation> {
+        SHORT_CODES
+            .iter()
+            .find(|(_, c)| c.eq_ignore_ascii_case(code))
+            .map(|(nation, _)| *nation)
     }
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[cfg(test)]
+const SHORT_CODES: [(Nation, &str); 14] = [
+    (France, "FR"),
+    (Italy, "IT"),
+    (Russia, "RU"),
+    (Egypt, "EG"),
+    (Serbia, "RS"),
+    (Romania, "RO"),
+    (Greece, "GR"),
+    (FrenchAfrica, "FA"),
+    (UnitedStates, "US"),
+    (Germany, "DE"),
+    (AustriaHungary, "AH"),
+    (OttomanEmpire, "OT"),
+    (Bulgaria, "BG"),
+    (GermanAfrica, "GA"),
+];
+
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum NationState {
     AtWar(u8),
+    /// Not at war yet, or out of the war without being defeated
     AtPeace,
+    /// Defeated, the nation is out of the war for good
+    Surrendered,
 }
 
 /// Returns the operational level given a breakdown value.
@@ -291,28 +380,39 @@ impl NationState {
     pub fn operational_level(&self) -> u8 {
         match self {
             AtWar(breakdown) => operational_level(*breakdown),
-            AtPeace => 0,
+            AtPeace | Surrendered => 0,
         }
     }
 
     pub(crate) fn reinforce(&mut self, pr: u8) {
         match self {
             AtWar(breakdown) => *breakdown += pr,
-            AtPeace => {}
+            AtPeace | Surrendered => {}
         }
     }
 
-    pub(crate) fn breakdown(&self) -> u8 {
+    /// The breakdown level of a nation at war, `None` otherwise
+    pub fn breakdown(&self) -> Option<u8> {
         match self {
-            AtWar(breakdown) => *breakdown,
-            AtPeace => 0,
+            AtWar(breakdown) => Some(*breakdown),
+            AtPeace | Surrendered => None,
         }
     }
 
+    pub fn is_at_war(&self) -> bool {
+        matches!(self, AtWar(_))
+    }
+
+    /// Whether an event can bring the nation into the war, which a defeated nation
+    /// cannot rejoin
+    pub fn can_enter_war(&self) -> bool {
+        matches!(self, AtPeace)
+    }
+
     pub(crate) fn breakdown_level(&self) -> u8 {
         match self {
             AtWar(breakdown) => *breakdown,
-            AtPeace => 0,
+            AtPeace | Surrendered => 0,
         }
     }
 }
@@ -322,6 +422,7 @@ impl Display for NationState {
         match self {
             AtWar(breakdown) => write!(f, "At war: {}", breakdown),
             AtPeace => write!(f, "At peace"),
+            Surrendered => write!(f, "Surrendered"),
         }
     }
 }
@@ -340,6 +441,7 @@ impl Display for Nation {
                 Nation::Romania => "Romania",
                 Nation::Greece => "Greece",
                 Nation::FrenchAfrica => "French Africa",
+                Nation::UnitedStates => "United States",
                 Nation::Germany => "Germany",
                 Nation::AustriaHungary => "Austria-Hungary",
                 Nation::OttomanEmpire => "Ottoman Empire",
@@ -350,13 +452,22 @@ impl Display for Nation {
     }
 }
 
-#[derive(Hash, Eq, PartialEq, Copy, Clone, Debug)]
+#[derive(Hash, Eq, PartialEq, Copy, Clone, Debug, Serialize, Deserialize)]
 pub enum Side {
     Allies,
     Empires,
 }
 
 impl Side {
+    /// Both sides, Allies first
+    pub fn all() -> [Side; 2] {
+        [Side::Allies, Side::Empires]
+    }
+
+    pub fn iter() -> impl Iterator<Item = Side> {
+        Side::all().into_iter()
+    }
+
     pub(crate) fn other(&self) -> Side {
         match self {
             Side::Allies => Side::Empires,
@@ -378,7 +489,7 @@ impl Display for Side {
     }
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct Country {
     pub nation: Nation,
     pub side: Side,
@@ -391,7 +502,7 @@ pub struct Country {
 
 use Nation::*;
 
-pub const COUNTRIES: [(Nation, Country); 13] = [
+pub const COUNTRIES: [(Nation, Country); 14] = [
     (
         France,
         Country {
@@ -488,6 +599,20 @@ pub const COUNTRIES: [(Nation, Country); 13] = [
             vp: 1,
         },
     ),
+    // The US have no front: no breakdown to take hits nor to reinforce, they only bring
+    // the Allies PR, see `GameState::lafayette_bonus`
+    (
+        UnitedStates,
+        Country {
+            nation: UnitedStates,
+            side: Side::Allies,
+            max_tech_level: 0,
+            resources: 0,
+            attack_factor: 5,
+            max_breakdown: 0,
+            vp: 0,
+        },
+    ),
     (
         Germany,
         Country {
@@ -552,7 +677,7 @@ pub const COUNTRIES: [(Nation, Country); 13] = [
 
 use NationState::*;
 
-pub const INITIAL_NATION_STATE: [(Nation, NationState); 13] = [
+pub const INITIAL_NATION_STATE: [(Nation, NationState); 14] = [
     (France, AtWar(7)),
     (Italy, AtPeace),
     (Russia, AtWar(7)),
@@ -561,6 +686,7 @@ pub const INITIAL_NATION_STATE: [(Nation, NationState); 13] = [
     (Romania, AtPeace),
     (Greece, AtPeace),
     (FrenchAfrica, AtWar(4)),
+    (UnitedStates, AtPeace),
     (Germany, AtWar(8)),
     (AustriaHungary, AtWar(5)),
     (OttomanEmpire, AtWar(5)),
@@ -570,7 +696,59 @@ pub const INITIAL_NATION_STATE: [(Nation, NationState); 13] = [
 
 #[cfg(test)]
 mod tests {
-    use crate::operational_level;
+    use std::collections::HashSet;
+
+    use crate::{operational_level, Nation, NationState, Side};
+
+    #[test]
+    fn all_sides_are_allies_then_empires() {
+        assert_eq!([Side::Allies, Side::Empires], Side::all());
+        assert_eq!(
+            vec![Side::Allies, Side::Empires],
+            Side::iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn other_side_of_other_side_is_same_side() {
+        for side in Side::iter() {
+            assert_ne!(side, side.other());
+            assert_eq!(side, side.other().other());
+        }
+    }
+
+    #[test]
+    fn short_codes_are_unique_and_round_trip() {
+        let codes: HashSet<&str> = Nation::values().map(|n| n.short_code()).collect();
+
+        assert_eq!(14, codes.len());
+        for nation in Nation::values() {
+            assert_eq!(Some(*nation), Nation::from_short_code(nation.short_code()));
+        }
+        assert_eq!(None, Nation::from_short_code("XX"));
+    }
+
+    #[test]
+    fn nations_know_their_side() {
+        assert_eq!(Side::Allies, Nation::France.side());
+        assert_eq!(Side::Empires, Nation::Germany.side());
+    }
+
+    #[test]
+    fn all_adjacent_nations_agree_with_adjacency() {
+        assert!(Nation::France.all_adjacent().contains(&Nation::Germany));
+        for nation in Nation::values() {
+            for other in Nation::values() {
+                assert_eq!(
+                    nation.adjacent_to(other),
+                    nation.all_adjacent().contains(other),
+                    "{} {}",
+                    nation,
+                    other
+                );
+            }
+        }
+    }
 
     #[test]
     fn operational_level_depends_on_breakdown_value() {
@@ -578,4 +756,18 @@ mod tests {
         assert_eq!(1, operational_level(3));
         assert_eq!(3, operational_level(7));
     }
+
+    #[test]
+    fn breakdown_is_only_defined_for_nations_at_war() {
+        assert_eq!(Some(4), NationState::AtWar(4).breakdown());
+        assert_eq!(Some(0), NationState::AtWar(0).breakdown());
+        assert_eq!(None, NationState::AtPeace.breakdown());
+    }
+
+    #[test]
+    fn only_nations_at_war_are_at_war() {
+        assert!(NationState::AtWar(4).is_at_war());
+        assert!(NationState::AtWar(0).is_at_war());
+        assert!(!NationState::AtPeace.is_at_war());
+    }
 }
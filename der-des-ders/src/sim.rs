@@ -0,0 +1,278 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    minimax::{Robot, RobotConfig},
+    robot::RobotIO,
+    robot_seed, run_turn, GameEngine, GameResult, Player, PlayerType, Players, Side,
+};
+
+/// Plays a full game between two search robots configured as given
+pub fn play_game(seed: u64, allies: RobotConfig, empires: RobotConfig) -> GameEngine {
+    let mut engine = GameEngine::new(seed);
+    let mut players = Players {
+        allies_player: Box::new(Robot::with_config(Side::Allies, allies)),
+        empires_player: Box::new(Robot::with_config(Side::Empires, empires)),
+    };
+    while !engine.game_ends() {
+        run_turn(&mut players, &mut engine);
+    }
+    engine
+}
+
+/// Head-to-head results of two robot configurations, from the point of view of
+/// the first one
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub struct Comparison {
+    pub games: u32,
+    pub a_wins: u32,
+    /// Number of games neither configuration won, see `GameState::result`
+    pub draws: u32,
+    /// Number of games the first configuration played as Allies
+    pub a_as_allies: u32,
+    /// Number of games the first configuration played as Empires
+    pub a_as_empires: u32,
+}
+
+impl Comparison {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        self.a_wins as f64 / self.games as f64
+    }
+
+    /// 95% confidence interval of the win rate, using the normal approximation
+    pub fn confidence_interval(&self) -> (f64, f64) {
+        if self.games == 0 {
+            return (0.0, 1.0);
+        }
+        let p = self.win_rate();
+        let margin = 1.96 * (p * (1.0 - p) / self.games as f64).sqrt();
+        ((p - margin).max(0.0), (p + margin).min(1.0))
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (low, high) = self.confidence_interval();
+        write!(
+            f,
+            "A won {}/{} games ({:.1}%, 95% CI {:.1}%-{:.1}%) with {} draws, playing {} as Allies and {} as Empires",
+            self.a_wins,
+            self.games,
+            self.win_rate() * 100.0,
+            low * 100.0,
+            high * 100.0,
+            self.draws,
+            self.a_as_allies,
+            self.a_as_empires
+        )
+    }
+}
+
+/// Pits two robot configurations against each other, two games per seed.
+/// Configuration `a` plays each seed once as Allies and once as Empires to cancel
+/// the bias of both the sides and the dice.
+pub fn compare_ais(
+    a: RobotConfig,
+    b: RobotConfig,
+    seeds: impl IntoIterator<Item = u64>,
+) -> Comparison {
+    let mut comparison = Comparison::default();
+    for seed in seeds {
+        for a_side in [Side::Allies, Side::Empires] {
+            let engine = match a_side {
+                Side::Allies => {
+                    comparison.a_as_allies += 1;
+                    play_game(seed, a, b)
+                }
+                Side::Empires => {
+                    comparison.a_as_empires += 1;
+                    play_game(seed, b, a)
+                }
+            };
+            comparison.games += 1;
+            match (engine.result(), a_side) {
+                (GameResult::Draw, _) => comparison.draws += 1,
+                (GameResult::AlliesWin, Side::Allies) | (GameResult::EmpiresWin, Side::Empires) => {
+                    comparison.a_wins += 1
+                }
+                _ => {}
+            }
+        }
+    }
+    comparison
+}
+
+/// Aggregate results of a batch of games, see `run_batch`
+#[derive(Eq, PartialEq, Clone, Debug, Default)]
+pub struct BatchStats {
+    pub games: u32,
+    pub allies_wins: u32,
+    pub empires_wins: u32,
+    /// Games ending with equal VP, see `GameState::result`
+    pub draws: u32,
+    /// Turns played over all games, see `GameState::turns_played`
+    pub total_turns: u32,
+    /// VP of each side at the end of the games, summed over all games
+    pub allies_vp: u32,
+    pub empires_vp: u32,
+}
+
+impl BatchStats {
+    fn record(&mut self, engine: &GameEngine) {
+        let state = &engine.state;
+        self.games += 1;
+        match engine.result() {
+            GameResult::AlliesWin => self.allies_wins += 1,
+            GameResult::EmpiresWin => self.empires_wins += 1,
+            GameResult::Draw => self.draws += 1,
+        }
+        self.total_turns += state.turns_played() as u32;
+        self.allies_vp += state.state_of_war.get(&Side::Allies).unwrap().vp as u32;
+        self.empires_vp += state.state_of_war.get(&Side::Empires).unwrap().vp as u32;
+    }
+
+    fn average(&self, total: u32) -> f64 {
+        if self.games == 0 {
+            return 0.0;
+        }
+        total as f64 / self.games as f64
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        self.average(self.total_turns)
+    }
+
+    pub fn average_vp(&self, side: Side) -> f64 {
+        match side {
+            Side::Allies => self.average(self.allies_vp),
+            Side::Empires => self.average(self.empires_vp),
+        }
+    }
+}
+
+impl Display for BatchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} games: Allies won {}, Empires won {}, {} draws, {:.1} turns on average, average VP {:.1} for Allies and {:.1} for Empires",
+            self.games,
+            self.allies_wins,
+            self.empires_wins,
+            self.draws,
+            self.average_turns(),
+            self.average_vp(Side::Allies),
+            self.average_vp(Side::Empires)
+        )
+    }
+}
+
+/// Players able to play a batch of games without reading anything from stdin
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum BatchPlayer {
+    Robot,
+    Search,
+}
+
+impl TryFrom<PlayerType> for BatchPlayer {
+    type Error = PlayerType;
+
+    fn try_from(player_type: PlayerType) -> Result<Self, Self::Error> {
+        match player_type {
+            PlayerType::Robot => Ok(BatchPlayer::Robot),
+            PlayerType::Search => Ok(BatchPlayer::Search),
+            PlayerType::Human | PlayerType::Json => Err(player_type),
+        }
+    }
+}
+
+impl BatchPlayer {
+    fn player(self, side: Side, seed: u64, depth: u8) -> Box<dyn Player> {
+        match self {
+            BatchPlayer::Robot => Box::new(RobotIO::new(&side, robot_seed(seed, side))),
+            BatchPlayer::Search => Box::new(Robot::with_config(
+                side,
+                RobotConfig {
+                    depth,
+                    resign: None,
+                    temperature: 0.0,
+                },
+            )),
+        }
+    }
+}
+
+/// Plays `n` games without any human player, one per seed starting at `seed_start`,
+/// with players of the given types. Search robots look `depth` moves ahead.
+pub fn run_batch(
+    n: usize,
+    seed_start: u64,
+    allies: BatchPlayer,
+    empires: BatchPlayer,
+    depth: u8,
+) -> BatchStats {
+    let mut stats = BatchStats::default();
+    for seed in seed_start..seed_start + n as u64 {
+        let mut engine = GameEngine::new(seed);
+        let mut players = Players {
+            allies_player: allies.player(Side::Allies, seed, depth),
+            empires_player: empires.player(Side::Empires, seed, depth),
+        };
+        while !engine.game_ends() {
+            run_turn(&mut players, &mut engine);
+        }
+        stats.record(&engine);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod sim_test {
+    use super::*;
+
+    #[test]
+    fn comparison_plays_each_seed_from_both_sides() {
+        let a = RobotConfig {
+            depth: 1,
+            resign: None,
+            temperature: 0.0,
+        };
+        let b = RobotConfig {
+            depth: 2,
+            resign: None,
+            temperature: 0.0,
+        };
+
+        let comparison = compare_ais(a, b, 1..3);
+
+        assert_eq!(4, comparison.games);
+        assert_eq!(2, comparison.a_as_allies);
+        assert_eq!(2, comparison.a_as_empires);
+        let (low, high) = comparison.confidence_interval();
+        assert!(low <= comparison.win_rate() && comparison.win_rate() <= high);
+    }
+
+    #[test]
+    fn batch_counts_a_win_or_a_draw_for_every_game() {
+        let stats = run_batch(5, 1, BatchPlayer::Robot, BatchPlayer::Robot, 1);
+
+        assert_eq!(5, stats.games);
+        assert_eq!(5, stats.allies_wins + stats.empires_wins + stats.draws);
+        assert!(stats.average_turns() >= 1.0);
+    }
+
+    #[test]
+    fn batch_only_accepts_players_which_do_not_read_stdin() {
+        assert_eq!(
+            Ok(BatchPlayer::Robot),
+            BatchPlayer::try_from(PlayerType::Robot)
+        );
+        assert_eq!(
+            Ok(BatchPlayer::Search),
+            BatchPlayer::try_from(PlayerType::Search)
+        );
+        assert!(BatchPlayer::try_from(PlayerType::Human).is_err());
+        assert!(BatchPlayer::try_from(PlayerType::Json).is_err());
+    }
+}
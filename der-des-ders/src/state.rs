@@ -2,21 +2,34 @@ use core::fmt;
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
-use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::action_log::RolledDice;
+use crate::config::*;
+use crate::countries::{load_countries, CountriesError};
 use crate::event::*;
+use crate::events::Layer;
 use crate::side::*;
 use crate::tech::*;
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct WarState {
     pub resources: u8,
     pub vp: u8,
     pub technologies: Box<Technologies>,
+    /// PR collected on each turn, only recorded when `GameConfig::record_resources` is set
+    pub collected: Vec<u8>,
+    /// Year this side reached the trench warfare defense level, if it did
+    pub trench_warfare_since: Option<u16>,
+    /// PR spent trying to improve technologies, whether it succeeded or not
+    pub tech_pr_spent: u16,
+    /// Technology levels gained by spending PR
+    pub tech_levels_gained: u8,
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum Phase {
     Initiative(Side),
     DrawEvents,
@@ -29,7 +42,35 @@ pub enum Phase {
     NewTurn,
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+/// Kind of input a phase expects from the side to play
+#[cfg(test)]
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum InputKind {
+    Bid,
+    TechSelection,
+    OffensiveDeclaration,
+    Reinforcement,
+    SeaBonus,
+    None,
+}
+
+impl Phase {
+    /// The kind of input players are expected to provide in this phase, so that
+    /// frontends can render the right control
+    #[cfg(test)]
+    pub fn expected_input(&self) -> InputKind {
+        match self {
+            Phase::Initiative(_) => InputKind::Bid,
+            Phase::ImproveTechnologies(_) => InputKind::TechSelection,
+            Phase::LaunchOffensives(_) => InputKind::OffensiveDeclaration,
+            Phase::Reinforcements(_) => InputKind::Reinforcement,
+            Phase::UBoot | Phase::Blockade => InputKind::SeaBonus,
+            Phase::DrawEvents | Phase::CollectResources | Phase::NewTurn => InputKind::None,
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct GameState {
     pub current_turn: u8,
     pub phase: Phase,
@@ -41,9 +82,123 @@ pub struct GameState {
     pub countries: HashMap<Nation, Country>,
     pub state_of_war: HashMap<Side, WarState>,
     pub end_game_this_turn: bool,
+    /// Events drawn during the last events phase
+    pub events_drawn_this_turn: Vec<Event>,
+    pub config: GameConfig,
+    active_events: Vec<ActiveEvent>,
     seed: u64,
-    rng: StdRng,
+    #[serde(with = "dice")]
+    rng: ChaCha12Rng,
+    last_roll: u8,
+    /// The surrendering nation, die roll and VP which led to a sudden death victory, if any
+    sudden_death: Option<(Nation, u8, u8)>,
+    /// Side which resigned the game, if any
+    resigned: Option<Side>,
+    /// PR spent or gained this turn without any effect, see `GameState::waste_pr`
+    wasted_pr: HashMap<Side, u8>,
     events_pool: Vec<Event>,
+    /// Weights of events overriding their definition's, by event id
+    #[serde(default)]
+    event_weights: HashMap<u8, u8>,
+    /// Ids of the events the next draw yields instead of drawing at random
+    forced_events: Vec<u8>,
+    /// Nations which surrendered, with the side they surrendered to, in order
+    surrendered: Vec<(Nation, Side)>,
+    /// Nations which launched an offensive during this turn's offensives phases
+    attacked_this_turn: Vec<Nation>,
+    #[serde(skip)]
+    rolled: RolledDice,
+}
+
+/// Weighted terms of one side's strength in `GameState::valuation`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct ValuationTerms {
+    pub resources: f64,
+    pub technologies: f64,
+    pub breakdowns: f64,
+    pub victory_points: f64,
+}
+
+impl ValuationTerms {
+    pub fn total(&self) -> f64 {
+        self.resources + self.technologies + self.breakdowns + self.victory_points
+    }
+}
+
+/// Each side's terms and the resulting valuation, see `GameState::valuation_components`
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct ValuationBreakdown {
+    pub allies: ValuationTerms,
+    pub empires: ValuationTerms,
+    pub valuation: f64,
+}
+
+impl Display for ValuationBreakdown {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        writeln!(f, "{:<10} {:>8} {:>8}", "", "Allies", "Empires")?;
+        writeln!(
+            f,
+            "{:<10} {:>8.1} {:>8.1}",
+            "Resources", self.allies.resources, self.empires.resources
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>8.1} {:>8.1}",
+            "Techs", self.allies.technologies, self.empires.technologies
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>8.1} {:>8.1}",
+            "Breakdowns", self.allies.breakdowns, self.empires.breakdowns
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>8.1} {:>8.1}",
+            "VP", self.allies.victory_points, self.empires.victory_points
+        )?;
+        writeln!(
+            f,
+            "{:<10} {:>8.1} {:>8.1}",
+            "Total",
+            self.allies.total(),
+            self.empires.total()
+        )?;
+        write!(f, "Valuation: {:.3}", self.valuation)
+    }
+}
+
+/// Who won a finished game, unlike `GameState::winner` telling draws apart
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GameResult {
+    AlliesWin,
+    EmpiresWin,
+    /// The game reached its end without a decision, both sides having the same VP
+    Draw,
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GameResult::AlliesWin => write!(f, "{} win", Side::Allies),
+            GameResult::EmpiresWin => write!(f, "{} win", Side::Empires),
+            GameResult::Draw => write!(f, "Draw"),
+        }
+    }
+}
+
+/// Final outcome of a game
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct GameSummary {
+    pub winner: Side,
+    pub turns: u8,
+    pub allies_vp: u8,
+    pub empires_vp: u8,
+    pub allies_resources: u8,
+    pub empires_resources: u8,
+    pub allies_collected: Vec<u8>,
+    pub empires_collected: Vec<u8>,
+    /// The rules the game was played with
+    pub config: GameConfig,
 }
 
 #[derive(Eq, PartialEq, Clone, Debug)]
@@ -54,28 +209,105 @@ pub struct Offensive {
     pub pr: u8,
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum OffensiveOutcome {
-    NotEnoughResources(u8, u8),
-    OperationalLevelTooLow(u8, u8),
+    /// The offensive was blocked before rolling any die
+    Rejected(OffensiveRejection),
     Hits(HitsResult),
+    /// The side had fewer PR than requested and spent all it had, see
+    /// `GameConfig::clamp_offensive_pr`: PR requested, PR used and hits result
+    PartialSpend(u8, u8, HitsResult),
 }
 
 impl Display for OffensiveOutcome {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         match self {
-            OffensiveOutcome::NotEnoughResources(pr, resources) => {
-                write!(f, "Not enough resources ({} < {})", pr, resources)
+            OffensiveOutcome::Rejected(rejection) => write!(f, "{}", rejection),
+            OffensiveOutcome::Hits(result) => write!(f, "{}", result),
+            OffensiveOutcome::PartialSpend(requested, used, result) => {
+                write!(f, "{} (spent {} of {} PR)", result, used, requested)
+            }
+        }
+    }
+}
+
+/// Why an offensive cannot be launched, no PR being spent nor dice rolled
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum OffensiveRejection {
+    /// The nation already launched an offensive this turn
+    AlreadyAttacked(Nation),
+    NotAdjacent(Nation, Nation),
+    /// No PR were assigned to the offensive, hence no dice to roll
+    NoPr(Nation, Nation),
+    OperationalLevelTooLow(u8, u8),
+    NotEnoughResources(u8, u8),
+}
+
+impl Display for OffensiveRejection {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            OffensiveRejection::AlreadyAttacked(nation) => {
+                write!(f, "Country already attacked: {}", nation)
+            }
+            OffensiveRejection::NotAdjacent(from, to) => {
+                write!(f, "{} is not adjacent to {}", from, to)
             }
-            OffensiveOutcome::OperationalLevelTooLow(level, required) => {
+            OffensiveRejection::NoPr(from, to) => {
+                write!(f, "Offensive from {} to {} needs at least 1 PR", from, to)
+            }
+            OffensiveRejection::OperationalLevelTooLow(level, required) => {
                 write!(f, "Operational level too low ({} < {})", level, required)
             }
-            OffensiveOutcome::Hits(result) => write!(f, "{}", result),
+            OffensiveRejection::NotEnoughResources(pr, resources) => {
+                write!(f, "Not enough resources ({} < {})", pr, resources)
+            }
         }
     }
 }
 
+/// A successful reinforcement
 #[derive(Eq, PartialEq, Clone, Debug)]
+pub struct ReinforceResult {
+    pub nation: Nation,
+    /// Breakdown levels gained
+    pub levels: u8,
+    pub spent: u8,
+    /// Whether the PR offered could pay for more levels than the nation could gain,
+    /// the PR left being kept
+    pub capped: bool,
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub enum ReinforceError {
+    NationNotAtWar(Nation),
+    /// The nation belongs to the other side
+    WrongSide(Nation),
+    /// Not even one breakdown level can be paid for
+    InsufficientResources(Nation),
+    /// The nation is already at its maximum breakdown
+    MaximumBreakdown(Nation),
+}
+
+impl Display for ReinforceError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ReinforceError::NationNotAtWar(nation) => write!(f, "{} is not at war", nation),
+            ReinforceError::WrongSide(nation) => write!(f, "{} is not on your side", nation),
+            ReinforceError::InsufficientResources(nation) => {
+                write!(f, "Not enough resources to reinforce {}", nation)
+            }
+            ReinforceError::MaximumBreakdown(nation) => {
+                write!(
+                    f,
+                    "{} cannot be reinforced past its maximum breakdown",
+                    nation
+                )
+            }
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum HitsResult {
     Surrenders(Nation),
     Winner(Side),
@@ -84,7 +316,7 @@ pub enum HitsResult {
     NoResult,
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum TechnologyImprovement {
     ImprovedTechnology(TechnologyType, u8),
     FailedTechnology(TechnologyType, u8),
@@ -115,10 +347,46 @@ impl Display for TechnologyImprovement {
     }
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
+/// How long the effects of an event last
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Deactivation {
+    /// Effects end with the turn the event is played
+    EndOfTurn,
+    /// Effects end once the given turn is over
+    AfterTurn(u8),
+    /// Effects last until the end of the game
+    Never,
+}
+
+impl Deactivation {
+    /// Whether the effects are over, checked once a new turn has started
+    pub fn is_over(&self, state: &GameState) -> bool {
+        match self {
+            Deactivation::EndOfTurn => true,
+            Deactivation::AfterTurn(turn) => state.current_turn > *turn,
+            Deactivation::Never => false,
+        }
+    }
+}
+
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct ActiveEvent {
     pub event: Event,
-    pub deactivation: fn(&GameState) -> bool,
+    pub deactivation: Deactivation,
+    /// The logic layered by the event when played, if any
+    #[serde(default)]
+    pub layer: Option<Layer>,
+}
+
+impl ActiveEvent {
+    /// An event whose effects end with the current turn
+    pub fn for_one_turn(event: Event) -> Self {
+        ActiveEvent {
+            event,
+            deactivation: Deactivation::EndOfTurn,
+            layer: None,
+        }
+    }
 }
 
 impl Display for HitsResult {
@@ -142,6 +410,22 @@ pub enum StateChange {
 }
 
 impl StateChange {
+    /// The change reverting this one: resources changes are negated and composite
+    /// changes inverted in reverse order.
+    /// Resources clamped when applied are not restored.
+    pub fn invert(&self) -> StateChange {
+        match self {
+            StateChange::ChangeResources { side, pr } => StateChange::ChangeResources {
+                side: *side,
+                pr: -*pr,
+            },
+            StateChange::MoreChanges(changes) => {
+                StateChange::MoreChanges(changes.iter().rev().map(StateChange::invert).collect())
+            }
+            StateChange::NoChange => StateChange::NoChange,
+        }
+    }
+
     pub(crate) fn allies_loss(&self) -> u8 {
         match self {
             StateChange::ChangeResources { side, pr } if *pr < 0 && *side == Side::Allies => {
@@ -167,31 +451,122 @@ impl StateChange {
     }
 }
 
+/// Outcome of the U-Boot phase: Empires spend `bonus` PR to sink Allies' shipping
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct UBootResult {
+    /// The die roll, modified by the bonus and active events
+    pub roll: u8,
+    pub bonus: u8,
+    /// PR lost by the Allies
+    pub loss: u8,
+}
+
+impl UBootResult {
+    pub fn new(roll: u8, bonus: u8) -> Self {
+        let loss = match roll {
+            1..=4 => 0,
+            5 => 2,
+            _ => 4,
+        };
+        UBootResult { roll, bonus, loss }
+    }
+}
+
+impl From<UBootResult> for StateChange {
+    fn from(result: UBootResult) -> Self {
+        StateChange::MoreChanges(vec![
+            StateChange::ChangeResources {
+                side: Side::Allies,
+                pr: -(result.loss as i8),
+            },
+            StateChange::ChangeResources {
+                side: Side::Empires,
+                pr: -(result.bonus as i8),
+            },
+        ])
+    }
+}
+
+/// Outcome of the Blockade phase: Allies spend `bonus` PR to starve the Empires
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct BlockadeResult {
+    /// The die roll, modified by the bonus
+    pub roll: u8,
+    pub bonus: u8,
+    /// PR gained by the Empires
+    pub gain: u8,
+}
+
+impl BlockadeResult {
+    pub fn new(roll: u8, bonus: u8) -> Self {
+        let gain = match roll {
+            1 => 3,
+            2 => 1,
+            _ => 0,
+        };
+        BlockadeResult { roll, bonus, gain }
+    }
+}
+
+impl From<BlockadeResult> for StateChange {
+    fn from(result: BlockadeResult) -> Self {
+        StateChange::MoreChanges(vec![
+            StateChange::ChangeResources {
+                side: Side::Allies,
+                pr: -(result.bonus as i8),
+            },
+            StateChange::ChangeResources {
+                side: Side::Empires,
+                pr: result.gain as i8,
+            },
+        ])
+    }
+}
+
+/// Dice are serialized as their key and the number of random words drawn so far, so
+/// that a deserialized state rolls the same dice as the original
+mod dice {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha12Rng;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(rng: &ChaCha12Rng, serializer: S) -> Result<S::Ok, S::Error> {
+        (rng.get_seed(), rng.get_word_pos() as u64).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<ChaCha12Rng, D::Error> {
+        let (key, rolls): ([u8; 32], u64) = Deserialize::deserialize(deserializer)?;
+        let mut rng = ChaCha12Rng::from_seed(key);
+        rng.set_word_pos(rolls as u128);
+        Ok(rng)
+    }
+}
+
+/// The game ends when reaching this turn, after the last turn of 1919
+pub const TURN_LIMIT: u8 = 15;
+
 impl GameState {
     pub fn new(seed: u64) -> Self {
         let nations = INITIAL_NATION_STATE.iter().cloned().collect();
         let countries = COUNTRIES.iter().cloned().collect();
-        let initial_state_of_war: HashMap<Side, WarState> = [
-            (
-                Side::Allies,
-                WarState {
-                    resources: 0,
-                    vp: 0,
-                    technologies: Box::new(initial_technologies()),
-                },
-            ),
-            (
-                Side::Empires,
-                WarState {
-                    resources: 0,
-                    vp: 0,
-                    technologies: Box::new(initial_technologies()),
-                },
-            ),
-        ]
-        .iter()
-        .cloned()
-        .collect();
+        let initial_state_of_war: HashMap<Side, WarState> = Side::iter()
+            .map(|side| {
+                (
+                    side,
+                    WarState {
+                        resources: 0,
+                        vp: 0,
+                        technologies: Box::new(initial_technologies()),
+                        collected: Vec::new(),
+                        trench_warfare_since: None,
+                        tech_pr_spent: 0,
+                        tech_levels_gained: 0,
+                    },
+                )
+            })
+            .collect();
 
         GameState {
             current_turn: 1,
@@ -204,45 +579,110 @@ impl GameState {
             countries,
             state_of_war: initial_state_of_war,
             end_game_this_turn: false,
+            events_drawn_this_turn: Vec::new(),
+            config: GameConfig::default(),
+            active_events: Vec::new(),
             seed,
-            rng: StdRng::seed_from_u64(seed),
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            last_roll: 0,
+            sudden_death: None,
+            resigned: None,
+            wasted_pr: HashMap::new(),
             events_pool: ALL_EVENTS
                 .iter()
                 .filter(|e| e.year == 1914)
                 .cloned()
                 .collect(),
+            event_weights: HashMap::new(),
+            forced_events: Vec::new(),
+            surrendered: Vec::new(),
+            attacked_this_turn: Vec::new(),
+            rolled: RolledDice::default(),
         }
     }
 
     pub fn tally_resources(&self, pr_for_side: &Side) -> u8 {
-        self.nations
+        self.resource_breakdown(pr_for_side)
+            .iter()
+            .map(|(_, pr)| pr)
+            .sum()
+    }
+
+    /// PR each nation at war of the given side brings when collecting resources, in a
+    /// stable order: Russia brings twice its operational level and the US a bonus growing
+    /// each turn since they entered the war, see `lafayette_bonus`
+    pub fn resource_breakdown(&self, pr_for_side: &Side) -> Vec<(Nation, u8)> {
+        let mut breakdown: Vec<(Nation, u8)> = self
+            .nations
             .iter()
-            .fold(0, |acc, (nation, status)| match status {
-                NationState::AtWar(breakdown) => match self.countries.get(nation) {
+            .filter_map(|(nation, status)| {
+                let breakdown = status.breakdown()?;
+                match self.countries.get(nation) {
                     Some(Country {
                         side, resources, ..
-                    }) if side == pr_for_side => {
-                        acc + if *nation == Nation::Russia {
-                            operational_level(*breakdown) * 2
-                        } else {
-                            *resources
-                        }
-                    }
-                    _ => acc,
-                },
-                _ => acc,
+                    }) if side == pr_for_side => Some((
+                        *nation,
+                        match nation {
+                            Nation::Russia => operational_level(breakdown) * 2,
+                            Nation::UnitedStates => self.lafayette_bonus(),
+                            _ => *resources,
+                        },
+                    )),
+                    _ => None,
+                }
             })
+            .collect();
+        breakdown.sort();
+        breakdown
     }
 
     pub fn increase_pr(&mut self, side: Side, pr: u8) -> &mut Self {
+        let mobilizing = self
+            .config
+            .mobilization_window
+            .as_ref()
+            .is_some_and(|window| window.contains(&self.current_turn));
         let st = self.state_of_war.get_mut(&side).unwrap();
-        st.resources += pr;
-        if st.resources > 20 {
-            st.resources = 20;
+        st.resources = st.resources.saturating_add(pr);
+        let max_resources = self.config.max_resources;
+        if st.resources > max_resources && !mobilizing {
+            let overflow = st.resources - max_resources;
+            st.resources = max_resources;
+            self.waste_pr(side, overflow);
         }
         self
     }
 
+    /// Record PR which were consumed without full effect this turn: failed technology
+    /// improvements, lost initiative bids, or resources in excess of the maximum,
+    /// see `GameConfig::max_resources`.
+    pub(crate) fn waste_pr(&mut self, side: Side, pr: u8) {
+        let wasted = self.wasted_pr.entry(side).or_insert(0);
+        *wasted = wasted.saturating_add(pr);
+    }
+
+    /// PR wasted by the given side since the beginning of the turn
+    pub fn wasted_pr(&self, side: &Side) -> u8 {
+        self.wasted_pr.get(side).copied().unwrap_or(0)
+    }
+
+    pub(crate) fn reset_wasted_pr(&mut self) {
+        self.wasted_pr.clear();
+    }
+
+    pub(crate) fn record_attack(&mut self, nation: Nation) {
+        self.attacked_this_turn.push(nation);
+    }
+
+    /// Whether the given nation already launched an offensive this turn
+    pub fn has_attacked(&self, nation: &Nation) -> bool {
+        self.attacked_this_turn.contains(nation)
+    }
+
+    pub(crate) fn reset_attacks(&mut self) {
+        self.attacked_this_turn.clear();
+    }
+
     pub fn decrease_pr(&mut self, side: Side, pr: u8) -> &mut Self {
         let st = self.state_of_war.get_mut(&side).unwrap();
         st.resources -= pr;
@@ -254,7 +694,36 @@ impl GameState {
     }
 
     pub fn roll(&mut self) -> u8 {
-        self.rng.gen_range(1..=6)
+        self.last_roll = self.rng.gen_range(1..=self.config.die_faces);
+        self.rolled.push(self.last_roll);
+        self.last_roll
+    }
+
+    /// Keep the dice rolled from now on until the engine logs them, see `rolled_dice`
+    pub(crate) fn log_dice(&mut self) {
+        self.rolled = RolledDice::logged();
+    }
+
+    /// Dice rolled since they were last taken, if they are logged
+    #[cfg(test)]
+    pub(crate) fn rolled_dice(&self) -> &[u8] {
+        self.rolled.dice()
+    }
+
+    pub(crate) fn take_rolled_dice(&mut self) -> Vec<u8> {
+        self.rolled.take()
+    }
+
+    /// Roll a die and apply the given modifier, keeping the result between 1 and
+    /// the number of faces of the die
+    pub fn modified_roll(&mut self, modifier: i8) -> u8 {
+        let die = self.roll() as i16 + modifier as i16;
+        die.clamp(1, self.config.die_faces as i16) as u8
+    }
+
+    /// The value of the last die rolled, or 0 if no die was rolled yet
+    pub(crate) fn last_roll(&self) -> u8 {
+        self.last_roll
     }
 
     pub fn current_year(&self) -> u16 {
@@ -269,17 +738,111 @@ impl GameState {
         }
     }
 
+    /// List nations of the given side which are at war, in a stable order so that
+    /// games replayed from the same seed are identical.
     pub fn all_nations_at_war(&self, initiative: Side) -> Vec<Nation> {
-        self.nations
-            .iter()
-            .filter_map(|(nation, status)| match status {
-                NationState::AtWar(_) => Some(*nation),
-                _ => None,
-            })
-            .filter(|nation| self.countries.get(nation).unwrap().side == initiative)
+        self.nations_of(initiative)
+            .into_iter()
+            .filter(|nation| self.is_at_war(nation))
             .collect()
     }
 
+    /// Replace the built-in countries with those of the configured `countries_path`, if any
+    pub fn load_countries(&mut self) -> Result<(), CountriesError> {
+        let Some(path) = &self.config.countries_path else {
+            return Ok(());
+        };
+        let table = load_countries(path)?;
+        self.countries = table.countries;
+        self.nations = table.nations;
+        self.config.adjacency_overrides.extend(table.adjacency);
+        Ok(())
+    }
+
+    /// The seed the game's dice were initialised with
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Number of random words drawn from the dice since the start of the game
+    pub fn rolls(&self) -> u64 {
+        self.rng.get_word_pos() as u64
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<GameState, String> {
+        serde_json::from_str(json).map_err(|e| e.to_string())
+    }
+
+    /// Put the dice back where they were after `rolls` words drawn from `seed`, e.g.
+    /// when loading a game, so that it continues with the same rolls
+    pub fn restore_dice(&mut self, seed: u64, rolls: u64) {
+        self.seed = seed;
+        self.rng = ChaCha12Rng::seed_from_u64(seed);
+        self.rng.set_word_pos(rolls as u128);
+    }
+
+    /// The side the given nation fights for
+    pub fn side_of(&self, nation: &Nation) -> Side {
+        self.countries.get(nation).unwrap().side
+    }
+
+    /// Side most likely to win initiative with the given bids, without rolling nor
+    /// spending PR, e.g. for robots to plan their bid. This is an expectation, not a
+    /// sample: all die rolls are enumerated, ties being broken as `determine_initiative`
+    /// does, and the side winning more than half of them is returned. When both sides
+    /// are equally likely to win, the side with the default initiative for the turn wins.
+    #[cfg(test)]
+    pub fn would_win_initiative(&self, allies_bid: u8, empires_bid: u8) -> Side {
+        let min_bid = self.config.min_initiative_bid;
+        let default = crate::DEFAULT_INITIATIVE[self.current_turn as usize - 1];
+        let (allies_bid, empires_bid) = match (allies_bid >= min_bid, empires_bid >= min_bid) {
+            (true, true) => (allies_bid as u16, empires_bid as u16),
+            (true, false) => return Side::Allies,
+            (false, true) => return Side::Empires,
+            (false, false) => (0, 0),
+        };
+        let faces = self.config.die_faces as u16;
+        let mut allies_wins = 0;
+        for allies_roll in 1..=faces {
+            for empires_roll in 1..=faces {
+                let allies = allies_bid + allies_roll;
+                let empires = empires_bid + empires_roll;
+                if allies > empires || (allies == empires && default == Side::Allies) {
+                    allies_wins += 1;
+                }
+            }
+        }
+        match (2 * allies_wins).cmp(&(faces * faces)) {
+            std::cmp::Ordering::Greater => Side::Allies,
+            std::cmp::Ordering::Less => Side::Empires,
+            std::cmp::Ordering::Equal => default,
+        }
+    }
+
+    /// List all nations belonging to the given side, whatever their status, in a stable order
+    pub fn nations_of(&self, side: Side) -> Vec<Nation> {
+        let mut nations: Vec<Nation> = self
+            .countries
+            .iter()
+            .filter(|(_, country)| country.side == side)
+            .map(|(nation, _)| *nation)
+            .collect();
+        nations.sort();
+        nations
+    }
+
+    /// List nations of the given side which can launch an offensive, in a stable order.
+    /// Nations too exhausted, according to the configured `exhaustion_threshold`, are left out.
+    pub fn offensive_sources(&self, side: Side) -> Vec<Nation> {
+        let mut nations = self.all_nations_at_war(side);
+        nations.retain(|nation| self.breakdown_level(nation) > self.config.exhaustion_threshold);
+        nations
+    }
+
     pub fn artillery_bonus(&self, initiative: &Side) -> u8 {
         self.state_of_war
             .get(initiative)
@@ -304,31 +867,122 @@ impl GameState {
             .defense
     }
 
+    /// Technology levels gained per PR spent on technologies by the given side,
+    /// 0 if it did not spend anything
+    pub fn tech_efficiency(&self, side: &Side) -> f64 {
+        let war_state = self.state_of_war.get(side).unwrap();
+        if war_state.tech_pr_spent == 0 {
+            return 0.0;
+        }
+        war_state.tech_levels_gained as f64 / war_state.tech_pr_spent as f64
+    }
+
+    /// Standing defense bonus of the given side from trench warfare, which applies as
+    /// soon as it is reached
+    pub fn trench_warfare_bonus(&self, side: &Side) -> u8 {
+        match self.state_of_war.get(side).unwrap().trench_warfare_since {
+            Some(_) => TRENCH_WARFARE_BONUS,
+            None => 0,
+        }
+    }
+
     pub fn surrenders(&mut self, to: &Nation) -> HitsResult {
-        let side = self.countries.get(to).unwrap().side.other();
+        let side = self.side_of(to).other();
         self.state_of_war.get_mut(&side).unwrap().vp += self.countries.get(to).unwrap().vp;
-        self.nations.insert(*to, NationState::AtPeace);
-        if self.roll() < self.state_of_war.get(&side).unwrap().vp {
+        self.nations.insert(*to, NationState::Surrendered);
+        self.surrendered.push((*to, side));
+        if !self.config.sudden_death {
+            return HitsResult::Surrenders(*to);
+        }
+        let roll = self.roll();
+        let vp = self.state_of_war.get(&side).unwrap().vp;
+        if vp >= self.config.victory_threshold && roll < vp && self.resigned.is_none() {
             self.winner = Some(side);
+            self.sudden_death = Some((*to, roll, vp));
             HitsResult::Winner(side)
         } else {
             HitsResult::Surrenders(*to)
         }
     }
 
+    /// Nations which surrendered paired with the side they surrendered to, leaving out
+    /// nations at peace because they never entered the war
+    #[cfg(test)]
+    pub fn surrendered(&self) -> Vec<(Nation, Side)> {
+        self.surrendered.clone()
+    }
+
     pub(crate) fn draw_events(&mut self) -> Vec<Event> {
+        if !self.forced_events.is_empty() {
+            let events: Vec<Event> = std::mem::take(&mut self.forced_events)
+                .into_iter()
+                .map(|id| self.take_event(id))
+                .collect();
+            self.events_drawn_this_turn = events.clone();
+            return events;
+        }
         let mut events = Vec::new();
-        for _ in 0..3 {
+        let mut requeued = Vec::new();
+        while events.len() < 3 {
             if self.events_pool.is_empty() {
                 break;
             }
-            let idx = self.rng.gen_range(0..self.events_pool.len());
+            let idx = pick_event(&mut self.rng, &self.events_pool, &self.event_weights);
             let event = self.events_pool.remove(idx);
-            events.push(event);
+            if event.applies_to(self) {
+                events.push(event);
+            } else {
+                requeued.push(event);
+            }
         }
+        self.events_pool.extend(requeued);
+        self.events_drawn_this_turn = events.clone();
         events
     }
 
+    /// Ids of the next `n` events `draw_events` would yield, forced events first, without
+    /// disturbing the dice
+    pub(crate) fn peek_next_draw(&self, n: usize) -> Vec<u8> {
+        let mut rng = self.rng.clone();
+        let mut pool = self.events_pool.clone();
+        pool.retain(|event| !self.forced_events.contains(&event.event_id));
+        let mut events: Vec<u8> = self.forced_events.iter().take(n).copied().collect();
+        while events.len() < n && !pool.is_empty() {
+            let idx = pick_event(&mut rng, &pool, &self.event_weights);
+            let event = pool.remove(idx);
+            if event.applies_to(self) {
+                events.push(event.event_id);
+            }
+        }
+        events
+    }
+
+    /// Change how likely the given event is to be drawn, whether it is already in the
+    /// pool or enters it in a later year
+    #[cfg(test)]
+    pub(crate) fn set_event_weight(&mut self, event_id: u8, weight: u8) {
+        self.event_weights.insert(event_id, weight);
+    }
+
+    /// Make the next draw yield exactly the given events, in order, whatever the dice
+    #[cfg(test)]
+    pub(crate) fn force_next_events(&mut self, event_ids: Vec<u8>) {
+        self.forced_events = event_ids;
+    }
+
+    /// Remove the event with the given id from the pool, or fetch it from all events
+    /// if it is not in the pool
+    fn take_event(&mut self, event_id: u8) -> Event {
+        match self.events_pool.iter().position(|e| e.event_id == event_id) {
+            Some(idx) => self.events_pool.remove(idx),
+            None => ALL_EVENTS
+                .iter()
+                .find(|e| e.event_id == event_id)
+                .cloned()
+                .unwrap_or_else(|| panic!("Unknown event {}", event_id)),
+        }
+    }
+
     pub(crate) fn can_draw_event(&mut self, event: &Event) -> bool {
         self.events_pool.contains(event)
     }
@@ -337,12 +991,59 @@ impl GameState {
         self.events_pool.push(event)
     }
 
+    /// List events currently in effect
+    pub fn active_events(&self) -> Vec<&Event> {
+        self.active_events
+            .iter()
+            .map(|active| &active.event)
+            .collect()
+    }
+
+    /// Whether the event with the given id is in effect
+    pub fn is_event_active(&self, event_id: u8) -> bool {
+        self.active_events
+            .iter()
+            .any(|active| active.event.event_id == event_id)
+    }
+
+    /// Events whose effects are still ongoing, in the order they were played
+    pub(crate) fn activations(&self) -> &[ActiveEvent] {
+        &self.active_events
+    }
+
+    pub(crate) fn activate(&mut self, event: ActiveEvent) {
+        self.active_events.push(event);
+    }
+
+    /// Remove events whose effects are over from the active events
+    pub(crate) fn sweep_active_events(&mut self) {
+        let active_events = std::mem::take(&mut self.active_events);
+        self.active_events = active_events
+            .into_iter()
+            .filter(|active| !active.deactivation.is_over(self))
+            .collect();
+    }
+
     pub(crate) fn operational_level(&self, nation: &Nation) -> u8 {
-        self.nations.get(nation).unwrap().operational_level()
+        self.nations
+            .get(nation)
+            .map_or(0, |status| status.operational_level())
     }
 
     pub(crate) fn breakdown_level(&self, nation: &Nation) -> u8 {
-        self.nations.get(nation).unwrap().breakdown_level()
+        self.nations
+            .get(nation)
+            .map_or(0, |status| status.breakdown_level())
+    }
+
+    /// Start the game on the given turn, with the events of the years before it put in
+    /// the pool as if no event had been drawn yet
+    pub(crate) fn start_on_turn(&mut self, turn: u8) {
+        let first_year = self.current_year();
+        self.current_turn = turn;
+        for year in first_year..self.current_year() {
+            self.new_year(year, year + 1);
+        }
     }
 
     pub fn new_year(&mut self, current_turn_year: u16, next_year: u16) {
@@ -359,10 +1060,13 @@ impl GameState {
 
     /// List technologies available to the given side in the current turn
     pub(crate) fn available_technologies(&self, side: &Side) -> Vec<Technology> {
-        match side {
-            Side::Allies => self.available_technologies_for(&ALLIES_TECHNOLOGIES),
-            Side::Empires => self.available_technologies_for(&EMPIRE_TECHNOLOGIES),
-        }
+        self.available_technologies_for(technologies_track(side))
+    }
+
+    /// Tell whether the given side has reached the last level of a technology category
+    pub fn is_tech_maxed(&self, side: &Side, t: TechnologyType) -> bool {
+        let level = self.state_of_war.get(side).unwrap().technologies.value(&t);
+        level >= tech_limit(technologies_track(side), &t)
     }
 
     fn available_technologies_for(
@@ -386,73 +1090,299 @@ impl GameState {
             .collect()
     }
 
+    /// The weakest nation at war of the given side, e.g. to hit it.
+    /// Ties are broken deterministically: lowest breakdown first, then the canonical
+    /// order of nations, so that robots and events play the same given the same seed.
+    #[cfg(test)]
+    pub fn weakest_nation(&self, side: Side) -> Option<Nation> {
+        self.all_nations_at_war(side)
+            .into_iter()
+            .min_by_key(|nation| (self.breakdown_level(nation), *nation))
+    }
+
     /// List enemy nations neighbouring the given nation
-    pub(crate) fn neighbours(&self, source: &Nation) -> Vec<&Nation> {
-        let mut neighbours = Vec::new();
-        for n in Nation::values() {
-            if source.adjacent_to(n) && self.is_at_war(n) {
-                neighbours.push(n);
-            }
+    pub(crate) fn neighbours(&self, source: &Nation) -> Vec<&'static Nation> {
+        if self.config.adjacency_overrides.is_empty() {
+            return source
+                .adjacent_nations()
+                .iter()
+                .filter(|n| self.is_at_war(n))
+                .collect();
+        }
+        Nation::values()
+            .filter(|n| self.adjacent(source, n) && self.is_at_war(n))
+            .collect()
+    }
+
+    /// Whether `from` can attack `to`, taking into account the game's adjacency overrides
+    pub fn adjacent(&self, from: &Nation, to: &Nation) -> bool {
+        let adjacency = self
+            .config
+            .adjacency_overrides
+            .iter()
+            .find(|o| o.between == (*from, *to) || o.between == (*to, *from))
+            .map(|o| o.adjacency);
+        match adjacency {
+            None => from.adjacent_to(to),
+            Some(Adjacency::Never) => false,
+            Some(Adjacency::Always) => true,
+            Some(Adjacency::WithTechnology(tech, level)) => self
+                .state_of_war
+                .get(&self.side_of(from))
+                .is_some_and(|war_state| war_state.technologies.value(&tech) >= level),
         }
-        neighbours
     }
 
     /// Evaluate the value of the given state, yielding a number -1 and +1 where
     /// positive values are better for the Allies and negative values are better for the Empires.
     pub fn valuation(&self) -> f64 {
+        self.valuation_components().valuation
+    }
+
+    /// Details each side's weighted terms contributing to `valuation`, e.g. to tune weights
+    pub fn valuation_components(&self) -> ValuationBreakdown {
         let allies = self.state_of_war.get(&Side::Allies).unwrap();
         let empires = self.state_of_war.get(&Side::Empires).unwrap();
         let allies_resources = allies.resources as f64;
         let empires_resources = empires.resources as f64;
         let allies_technologies = allies.technologies.values().into_iter().sum::<u8>() as f64;
         let empires_technologies = empires.technologies.values().into_iter().sum::<u8>() as f64;
-        let (allies_breakdowns, empires_breakdowns): (f64, f64) = self.nations.iter().fold(
-            (0.0, 0.0),
-            |(acc_a, acc_e), (nation, status)| match status {
-                NationState::AtWar(level) => {
-                    if nation.is_allies() {
-                        (acc_a + *level as f64, acc_e)
-                    } else {
-                        (acc_a, acc_e + *level as f64)
-                    }
-                }
-                _ => (acc_a, acc_e),
-            },
-        );
+        let (allies_breakdowns, empires_breakdowns): (f64, f64) =
+            self.nations
+                .iter()
+                .fold(
+                    (0.0, 0.0),
+                    |(acc_a, acc_e), (nation, status)| match status.breakdown() {
+                        Some(level) if self.side_of(nation) == Side::Allies => {
+                            (acc_a + level as f64, acc_e)
+                        }
+                        Some(level) => (acc_a, acc_e + level as f64),
+                        None => (acc_a, acc_e),
+                    },
+                );
         let allies_victory_points = allies.vp as f64;
         let empires_victory_points = empires.vp as f64;
-        let allies_total = allies_resources
-            + allies_technologies * 3.0
-            + allies_breakdowns * 5.0
-            + allies_victory_points * 4.0;
-        let empires_total = empires_resources
-            + empires_technologies * 3.0
-            + empires_breakdowns * 5.0
-            + empires_victory_points * 4.0;
-        (allies_total - empires_total) / (allies_total + empires_total)
+        let allies_terms = ValuationTerms {
+            resources: allies_resources,
+            technologies: allies_technologies * 3.0,
+            breakdowns: allies_breakdowns * 5.0,
+            victory_points: allies_victory_points * 4.0,
+        };
+        let empires_terms = ValuationTerms {
+            resources: empires_resources,
+            technologies: empires_technologies * 3.0,
+            breakdowns: empires_breakdowns * 5.0,
+            victory_points: empires_victory_points * 4.0,
+        };
+        let allies_total = allies_terms.total();
+        let empires_total = empires_terms.total();
+        let valuation = if allies_total + empires_total == 0.0 {
+            0.0
+        } else {
+            (allies_total - empires_total) / (allies_total + empires_total)
+        };
+        ValuationBreakdown {
+            allies: allies_terms,
+            empires: empires_terms,
+            valuation,
+        }
     }
 
-    fn is_at_war(&self, n: &Nation) -> bool {
-        matches!(self.nations.get(n), Some(NationState::AtWar(_)))
+    /// The given nation enters the war at the given breakdown level, unless it is
+    /// already at war or it surrendered
+    pub(crate) fn enter_war(&mut self, nation: Nation, breakdown: u8) {
+        if self.can_enter_war(&nation) {
+            self.nations.insert(nation, NationState::AtWar(breakdown));
+        }
+    }
+
+    /// Whether the given nation may still enter the war
+    pub fn can_enter_war(&self, nation: &Nation) -> bool {
+        self.nations
+            .get(nation)
+            .is_none_or(NationState::can_enter_war)
+    }
+
+    /// The US enter the war on the Allies' side, unless they already did. They border no
+    /// enemy and have no breakdown, but bring a PR bonus growing each turn, see
+    /// `lafayette_bonus`.
+    pub(crate) fn us_enters_war(&mut self) {
+        if self.lafayette.is_some() {
+            return;
+        }
+        self.lafayette = Some(0);
+        if let Some(country) = self.countries.get(&Nation::UnitedStates) {
+            self.nations.insert(
+                Nation::UnitedStates,
+                NationState::AtWar(country.max_breakdown),
+            );
+        }
+    }
+
+    pub fn us_at_war(&self) -> bool {
+        self.is_at_war(&Nation::UnitedStates)
+    }
+
+    /// PR the US bring to the Allies while at war: one more for each collection since
+    /// they entered the war
+    pub fn lafayette_bonus(&self) -> u8 {
+        self.lafayette.unwrap_or(0)
+    }
+
+    /// Whether hits the given side takes can be applied to the nation: one of the
+    /// side's nations at war with some breakdown left, which leaves out the US
+    pub fn can_take_hit(&self, side: Side, nation: &Nation) -> bool {
+        self.countries.get(nation).is_some_and(|c| c.side == side)
+            && self.breakdown_level(nation) > 0
+    }
+
+    pub(crate) fn is_at_war(&self, n: &Nation) -> bool {
+        self.nations.get(n).is_some_and(NationState::is_at_war)
     }
 
     pub(crate) fn game_ends(&self) -> bool {
-        self.end_game_this_turn || self.current_turn >= 15 || self.winner.is_some()
+        self.end_game_this_turn || self.reached_turn_limit() || self.winner.is_some()
+    }
+
+    /// Whether the game went past its last turn, in 1919 or earlier when capped by
+    /// `GameConfig::max_turns`. There's no year for the turn after 1919 so
+    /// `current_year` must not be called once it's reached.
+    pub fn reached_turn_limit(&self) -> bool {
+        self.current_turn > self.last_turn()
+    }
+
+    /// Number of turns played, the turn the game was decided counting as played
+    pub fn turns_played(&self) -> u8 {
+        if self.reached_turn_limit() {
+            self.last_turn()
+        } else {
+            self.current_turn
+        }
+    }
+
+    /// The last turn of the game, which cannot go past 1919 whatever its configuration
+    pub fn last_turn(&self) -> u8 {
+        self.config.max_turns.min(TURN_LIMIT - 1)
     }
 
     pub(crate) fn winner(&self) -> Side {
         match self.winner {
             Some(side) => side,
-            None => {
-                let empires_pv = self.state_of_war.get(&Side::Empires).unwrap().vp;
-                let allies_pv = self.state_of_war.get(&Side::Allies).unwrap().vp;
-                if empires_pv >= allies_pv {
-                    Side::Empires
-                } else {
-                    Side::Allies
-                }
+            None => self.final_scoring(),
+        }
+    }
+
+    /// Result of the game: without a decision, equal VP make a draw whatever the
+    /// resources, unlike `GameState::winner` which breaks ties
+    pub fn result(&self) -> GameResult {
+        let allies = self.state_of_war.get(&Side::Allies).unwrap();
+        let empires = self.state_of_war.get(&Side::Empires).unwrap();
+        let tied = allies.vp == empires.vp;
+        match self.winner {
+            None if tied => GameResult::Draw,
+            _ => match self.winner() {
+                Side::Allies => GameResult::AlliesWin,
+                Side::Empires => GameResult::EmpiresWin,
+            },
+        }
+    }
+
+    /// Winner of a game which ended without a decision: the side with most VP wins, then
+    /// the side with most resources, the Empires winning ties
+    fn final_scoring(&self) -> Side {
+        let allies = self.state_of_war.get(&Side::Allies).unwrap();
+        let empires = self.state_of_war.get(&Side::Empires).unwrap();
+        if (allies.vp, allies.resources) > (empires.vp, empires.resources) {
+            Side::Allies
+        } else {
+            Side::Empires
+        }
+    }
+
+    /// The given side concedes, the other side wins the game
+    pub(crate) fn resign(&mut self, side: Side) {
+        if self.resigned.is_none() {
+            self.resigned = Some(side);
+            self.winner = Some(side.other());
+        }
+    }
+
+    /// Describe why the winning side won the game
+    pub fn explain_winner(&self) -> String {
+        let allies_vp = self.state_of_war.get(&Side::Allies).unwrap().vp;
+        let empires_vp = self.state_of_war.get(&Side::Empires).unwrap().vp;
+        if let Some(side) = self.resigned {
+            return format!("{} win: {} resigned", side.other(), side);
+        }
+        match (self.winner, self.sudden_death) {
+            (Some(side), Some((nation, roll, vp))) => format!(
+                "{} win: {} surrendered and die roll {} < VP {}",
+                side, nation, roll, vp
+            ),
+            (Some(side), None) => format!("{} win", side),
+            (None, _) if allies_vp == empires_vp => {
+                format!("Draw at turn limit (VP {} vs {})", allies_vp, empires_vp)
+            }
+            (None, _) => {
+                let side = self.winner();
+                let (winner_vp, loser_vp) = match side {
+                    Side::Allies => (allies_vp, empires_vp),
+                    Side::Empires => (empires_vp, allies_vp),
+                };
+                format!("{} win by VP ({} vs {})", side, winner_vp, loser_vp)
+            }
+        }
+    }
+
+    pub fn summary(&self) -> GameSummary {
+        let allies = self.state_of_war.get(&Side::Allies).unwrap();
+        let empires = self.state_of_war.get(&Side::Empires).unwrap();
+        GameSummary {
+            winner: self.winner(),
+            turns: self.current_turn,
+            allies_vp: allies.vp,
+            empires_vp: empires.vp,
+            allies_resources: allies.resources,
+            empires_resources: empires.resources,
+            allies_collected: allies.collected.clone(),
+            empires_collected: empires.collected.clone(),
+            config: self.config.clone(),
+        }
+    }
+
+    /// A stable hash of the game's state, useful to check games played from the same seed
+    /// end up identical.
+    /// This uses FNV-1a over a canonical description of the state so that values do not
+    /// depend on the iteration order of maps nor on the standard library's hasher.
+    pub fn fingerprint(&self) -> u64 {
+        let mut description = format!(
+            "{}|{:?}|{:?}|{:?}|{}|{:?}|{}",
+            self.current_turn,
+            self.phase,
+            self.initiative,
+            self.winner,
+            self.russian_revolution,
+            self.lafayette,
+            self.end_game_this_turn
+        );
+        for nation in Nation::values() {
+            if let Some(status) = self.nations.get(nation) {
+                description.push_str(&format!("|{:?}:{:?}", nation, status));
             }
         }
+        for side in Side::iter() {
+            let war_state = self.state_of_war.get(&side).unwrap();
+            description.push_str(&format!(
+                "|{:?}:{}:{}:{:?}",
+                side,
+                war_state.resources,
+                war_state.vp,
+                war_state.technologies.values()
+            ));
+        }
+        description.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
     }
 
     pub(crate) fn apply_change(&mut self, change: &StateChange) -> &mut Self {
@@ -474,10 +1404,42 @@ impl GameState {
         self
     }
 
+    /// The state resulting from applying the given change, leaving this state untouched,
+    /// e.g. to show players the effect of spending resources before they commit
+    #[cfg(test)]
+    pub fn preview_change(&self, change: &StateChange) -> GameState {
+        let mut preview = self.clone();
+        preview.apply_change(change);
+        preview
+    }
+
     pub(crate) fn set_phase(&mut self, phase: Phase) {
         self.phase = phase;
     }
 
+    /// The phase following the current one in the sequence of play of a turn
+    #[cfg(test)]
+    pub(crate) fn next_phase(&self) -> Phase {
+        match self.phase {
+            Phase::Initiative(Side::Allies) => Phase::Initiative(Side::Empires),
+            Phase::Initiative(Side::Empires) => Phase::DrawEvents,
+            Phase::DrawEvents => Phase::CollectResources,
+            Phase::CollectResources => Phase::ImproveTechnologies(self.initiative),
+            Phase::ImproveTechnologies(side) => Phase::LaunchOffensives(side),
+            Phase::LaunchOffensives(side) => Phase::Reinforcements(side),
+            Phase::Reinforcements(Side::Allies) => Phase::Blockade,
+            Phase::Reinforcements(Side::Empires) => Phase::UBoot,
+            Phase::UBoot if self.initiative == Side::Empires => {
+                Phase::ImproveTechnologies(Side::Allies)
+            }
+            Phase::Blockade if self.initiative == Side::Allies => {
+                Phase::ImproveTechnologies(Side::Empires)
+            }
+            Phase::UBoot | Phase::Blockade => Phase::NewTurn,
+            Phase::NewTurn => Phase::Initiative(Side::Allies),
+        }
+    }
+
     pub(crate) fn side_to_play(&self) -> Option<Side> {
         match self.phase {
             Phase::Initiative(side) => Some(side),
@@ -493,11 +1455,86 @@ impl GameState {
     }
 }
 
+/// Index of an event of the given non empty pool, picked with a probability
+/// proportional to its weight, `weights` overriding the events' own. When all weights
+/// are 1, the dice are used as for a uniform pick.
+fn pick_event(rng: &mut ChaCha12Rng, pool: &[Event], weights: &HashMap<u8, u8>) -> usize {
+    let weight_of = |event: &Event| {
+        weights
+            .get(&event.event_id)
+            .copied()
+            .unwrap_or(event.weight) as usize
+    };
+    let total: usize = pool.iter().map(weight_of).sum();
+    if total == 0 {
+        return rng.gen_range(0..pool.len());
+    }
+    let mut pick = rng.gen_range(0..total);
+    pool.iter()
+        .position(|event| {
+            let weight = weight_of(event);
+            if pick < weight {
+                true
+            } else {
+                pick -= weight;
+                false
+            }
+        })
+        .unwrap()
+}
+
+impl GameState {
+    /// Draw the nations of both sides side by side, with their breakdown and maximum
+    /// breakdown when at war: `*` marks nations at war, `-` nations at peace and `x`
+    /// nations which surrendered
+    pub fn render_ascii(&self) -> String {
+        let border = format!("+{}+{}+\n", "-".repeat(26), "-".repeat(26));
+        let columns = Side::all().map(|side| {
+            self.nations_of(side)
+                .iter()
+                .map(|nation| self.render_nation(nation))
+                .collect::<Vec<String>>()
+        });
+        let rows = columns.iter().map(Vec::len).max().unwrap_or(0);
+        let mut map = border.clone();
+        map.push_str(&format!(
+            "| {:<24} | {:<24} |\n",
+            Side::Allies.to_string(),
+            Side::Empires.to_string()
+        ));
+        map.push_str(&border);
+        for row in 0..rows {
+            let [allies, empires] = columns
+                .each_ref()
+                .map(|column| column.get(row).map_or("", String::as_str));
+            map.push_str(&format!("| {:<24} | {:<24} |\n", allies, empires));
+        }
+        map.push_str(&border);
+        map
+    }
+
+    fn render_nation(&self, nation: &Nation) -> String {
+        let (marker, level) = match self.nations.get(nation) {
+            Some(NationState::AtWar(breakdown)) => {
+                let maximum = self.countries.get(nation).map_or(0, |c| c.max_breakdown);
+                ('*', format!("{}/{}", breakdown, maximum))
+            }
+            Some(NationState::Surrendered) => ('x', "out".to_string()),
+            Some(NationState::AtPeace) | None => ('-', "peace".to_string()),
+        };
+        format!("{} {:<16}{:>6}", marker, nation.to_string(), level)
+    }
+}
+
 impl Display for GameState {
     /// TODO: take care of writeln! result
     #[allow(unused_must_use)]
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        writeln!(f, "Turn: {} ({})", self.current_turn, self.current_year());
+        if self.reached_turn_limit() {
+            writeln!(f, "Turn: {} (end of war)", self.current_turn);
+        } else {
+            writeln!(f, "Turn: {} ({})", self.current_turn, self.current_year());
+        }
         writeln!(f, "Initiative: {}", self.initiative);
         if let Some(winner) = self.winner {
             writeln!(f, "Winner: {}", winner);
@@ -505,16 +1542,32 @@ impl Display for GameState {
             writeln!(f, "Game value: {}", self.valuation());
         }
         writeln!(f, "Russian Revolution: {}", self.russian_revolution);
+        if !self.active_events.is_empty() {
+            writeln!(f, "Active events:");
+            for event in self.active_events() {
+                writeln!(f, "\t{}", event.title);
+            }
+        }
         writeln!(f, "Breakdown:");
-        for (nation, status) in self.nations.iter() {
+        let mut nations: Vec<_> = self.nations.iter().collect();
+        nations.sort_by_key(|(nation, _)| **nation);
+        for (nation, status) in nations {
             writeln!(f, "\t{}: {}", nation, status);
         }
         writeln!(f, "State of War:");
-        for (side, war_state) in self.state_of_war.iter() {
+        for side in Side::iter() {
+            let Some(war_state) = self.state_of_war.get(&side) else {
+                continue;
+            };
             writeln!(f, "\t{}:", side);
             writeln!(f, "\t\tResources: {}", war_state.resources);
             writeln!(f, "\t\tVP: {}", war_state.vp);
             writeln!(f, "\t\tTechnologies: {}", war_state.technologies);
+            writeln!(
+                f,
+                "\t\tTechnology efficiency: {:.2} levels/PR",
+                self.tech_efficiency(&side)
+            );
         }
         Ok(())
     }
@@ -524,11 +1577,61 @@ impl Display for GameState {
 mod game_state_tests {
 
     use super::HitsResult::*;
+    use super::{ActiveEvent, BlockadeResult, Deactivation, GameResult, UBootResult};
     use crate::{
-        fixtures::EngineBuilder, GameState, Nation::*, NationState::*, Side::*, StateChange,
+        event::ALL_EVENTS, fixtures::EngineBuilder, GameConfig, GameState, InputKind, Nation,
+        Nation::*, NationState::*, Phase, Side::*, StateChange, TechnologyType::*,
         ZERO_TECHNOLOGIES,
     };
 
+    #[test]
+    fn uboot_result_derives_allies_loss_and_empires_spending() {
+        let result = UBootResult::new(5, 2);
+
+        assert_eq!(5, result.roll);
+        assert_eq!(2, result.bonus);
+        assert_eq!(2, result.loss);
+        assert_eq!(
+            StateChange::MoreChanges(vec![
+                StateChange::ChangeResources {
+                    side: Allies,
+                    pr: -2
+                },
+                StateChange::ChangeResources {
+                    side: Empires,
+                    pr: -2
+                },
+            ]),
+            StateChange::from(result)
+        );
+        assert_eq!(0, UBootResult::new(4, 0).loss);
+        assert_eq!(4, UBootResult::new(6, 0).loss);
+    }
+
+    #[test]
+    fn blockade_result_derives_empires_gain_and_allies_spending() {
+        let result = BlockadeResult::new(1, 1);
+
+        assert_eq!(1, result.roll);
+        assert_eq!(1, result.bonus);
+        assert_eq!(3, result.gain);
+        assert_eq!(
+            StateChange::MoreChanges(vec![
+                StateChange::ChangeResources {
+                    side: Allies,
+                    pr: -1
+                },
+                StateChange::ChangeResources {
+                    side: Empires,
+                    pr: 3
+                },
+            ]),
+            StateChange::from(result)
+        );
+        assert_eq!(1, BlockadeResult::new(2, 0).gain);
+        assert_eq!(0, BlockadeResult::new(3, 0).gain);
+    }
+
     #[test]
     fn nation_surrenders_when_brought_to_0_then_increase_vp_of_other_side() {
         let mut engine = EngineBuilder::new(14) // die roll = 6
@@ -537,8 +1640,29 @@ mod game_state_tests {
         let result = engine.apply_hits(&France, 4);
 
         assert_eq!(Surrenders(France), result);
-        assert_eq!(6, engine.state.state_of_war.get(&Empires).unwrap().vp);
-        assert_eq!(AtPeace, engine.state.nations.get(&France).unwrap().clone());
+        assert_eq!(6, engine.vp_for(Empires));
+        assert_eq!(
+            Surrendered,
+            engine.state.nations.get(&France).unwrap().clone()
+        );
+    }
+
+    #[test]
+    fn surrendered_nation_is_neither_at_war_nor_can_enter_it_again() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_nation(France, AtWar(4))
+            .build();
+        engine.apply_hits(&France, 4);
+
+        engine.state.enter_war(France, 4);
+
+        assert!(!engine.state.all_nations_at_war(Allies).contains(&France));
+        assert!(!engine.state.can_enter_war(&France));
+        assert!(engine.state.can_enter_war(&Italy));
+        assert_eq!(
+            Surrendered,
+            engine.state.nations.get(&France).unwrap().clone()
+        );
     }
 
     #[test]
@@ -590,6 +1714,230 @@ mod game_state_tests {
         assert_eq!(Allies, engine.state.winner());
     }
 
+    #[test]
+    fn ascii_map_shows_every_nation_with_its_breakdown() {
+        let engine = EngineBuilder::new(14)
+            .with_nation(France, AtWar(5))
+            .with_nation(Italy, AtPeace)
+            .with_nation(Serbia, Surrendered)
+            .with_nation(Germany, AtWar(8))
+            .build();
+
+        let map = engine.state.render_ascii();
+
+        for nation in engine.state.nations.keys() {
+            assert!(map.contains(&nation.to_string()), "{}\n{}", nation, map);
+        }
+        assert!(map.contains("| * France             5/7 |"), "{}", map);
+        assert!(map.contains("| - Italy            peace |"), "{}", map);
+        assert!(map.contains("| x Serbia             out |"), "{}", map);
+        assert!(map.contains("| * Germany            8/8 |"), "{}", map);
+    }
+
+    #[test]
+    fn game_without_decision_is_a_draw_given_equal_vp_and_resources() {
+        let engine = EngineBuilder::new(14).on_turn(15).build();
+
+        assert_eq!(GameResult::Draw, engine.state.result());
+        assert_eq!(Empires, engine.state.winner());
+    }
+
+    #[test]
+    fn game_is_a_draw_given_equal_vp_whatever_the_resources() {
+        let engine = EngineBuilder::new(14)
+            .with_resources(Allies, 1)
+            .on_turn(15)
+            .build();
+
+        assert_eq!(GameResult::Draw, engine.state.result());
+    }
+
+    #[test]
+    fn explains_winner_by_victory_points() {
+        let mut engine = EngineBuilder::new(14) // die roll = 6
+            .with_nation(France, AtWar(4))
+            .on_turn(15)
+            .build();
+        engine.apply_hits(&France, 4);
+
+        assert_eq!("Empires win by VP (6 vs 0)", engine.state.explain_winner());
+    }
+
+    #[test]
+    fn explains_winner_by_sudden_death() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_nation(France, AtWar(4))
+            .build();
+        engine.apply_hits(&France, 4);
+
+        assert_eq!(
+            "Empires win: France surrendered and die roll 2 < VP 6",
+            engine.state.explain_winner()
+        );
+    }
+
+    #[test]
+    fn surrender_does_not_win_before_reaching_victory_threshold() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_nation(France, AtWar(4))
+            .with_victory_threshold(7)
+            .build();
+
+        assert_eq!(Surrenders(France), engine.apply_hits(&France, 4));
+        assert_eq!(None, engine.state.winner);
+        assert!(!engine.game_ends());
+    }
+
+    #[test]
+    fn surrender_wins_once_victory_threshold_is_reached() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_nation(France, AtWar(4))
+            .with_victory_threshold(6)
+            .build();
+
+        assert_eq!(Winner(Empires), engine.apply_hits(&France, 4));
+    }
+
+    #[test]
+    fn surrender_does_not_end_the_game_without_sudden_death() {
+        let mut engine = EngineBuilder::new(11) // die roll = 2
+            .with_nation(France, AtWar(4))
+            .with_sudden_death(false)
+            .build();
+
+        assert_eq!(Surrenders(France), engine.apply_hits(&France, 4));
+        assert_eq!(6, engine.vp_for(Empires));
+        assert_eq!(Surrendered, engine.nation_state(France));
+        assert_eq!(None, engine.state.winner);
+        assert!(!engine.game_ends());
+    }
+
+    #[test]
+    fn explains_draw_at_turn_limit() {
+        let engine = EngineBuilder::new(11).on_turn(15).build();
+
+        assert_eq!(
+            "Draw at turn limit (VP 0 vs 0)",
+            engine.state.explain_winner()
+        );
+    }
+
+    #[test]
+    fn surrendered_nations_are_listed_with_their_captor() {
+        let mut engine = EngineBuilder::new(14)
+            .with_nation(France, AtWar(2))
+            .with_nation(Italy, AtPeace)
+            .build();
+
+        engine.apply_hits(&France, 2);
+
+        assert_eq!(vec![(France, Empires)], engine.state.surrendered());
+    }
+
+    #[test]
+    fn previewing_a_change_leaves_state_untouched() {
+        let engine = EngineBuilder::new(11)
+            .with_resources(Allies, 7)
+            .with_resources(Empires, 3)
+            .build();
+        let change = StateChange::MoreChanges(vec![
+            StateChange::ChangeResources {
+                side: Allies,
+                pr: -2,
+            },
+            StateChange::ChangeResources {
+                side: Empires,
+                pr: 4,
+            },
+        ]);
+
+        let preview = engine.state.preview_change(&change);
+
+        assert_eq!(7, engine.state.resources_for(&Allies));
+        assert_eq!(3, engine.state.resources_for(&Empires));
+        assert_eq!(5, preview.resources_for(&Allies));
+        assert_eq!(7, preview.resources_for(&Empires));
+    }
+
+    #[test]
+    fn valuation_components_recombine_into_valuation() {
+        let mut engine = EngineBuilder::new(11)
+            .with_resources(Allies, 7)
+            .with_resources(Empires, 3)
+            .with_nation(France, AtWar(2))
+            .build();
+        engine.state.state_of_war.get_mut(&Empires).unwrap().vp = 2;
+
+        let components = engine.state.valuation_components();
+        let (allies, empires) = (components.allies.total(), components.empires.total());
+
+        assert_eq!(7.0, components.allies.resources);
+        assert_eq!(8.0, components.empires.victory_points);
+        assert_eq!(
+            (allies - empires) / (allies + empires),
+            components.valuation
+        );
+        assert_eq!(engine.state.valuation(), components.valuation);
+    }
+
+    #[test]
+    fn game_ends_cleanly_when_reaching_turn_limit() {
+        let mut engine = EngineBuilder::new(11)
+            .on_turn(14)
+            .with_resources(Allies, 4)
+            .with_resources(Empires, 2)
+            .build();
+
+        engine.new_turn();
+
+        assert_eq!(15, engine.state.current_turn);
+        assert!(engine.game_ends());
+        assert!(format!("{}", engine.state).starts_with("Turn: 15 (end of war)"));
+        assert_eq!(Allies, engine.state.summary().winner);
+        assert_eq!(GameResult::Draw, engine.state.result());
+        assert_eq!(
+            "Draw at turn limit (VP 0 vs 0)",
+            engine.state.explain_winner()
+        );
+    }
+
+    #[test]
+    fn capped_game_ends_after_its_last_turn_and_is_scored_on_vp() {
+        let mut engine = EngineBuilder::new(11)
+            .with_config(GameConfig {
+                max_turns: 3,
+                ..GameConfig::default()
+            })
+            .on_turn(3)
+            .with_resources(Allies, 4)
+            .build();
+        engine.state.state_of_war.get_mut(&Empires).unwrap().vp = 2;
+
+        assert!(!engine.game_ends());
+        engine.new_turn();
+
+        assert_eq!(4, engine.state.current_turn);
+        assert!(engine.game_ends());
+        assert_eq!(1915, engine.state.current_year());
+        assert_eq!(Empires, engine.state.winner());
+    }
+
+    #[test]
+    fn game_without_nations_yields_no_resources_nor_nations() {
+        let mut engine = EngineBuilder::new(11).build();
+        engine.state.nations.clear();
+
+        engine.collect_resources();
+
+        assert_eq!(0, engine.state.resources_for(&Allies));
+        assert_eq!(0, engine.state.resources_for(&Empires));
+        assert!(engine.state.all_nations_at_war(Allies).is_empty());
+        assert!(engine.state.neighbours(&France).is_empty());
+        assert_eq!(0, engine.state.breakdown_level(&France));
+        assert_eq!(0.0, engine.state.valuation());
+        assert_eq!(NationNotAtWar(France), engine.apply_hits(&France, 1));
+    }
+
     #[test]
     fn breakdown_returns_hits_lost() {
         let mut engine = EngineBuilder::new(11).with_nation(France, AtWar(4)).build();
@@ -656,6 +2004,42 @@ mod game_state_tests {
         );
     }
 
+    #[test]
+    fn technology_is_maxed_given_side_reached_last_level_of_its_track() {
+        let engine = EngineBuilder::new(11)
+            .with_technologies(
+                Allies,
+                crate::Technologies {
+                    attack: 4,
+                    defense: 3,
+                    ..ZERO_TECHNOLOGIES
+                },
+            )
+            .build();
+
+        assert!(engine.state.is_tech_maxed(&Allies, Attack));
+        assert!(engine.state.is_tech_maxed(&Allies, Defense));
+        assert!(!engine.state.is_tech_maxed(&Allies, Artillery));
+        assert!(!engine.state.is_tech_maxed(&Empires, Defense));
+    }
+
+    #[test]
+    fn technology_is_not_maxed_below_last_level_of_its_track() {
+        let engine = EngineBuilder::new(11)
+            .with_technologies(
+                Allies,
+                crate::Technologies {
+                    attack: 3,
+                    defense: 2,
+                    ..ZERO_TECHNOLOGIES
+                },
+            )
+            .build();
+
+        assert!(!engine.state.is_tech_maxed(&Allies, Attack));
+        assert!(!engine.state.is_tech_maxed(&Allies, Defense));
+    }
+
     #[test]
     fn can_list_all_neighbouring_nations_one_can_attack() {
         let engine = EngineBuilder::new(11).build();
@@ -664,6 +2048,87 @@ mod game_state_tests {
         assert_eq!(vec![&France, &Russia], engine.state.neighbours(&Germany));
     }
 
+    #[test]
+    fn nations_of_side_only_lists_nations_of_that_side() {
+        let engine = EngineBuilder::new(11).build();
+
+        let allies = engine.state.nations_of(Allies);
+
+        for nation in [
+            France,
+            Russia,
+            Serbia,
+            Italy,
+            Romania,
+            Greece,
+            Egypt,
+            FrenchAfrica,
+        ] {
+            assert!(allies.contains(&nation), "{} should be an ally", nation);
+        }
+        for nation in [
+            Germany,
+            AustriaHungary,
+            OttomanEmpire,
+            Bulgaria,
+            GermanAfrica,
+        ] {
+            assert!(
+                !allies.contains(&nation),
+                "{} should not be an ally",
+                nation
+            );
+        }
+        assert!(allies
+            .iter()
+            .all(|nation| engine.state.side_of(nation) == Allies));
+    }
+
+    #[test]
+    fn neighbours_match_adjacent_nations_at_war() {
+        let engine = EngineBuilder::new(11)
+            .with_nation(Italy, AtWar(5))
+            .with_nation(Bulgaria, AtPeace)
+            .build();
+
+        for source in Nation::values() {
+            let expected: Vec<&Nation> = Nation::values()
+                .filter(|n| source.adjacent_to(n) && engine.state.is_at_war(n))
+                .collect();
+            assert_eq!(expected, engine.state.neighbours(source));
+        }
+    }
+
+    #[test]
+    fn repeated_neighbours_queries_yield_same_result() {
+        let engine = EngineBuilder::new(11).build();
+
+        let first = engine.state.neighbours(&Russia);
+        for _ in 0..1000 {
+            assert_eq!(first, engine.state.neighbours(&Russia));
+        }
+    }
+
+    #[test]
+    fn rolls_stay_within_configured_die_faces() {
+        let mut state = GameState::new(12);
+        state.config.die_faces = 10;
+
+        let rolls: Vec<u8> = (0..100).map(|_| state.roll()).collect();
+
+        assert!(rolls.iter().all(|die| (1..=10).contains(die)));
+        assert!(rolls.iter().any(|die| *die > 6));
+    }
+
+    #[test]
+    fn modified_roll_is_capped_by_die_faces() {
+        let mut state = GameState::new(12);
+        state.config.die_faces = 10;
+
+        assert_eq!(10, state.modified_roll(20));
+        assert_eq!(1, state.modified_roll(-20));
+    }
+
     #[test]
     fn no_change_does_not_modify_state() {
         let mut state = GameState::new(12);
@@ -706,4 +2171,133 @@ mod game_state_tests {
         assert_eq!(10, state.resources_for(&Allies));
         assert_eq!(11, state.resources_for(&Empires));
     }
+
+    #[test]
+    fn inverted_change_negates_resources_in_reverse_order() {
+        let change = StateChange::MoreChanges(vec![
+            StateChange::ChangeResources {
+                side: Allies,
+                pr: -4,
+            },
+            StateChange::ChangeResources {
+                side: Empires,
+                pr: 2,
+            },
+        ]);
+
+        assert_eq!(
+            StateChange::MoreChanges(vec![
+                StateChange::ChangeResources {
+                    side: Empires,
+                    pr: -2,
+                },
+                StateChange::ChangeResources {
+                    side: Allies,
+                    pr: 4,
+                },
+            ]),
+            change.invert()
+        );
+        assert_eq!(StateChange::NoChange, StateChange::NoChange.invert());
+    }
+
+    #[test]
+    fn display_lists_nations_and_sides_in_canonical_order() {
+        let state = GameState::new(14);
+        let display = state.to_string();
+
+        let positions: Vec<usize> = Nation::values()
+            .filter(|nation| state.nations.contains_key(nation))
+            .map(|nation| display.find(&format!("\t{}: ", nation)).unwrap())
+            .collect();
+        let mut sorted = positions.clone();
+        sorted.sort();
+
+        assert_eq!(sorted, positions);
+        assert!(display.find("\tAllies:").unwrap() < display.find("\tEmpires:").unwrap());
+    }
+
+    #[test]
+    fn phases_expect_input_of_their_kind() {
+        let expected = [
+            (Phase::Initiative(Allies), InputKind::Bid),
+            (Phase::Initiative(Empires), InputKind::Bid),
+            (Phase::DrawEvents, InputKind::None),
+            (Phase::CollectResources, InputKind::None),
+            (Phase::ImproveTechnologies(Allies), InputKind::TechSelection),
+            (
+                Phase::LaunchOffensives(Empires),
+                InputKind::OffensiveDeclaration,
+            ),
+            (Phase::Reinforcements(Allies), InputKind::Reinforcement),
+            (Phase::UBoot, InputKind::SeaBonus),
+            (Phase::Blockade, InputKind::SeaBonus),
+            (Phase::NewTurn, InputKind::None),
+        ];
+
+        for (phase, kind) in expected {
+            assert_eq!(kind, phase.expected_input(), "{:?}", phase);
+        }
+    }
+
+    #[test]
+    fn weakest_nation_tie_is_broken_by_canonical_order() {
+        let engine = EngineBuilder::new(14)
+            .with_nation(Serbia, AtWar(2))
+            .with_nation(Egypt, AtWar(2))
+            .with_nation(FrenchAfrica, AtWar(3))
+            .build();
+
+        assert_eq!(Some(Egypt), engine.state.weakest_nation(Allies));
+    }
+
+    #[test]
+    fn higher_bidder_wins_initiative_without_rolling() {
+        let state = GameState::new(14);
+
+        assert_eq!(Allies, state.would_win_initiative(6, 0));
+        assert_eq!(Empires, state.would_win_initiative(1, 4));
+    }
+
+    #[test]
+    fn state_is_identical_after_json_round_trip() {
+        let mut state = GameState::new(14);
+        state.draw_events();
+        state.roll();
+
+        let json = state.to_json();
+
+        assert_eq!(Ok(state.clone()), GameState::from_json(&json));
+        let mut reloaded = GameState::from_json(&json).unwrap();
+        assert_eq!(state.roll(), reloaded.roll());
+    }
+
+    #[test]
+    fn active_events_keep_their_deactivation_after_json_round_trip() {
+        let mut state = GameState::new(14);
+        state.activate(ActiveEvent {
+            event: ALL_EVENTS[26].clone(),
+            deactivation: Deactivation::AfterTurn(2),
+            layer: None,
+        });
+
+        let mut reloaded = GameState::from_json(&state.to_json()).unwrap();
+        reloaded.current_turn = 2;
+        reloaded.sweep_active_events();
+
+        assert_eq!(vec![&ALL_EVENTS[26]], reloaded.active_events());
+    }
+
+    #[test]
+    fn event_weights_survive_json_round_trip_and_apply_to_later_years() {
+        let mut state = GameState::new(14);
+        let reweighted = ALL_EVENTS.iter().find(|e| e.year == 1915).unwrap();
+        state.set_event_weight(reweighted.event_id, 200);
+
+        let mut reloaded = GameState::from_json(&state.to_json()).unwrap();
+        reloaded.new_year(1914, 1915);
+
+        assert!(reloaded.can_draw_event(reweighted));
+        assert_eq!(vec![reweighted.event_id], reloaded.peek_next_draw(1));
+    }
 }
@@ -4,7 +4,11 @@ use std::{
     fmt::{Display, Formatter},
 };
 
-#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash)]
+use serde::{Deserialize, Serialize};
+
+use crate::side::Side;
+
+#[derive(Eq, PartialEq, Clone, Copy, Debug, Hash, Serialize, Deserialize)]
 pub enum TechnologyType {
     Attack,
     Defense,
@@ -50,7 +54,7 @@ impl Display for TechnologyType {
 /// Current technologies of a side
 /// Note these numbers are not the actual technology levels but 1-based indices into the
 /// side's technology list (see EMPIRE_TECHNOLOGIES and ALLIES_TECHNOLOGIES).
-#[derive(Eq, PartialEq, Clone, Debug)]
+#[derive(Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct Technologies {
     pub attack: u8,
     pub defense: u8,
@@ -133,6 +137,124 @@ pub struct Technology {
     pub min_dice_unlock: u8,
 }
 
+/// All the levels of one category of a side's technology track
+#[cfg(test)]
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct TechRow {
+    pub category: TechnologyType,
+    pub levels: Vec<Technology>,
+}
+
+impl Technology {
+    /// The full technology tree of the given side, independent of the current state of the game
+    #[cfg(test)]
+    pub fn tree(side: &Side) -> Vec<TechRow> {
+        technologies_track(side)
+            .iter()
+            .zip(all_technology_types())
+            .map(|(row, category)| TechRow {
+                category,
+                levels: row.iter().filter_map(|tech| *tech).collect(),
+            })
+            .collect()
+    }
+}
+
+/// The technology track of the given side
+pub(crate) fn technologies_track(side: &Side) -> &'static [[Option<Technology>; 4]; 4] {
+    match side {
+        Side::Allies => &ALLIES_TECHNOLOGIES,
+        Side::Empires => &EMPIRE_TECHNOLOGIES,
+    }
+}
+
+/// Inconsistencies in a technology table
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub enum TechTableError {
+    /// A technology is listed in another category's row
+    WrongCategory(TechnologyType, &'static str),
+    /// A technology follows an empty slot
+    Gap(TechnologyType, usize),
+    /// A technology's level is not its position in the row: levels start at 1 and
+    /// follow each other without gaps
+    LevelNotIncreasing(TechnologyType, &'static str),
+    /// A technology is available before the previous one
+    DateDecreasing(TechnologyType, &'static str),
+}
+
+impl Display for TechTableError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TechTableError::WrongCategory(category, name) => {
+                write!(f, "{} is not a {} technology", name, category)
+            }
+            TechTableError::Gap(category, index) => {
+                write!(f, "{} technologies have a gap at {}", category, index)
+            }
+            TechTableError::LevelNotIncreasing(category, name) => {
+                write!(
+                    f,
+                    "{} level of {} does not follow the previous one",
+                    category, name
+                )
+            }
+            TechTableError::DateDecreasing(category, name) => {
+                write!(f, "{} date of {} is before previous one", category, name)
+            }
+        }
+    }
+}
+
+/// Check both sides' technology tables are consistent
+pub fn validate_tech_tables() -> Result<(), TechTableError> {
+    validate_tech_table(&ALLIES_TECHNOLOGIES)?;
+    validate_tech_table(&EMPIRE_TECHNOLOGIES)
+}
+
+/// Check each row of the table lists technologies of its category without gaps, with
+/// levels 1, 2, 3... and dates in chronological order
+pub fn validate_tech_table(table: &[[Option<Technology>; 4]; 4]) -> Result<(), TechTableError> {
+    for (row, category) in table.iter().zip(all_technology_types()) {
+        let mut previous: Option<&Technology> = None;
+        let mut ended = false;
+        for (index, slot) in row.iter().enumerate() {
+            let Some(tech) = slot else {
+                ended = true;
+                continue;
+            };
+            if ended {
+                return Err(TechTableError::Gap(category, index));
+            }
+            if tech.category != category {
+                return Err(TechTableError::WrongCategory(category, tech.name));
+            }
+            if tech.level != index as u8 + 1 {
+                return Err(TechTableError::LevelNotIncreasing(category, tech.name));
+            }
+            if previous.is_some_and(|p| tech.date < p.date) {
+                return Err(TechTableError::DateDecreasing(category, tech.name));
+            }
+            previous = Some(tech);
+        }
+    }
+    Ok(())
+}
+
+/// Defense level of the "Trench warfare" technology, the same for both sides
+pub const TRENCH_WARFARE_LEVEL: u8 = 2;
+
+/// Extra defense given by trench warfare once fronts have settled, i.e. from the year
+/// following the one the technology was reached
+pub const TRENCH_WARFARE_BONUS: u8 = 1;
+
+/// Number of levels a side can reach in the given category of its technology track
+pub fn tech_limit(technologies: &[[Option<Technology>; 4]; 4], category: &TechnologyType) -> u8 {
+    technologies[category.index()]
+        .iter()
+        .filter(|tech| tech.is_some())
+        .count() as u8
+}
+
 pub const EMPIRE_TECHNOLOGIES: [[Option<Technology>; 4]; 4] = [
     // Attack
     [
@@ -226,14 +348,14 @@ pub const EMPIRE_TECHNOLOGIES: [[Option<Technology>; 4]; 4] = [
         }),
         Some(Technology {
             category: TechnologyType::Air,
-            level: 5,
+            level: 2,
             name: "Jagdstaffeln",
             date: 1916,
             min_dice_unlock: 5,
         }),
         Some(Technology {
             category: TechnologyType::Air,
-            level: 6,
+            level: 3,
             name: "Fokker D.VII",
             date: 1918,
             min_dice_unlock: 5,
@@ -335,24 +457,121 @@ pub const ALLIES_TECHNOLOGIES: [[Option<Technology>; 4]; 4] = [
         }),
         Some(Technology {
             category: TechnologyType::Air,
-            level: 4,
+            level: 2,
             name: "Nieuport 11",
             date: 1916,
             min_dice_unlock: 5,
         }),
         Some(Technology {
             category: TechnologyType::Air,
-            level: 5,
+            level: 3,
             name: "Spad",
             date: 1917,
             min_dice_unlock: 5,
         }),
         Some(Technology {
             category: TechnologyType::Air,
-            level: 7,
+            level: 4,
             name: "Air division",
             date: 1918,
             min_dice_unlock: 6,
         }),
     ],
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn technology_tables_are_consistent() {
+        assert_eq!(Ok(()), validate_tech_tables());
+    }
+
+    #[test]
+    fn validation_reports_broken_technology_tables() {
+        let mut gap = ALLIES_TECHNOLOGIES;
+        gap[TechnologyType::Defense.index()][1] = None;
+        let mut levels = ALLIES_TECHNOLOGIES;
+        levels[TechnologyType::Attack.index()].swap(1, 2);
+        let mut dates = EMPIRE_TECHNOLOGIES;
+        dates[TechnologyType::Artillery.index()][2]
+            .as_mut()
+            .unwrap()
+            .date = 1914;
+        let mut skipped = ALLIES_TECHNOLOGIES;
+        skipped[TechnologyType::Attack.index()][1]
+            .as_mut()
+            .unwrap()
+            .level = 3;
+        let mut category = EMPIRE_TECHNOLOGIES;
+        category[TechnologyType::Air.index()][0] = category[TechnologyType::Attack.index()][0];
+
+        assert_eq!(
+            Err(TechTableError::Gap(TechnologyType::Defense, 2)),
+            validate_tech_table(&gap)
+        );
+        assert_eq!(
+            Err(TechTableError::LevelNotIncreasing(
+                TechnologyType::Attack,
+                "English Tanks Mark"
+            )),
+            validate_tech_table(&levels)
+        );
+        assert_eq!(
+            Err(TechTableError::LevelNotIncreasing(
+                TechnologyType::Attack,
+                skipped[TechnologyType::Attack.index()][1].unwrap().name
+            )),
+            validate_tech_table(&skipped)
+        );
+        assert_eq!(
+            Err(TechTableError::DateDecreasing(
+                TechnologyType::Artillery,
+                "Bruchmüller"
+            )),
+            validate_tech_table(&dates)
+        );
+        assert_eq!(
+            Err(TechTableError::WrongCategory(
+                TechnologyType::Air,
+                "Combat Gas"
+            )),
+            validate_tech_table(&category)
+        );
+    }
+
+    #[test]
+    fn allies_tree_lists_technologies_per_category_and_level() {
+        let tree = Technology::tree(&Side::Allies);
+
+        let names = |category: TechnologyType| {
+            tree.iter()
+                .find(|row| row.category == category)
+                .unwrap()
+                .levels
+                .iter()
+                .map(|tech| (tech.name, tech.date))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(4, tree.len());
+        assert_eq!(
+            vec![
+                ("Combat Gas", 1915),
+                ("New Tactics", 1916),
+                ("English Tanks Mark", 1917),
+                ("French Tanks Renault FT", 1918)
+            ],
+            names(TechnologyType::Attack)
+        );
+        assert_eq!(
+            vec![
+                ("Machine guns", 1914),
+                ("Trench warfare", 1915),
+                ("Bunkers", 1916)
+            ],
+            names(TechnologyType::Defense)
+        );
+    }
+}
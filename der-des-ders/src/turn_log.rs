@@ -0,0 +1,186 @@
+#[cfg(test)]
+use std::{cell::RefCell, collections::VecDeque, rc::Rc};
+
+use crate::{action_log::LoggedAction, GameState, Input, StateChange};
+#[cfg(test)]
+use crate::{action_log::LoggedInputs, run_turn, GameEngine, Players};
+
+/// What happened during one turn
+#[derive(Eq, PartialEq, Clone, Debug)]
+pub struct TurnRecord {
+    pub turn: u8,
+    /// Ids of the events drawn during the turn
+    pub events: Vec<u8>,
+    /// Inputs read from players during the turn, in order
+    pub inputs: Vec<Input>,
+    /// Changes applied with `GameEngine::apply_change` during the turn
+    pub changes: Vec<StateChange>,
+}
+
+/// A game's history, recorded at each turn boundary as the inputs read and changes
+/// applied during the turn rather than full states: replaying the inputs from the
+/// state the game started in, its seed included, rebuilds any turn's end state.
+/// As the action log, the turn log is only kept for the actual game and is not
+/// carried over when the engine is cloned.
+#[derive(Debug, Default)]
+pub struct TurnLog {
+    /// State the game started in, `None` for the log of a clone
+    initial: Option<GameState>,
+    /// Length of the engine's action log when the turn being played started
+    turn_start: usize,
+    turns: Vec<TurnRecord>,
+}
+
+impl Clone for TurnLog {
+    fn clone(&self) -> Self {
+        TurnLog::default()
+    }
+}
+
+impl TurnLog {
+    pub fn new(initial: &GameState) -> Self {
+        TurnLog {
+            initial: Some(initial.clone()),
+            turn_start: 0,
+            turns: Vec::new(),
+        }
+    }
+
+    /// Record the turn which just ended, `state` being the state at the start of the
+    /// next turn and `actions` the whole action log of the game
+    pub fn end_turn(
+        &mut self,
+        state: &GameState,
+        actions: &[LoggedAction],
+        changes: Vec<StateChange>,
+    ) {
+        if self.initial.is_none() {
+            return;
+        }
+        self.turns.push(TurnRecord {
+            turn: state.current_turn - 1,
+            events: state
+                .events_drawn_this_turn
+                .iter()
+                .map(|event| event.event_id)
+                .collect(),
+            inputs: actions[self.turn_start..]
+                .iter()
+                .filter_map(|action| match action {
+                    LoggedAction::Input(input) => Some(input.clone()),
+                    LoggedAction::Roll(_) => None,
+                })
+                .collect(),
+            changes,
+        });
+        self.turn_start = actions.len();
+    }
+
+    #[cfg(test)]
+    pub fn turns(&self) -> &[TurnRecord] {
+        &self.turns
+    }
+
+    /// State at the end of the given turn, replayed from the state the game started in,
+    /// or `None` if the turn was not recorded.
+    /// Turns are replayed from their start: a game loaded in the middle of a turn does
+    /// not replay the same way.
+    #[cfg(test)]
+    pub fn replay(&self, turn: u8) -> Option<GameState> {
+        let initial = self.initial.as_ref()?;
+        if !self.turns.iter().any(|record| record.turn == turn) {
+            return None;
+        }
+        let inputs: VecDeque<Input> = self
+            .turns
+            .iter()
+            .take_while(|record| record.turn <= turn)
+            .flat_map(|record| record.inputs.iter().cloned())
+            .collect();
+        let inputs = Rc::new(RefCell::new(inputs));
+        let mut players = Players {
+            allies_player: Box::new(LoggedInputs(inputs.clone())),
+            empires_player: Box::new(LoggedInputs(inputs)),
+        };
+        let mut engine = GameEngine::from_state(initial.clone());
+        while engine.state.current_turn <= turn && !engine.game_ends() {
+            run_turn(&mut players, &mut engine);
+        }
+        Some(engine.state)
+    }
+}
+
+#[cfg(test)]
+mod turn_log_tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::{
+        action_log::LoggedAction, fixtures::EngineBuilder, minimax::Robot, run_turn, Players,
+        Side::*,
+    };
+
+    #[test]
+    fn replaying_the_log_reproduces_the_game_at_each_turn_boundary() {
+        let mut engine = EngineBuilder::new(42).build();
+        let fingerprints = Rc::new(RefCell::new(Vec::new()));
+        let recorded = fingerprints.clone();
+        engine.on_turn_end(move |state| recorded.borrow_mut().push(state.fingerprint()));
+        let mut players = Players {
+            allies_player: Box::new(Robot::new(Allies, 1)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+
+        for _ in 0..4 {
+            run_turn(&mut players, &mut engine);
+        }
+
+        let log = engine.turn_log();
+        assert_eq!(4, log.turns().len());
+        for (record, fingerprint) in log.turns().iter().zip(fingerprints.borrow().iter()) {
+            assert_eq!(
+                *fingerprint,
+                log.replay(record.turn).unwrap().fingerprint(),
+                "turn {}",
+                record.turn
+            );
+        }
+        assert_eq!(None, log.replay(5));
+    }
+
+    #[test]
+    fn turns_record_the_inputs_read_during_the_turn() {
+        let mut engine = EngineBuilder::new(42).build();
+        let mut players = Players {
+            allies_player: Box::new(Robot::new(Allies, 1)),
+            empires_player: Box::new(Robot::new(Empires, 1)),
+        };
+
+        run_turn(&mut players, &mut engine);
+        run_turn(&mut players, &mut engine);
+
+        let inputs: Vec<_> = engine
+            .turn_log()
+            .turns()
+            .iter()
+            .flat_map(|record| record.inputs.iter().cloned())
+            .collect();
+        let logged: Vec<_> = engine
+            .replay_log()
+            .into_iter()
+            .filter_map(|action| match action {
+                LoggedAction::Input(input) => Some(input),
+                LoggedAction::Roll(_) => None,
+            })
+            .collect();
+        assert_eq!(logged, inputs);
+        assert_eq!(
+            vec![1, 2],
+            engine
+                .turn_log()
+                .turns()
+                .iter()
+                .map(|record| record.turn)
+                .collect::<Vec<_>>()
+        );
+    }
+}